@@ -1,3 +1,5 @@
+// Módulo ArbolSumadoresSegmentacion generado con RustHDL
+// Crate: ArbolSumadoresSegmentacion v0.1.0
 
 
 module ArbolSumadoresSegmentacion(inputs$0,inputs$1,inputs$2,inputs$3,inputs$4,inputs$5,inputs$6,inputs$7,result,clk,rstn);
@@ -16,6 +18,18 @@ module ArbolSumadoresSegmentacion(inputs$0,inputs$1,inputs$2,inputs$3,inputs$4,i
     input wire  rstn;
     
     // Stub signals
+    reg signed [7:0] sumador1$a;
+    reg signed [7:0] sumador1$b;
+    wire signed [8:0] sumador1$sum;
+    reg signed [7:0] sumador2$a;
+    reg signed [7:0] sumador2$b;
+    wire signed [8:0] sumador2$sum;
+    reg signed [7:0] sumador3$a;
+    reg signed [7:0] sumador3$b;
+    wire signed [8:0] sumador3$sum;
+    reg signed [7:0] sumador4$a;
+    reg signed [7:0] sumador4$b;
+    wire signed [8:0] sumador4$sum;
     reg signed [8:0] sum1$d;
     wire signed [8:0] sum1$q;
     reg  sum1$clock;
@@ -39,37 +53,57 @@ module ArbolSumadoresSegmentacion(inputs$0,inputs$1,inputs$2,inputs$3,inputs$4,i
     reg  final_sum$clock;
     
     // Sub module instances
-    top$sum1 sum1(
+    ArbolSumadoresSegmentacion$sumador1 sumador1(
+        .a(sumador1$a),
+        .b(sumador1$b),
+        .sum(sumador1$sum)
+    );
+    ArbolSumadoresSegmentacion$sumador2 sumador2(
+        .a(sumador2$a),
+        .b(sumador2$b),
+        .sum(sumador2$sum)
+    );
+    ArbolSumadoresSegmentacion$sumador3 sumador3(
+        .a(sumador3$a),
+        .b(sumador3$b),
+        .sum(sumador3$sum)
+    );
+    ArbolSumadoresSegmentacion$sumador4 sumador4(
+        .a(sumador4$a),
+        .b(sumador4$b),
+        .sum(sumador4$sum)
+    );
+    ArbolSumadoresSegmentacion$sum1 sum1(
         .d(sum1$d),
         .q(sum1$q),
         .clock(sum1$clock)
     );
-    top$sum2 sum2(
+    ArbolSumadoresSegmentacion$sum2 sum2(
         .d(sum2$d),
         .q(sum2$q),
         .clock(sum2$clock)
     );
-    top$sum3 sum3(
+    ArbolSumadoresSegmentacion$sum3 sum3(
         .d(sum3$d),
         .q(sum3$q),
         .clock(sum3$clock)
     );
-    top$sum4 sum4(
+    ArbolSumadoresSegmentacion$sum4 sum4(
         .d(sum4$d),
         .q(sum4$q),
         .clock(sum4$clock)
     );
-    top$sum1_1 sum1_1(
+    ArbolSumadoresSegmentacion$sum1_1 sum1_1(
         .d(sum1_1$d),
         .q(sum1_1$q),
         .clock(sum1_1$clock)
     );
-    top$sum1_2 sum1_2(
+    ArbolSumadoresSegmentacion$sum1_2 sum1_2(
         .d(sum1_2$d),
         .q(sum1_2$q),
         .clock(sum1_2$clock)
     );
-    top$final_sum final_sum(
+    ArbolSumadoresSegmentacion$final_sum final_sum(
         .d(final_sum$d),
         .q(final_sum$q),
         .clock(final_sum$clock)
@@ -84,6 +118,14 @@ module ArbolSumadoresSegmentacion(inputs$0,inputs$1,inputs$2,inputs$3,inputs$4,i
         sum1_1$clock = clk;
         sum1_2$clock = clk;
         final_sum$clock = clk;
+        sumador1$a = inputs$0;
+        sumador1$b = inputs$1;
+        sumador2$a = inputs$2;
+        sumador2$b = inputs$3;
+        sumador3$a = inputs$4;
+        sumador3$b = inputs$5;
+        sumador4$a = inputs$6;
+        sumador4$b = inputs$7;
         if (~rstn) begin
             sum1$d = 32'h0;
             sum2$d = 32'h0;
@@ -95,10 +137,10 @@ module ArbolSumadoresSegmentacion(inputs$0,inputs$1,inputs$2,inputs$3,inputs$4,i
             result = 32'h0;
         end
         else begin
-            sum1$d = $signed(inputs$0) + $signed(inputs$1);
-            sum2$d = $signed(inputs$2) + $signed(inputs$3);
-            sum3$d = $signed(inputs$4) + $signed(inputs$5);
-            sum4$d = $signed(inputs$6) + $signed(inputs$7);
+            sum1$d = sumador1$sum;
+            sum2$d = sumador2$sum;
+            sum3$d = sumador3$sum;
+            sum4$d = sumador4$sum;
             sum1_1$d = $signed(sum1$q) + $signed(sum2$q);
             sum1_2$d = $signed(sum3$q) + $signed(sum4$q);
             final_sum$d = $signed(sum1_1$q) + $signed(sum1_2$q);
@@ -106,10 +148,10 @@ module ArbolSumadoresSegmentacion(inputs$0,inputs$1,inputs$2,inputs$3,inputs$4,i
         end
     end
     
-endmodule // top
+endmodule // ArbolSumadoresSegmentacion
 
 
-module top$final_sum(d,q,clock);
+module ArbolSumadoresSegmentacion$final_sum(d,q,clock);
     
     // Module arguments
     input wire signed [10:0] d;
@@ -125,10 +167,10 @@ module top$final_sum(d,q,clock);
        q <= d;
     end
           
-endmodule // top$final_sum
+endmodule // ArbolSumadoresSegmentacion$final_sum
 
 
-module top$sum1(d,q,clock);
+module ArbolSumadoresSegmentacion$sum1(d,q,clock);
     
     // Module arguments
     input wire signed [8:0] d;
@@ -144,10 +186,10 @@ module top$sum1(d,q,clock);
        q <= d;
     end
           
-endmodule // top$sum1
+endmodule // ArbolSumadoresSegmentacion$sum1
 
 
-module top$sum1_1(d,q,clock);
+module ArbolSumadoresSegmentacion$sum1_1(d,q,clock);
     
     // Module arguments
     input wire signed [9:0] d;
@@ -163,10 +205,10 @@ module top$sum1_1(d,q,clock);
        q <= d;
     end
           
-endmodule // top$sum1_1
+endmodule // ArbolSumadoresSegmentacion$sum1_1
 
 
-module top$sum1_2(d,q,clock);
+module ArbolSumadoresSegmentacion$sum1_2(d,q,clock);
     
     // Module arguments
     input wire signed [9:0] d;
@@ -182,10 +224,10 @@ module top$sum1_2(d,q,clock);
        q <= d;
     end
           
-endmodule // top$sum1_2
+endmodule // ArbolSumadoresSegmentacion$sum1_2
 
 
-module top$sum2(d,q,clock);
+module ArbolSumadoresSegmentacion$sum2(d,q,clock);
     
     // Module arguments
     input wire signed [8:0] d;
@@ -201,10 +243,10 @@ module top$sum2(d,q,clock);
        q <= d;
     end
           
-endmodule // top$sum2
+endmodule // ArbolSumadoresSegmentacion$sum2
 
 
-module top$sum3(d,q,clock);
+module ArbolSumadoresSegmentacion$sum3(d,q,clock);
     
     // Module arguments
     input wire signed [8:0] d;
@@ -220,10 +262,10 @@ module top$sum3(d,q,clock);
        q <= d;
     end
           
-endmodule // top$sum3
+endmodule // ArbolSumadoresSegmentacion$sum3
 
 
-module top$sum4(d,q,clock);
+module ArbolSumadoresSegmentacion$sum4(d,q,clock);
     
     // Module arguments
     input wire signed [8:0] d;
@@ -239,4 +281,64 @@ module top$sum4(d,q,clock);
        q <= d;
     end
           
-endmodule // top$sum4
+endmodule // ArbolSumadoresSegmentacion$sum4
+
+
+module ArbolSumadoresSegmentacion$sumador1(a,b,sum);
+    
+    // Module arguments
+    input wire signed [7:0] a;
+    input wire signed [7:0] b;
+    output reg signed [8:0] sum;
+    
+    // Update code
+    always @(*) begin
+        sum = $signed(a) + $signed(b);
+    end
+    
+endmodule // ArbolSumadoresSegmentacion$sumador1
+
+
+module ArbolSumadoresSegmentacion$sumador2(a,b,sum);
+    
+    // Module arguments
+    input wire signed [7:0] a;
+    input wire signed [7:0] b;
+    output reg signed [8:0] sum;
+    
+    // Update code
+    always @(*) begin
+        sum = $signed(a) + $signed(b);
+    end
+    
+endmodule // ArbolSumadoresSegmentacion$sumador2
+
+
+module ArbolSumadoresSegmentacion$sumador3(a,b,sum);
+    
+    // Module arguments
+    input wire signed [7:0] a;
+    input wire signed [7:0] b;
+    output reg signed [8:0] sum;
+    
+    // Update code
+    always @(*) begin
+        sum = $signed(a) + $signed(b);
+    end
+    
+endmodule // ArbolSumadoresSegmentacion$sumador3
+
+
+module ArbolSumadoresSegmentacion$sumador4(a,b,sum);
+    
+    // Module arguments
+    input wire signed [7:0] a;
+    input wire signed [7:0] b;
+    output reg signed [8:0] sum;
+    
+    // Update code
+    always @(*) begin
+        sum = $signed(a) + $signed(b);
+    end
+    
+endmodule // ArbolSumadoresSegmentacion$sumador4
@@ -0,0 +1,347 @@
+use std::ops::Add;
+
+use rust_hdl::prelude::*;
+// signed_bit_cast de RustHDL desborda la pila con el valor mínimo
+use tfg_hdl_common::{signed_bit_cast, PortValue};
+
+// Árbol de sumadores segmentado de N entradas de W bits, con log2(N) etapas
+// de registros. Es la misma estructura que ArbolSumadoresSegmentacion pero sin
+// fijar el número de entradas ni la anchura: AdderTree<16, 8>,
+// AdderTree<64, 8>...
+//
+// Como en ArbolSumadoresSegmentacion, los registros de la etapa s tienen
+// W + s bits y la salida W + log2(N). El Rust estable no deja escribir
+// Signed<{W + 1}>, así que las anchuras salen de una tabla (Widen, que lleva
+// de Signed<W> a Signed<W + 1>) y cada N tiene su propio tipo: una etapa de
+// N/2 sumas seguida del árbol de N/2 entradas con un bit más. AdderTree<8, W>
+// es AdderTree8<Signed<W>>, que lleva dentro un AdderTree4<Signed<W + 1>>, y
+// así hasta AdderTree1, que sólo pasa la suma a la salida. Con un N para el
+// que no hay árbol, o una W fuera de la tabla, AdderTree<N, W> no compila.
+//
+// El resultado sale log2(N) - 1 ciclos después del flanco que carga las
+// entradas. Cada etapa lleva al lado un bit de válido (valid_in a valid_out)
+// y todas se paran a la vez con enable a 0, que es lo que usa
+// AdderTreeStream; sin paradas, enable se deja a 1.
+pub type AdderTree<const N: usize, const W: usize> = <Inputs<N> as TreeOf<W>>::Tree;
+
+// N en el tipo, para elegir el árbol de AdderTree<N, W>
+pub struct Inputs<const N: usize>;
+
+pub trait TreeOf<const W: usize> {
+    type Tree: SumTree<Input = Signed<W>>;
+}
+
+// Lo que tienen en común los árboles de cualquier N, para usarlos desde
+// código genérico
+pub trait SumTree: Block + Default + Clone {
+    type Input: PortValue;
+    type Output: PortValue;
+    // Número de etapas de registros
+    const STAGES: usize;
+    fn inputs(&mut self) -> &mut [Signal<In, Self::Input>];
+    fn result(&self) -> &Signal<Out, Self::Output>;
+    fn valid_in(&mut self) -> &mut Signal<In, Bit>;
+    fn valid_out(&self) -> &Signal<Out, Bit>;
+    fn enable(&mut self) -> &mut Signal<In, Bit>;
+    fn clk(&mut self) -> &mut Signal<In, Clock>;
+    fn rstn(&mut self) -> &mut Signal<In, Bit>;
+}
+
+// Tabla de anchuras: la suma de dos Signed<W> cabe en Wider = Signed<W + 1>
+pub trait Widen: PortValue {
+    type Wider: PortValue + Add<Output = Self::Wider> + From<i64>;
+    fn widen(self) -> Self::Wider;
+}
+
+// Cada pareja de anchuras seguidas de la lista es una entrada de la tabla
+macro_rules! widen {
+    ($w:literal $v:literal $($resto:literal)*) => {
+        impl Widen for Signed<$w> {
+            type Wider = Signed<$v>;
+            fn widen(self) -> Signed<$v> {
+                signed_bit_cast::<$v, $w>(self)
+            }
+        }
+        widen!($v $($resto)*);
+    };
+    ($w:literal) => {};
+}
+
+widen!(
+    1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+    33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63 64
+);
+
+// Widen para los tipos que no tienen anchura fija. Empieza por
+// signed_bit_cast para que #[hdl_gen] también la traduzca a $signed(...)
+pub fn signed_bit_cast_widen<T: Widen>(x: T) -> T::Wider {
+    x.widen()
+}
+
+// Árbol de una entrada: la última etapa, sin registros
+#[derive(LogicBlock, Clone, Default)]
+pub struct AdderTree1<T: PortValue + From<i64>> {
+    pub inputs: [Signal<In, T>; 1],
+    pub result: Signal<Out, T>,
+    pub valid_in: Signal<In, Bit>,
+    pub valid_out: Signal<Out, Bit>,
+    pub enable: Signal<In, Bit>,
+    pub clk: Signal<In, Clock>,
+    pub rstn: Signal<In, Bit>,
+}
+
+impl<T: PortValue + From<i64>> Logic for AdderTree1<T> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        if !self.rstn.val() {
+            self.result.next = 0.into();
+            self.valid_out.next = false;
+        } else {
+            self.result.next = self.inputs[0].val();
+            self.valid_out.next = self.valid_in.val();
+        }
+    }
+}
+
+impl<T: PortValue + From<i64>> SumTree for AdderTree1<T> {
+    type Input = T;
+    type Output = T;
+    const STAGES: usize = 0;
+    fn inputs(&mut self) -> &mut [Signal<In, T>] {
+        &mut self.inputs
+    }
+    fn result(&self) -> &Signal<Out, T> {
+        &self.result
+    }
+    fn valid_in(&mut self) -> &mut Signal<In, Bit> {
+        &mut self.valid_in
+    }
+    fn valid_out(&self) -> &Signal<Out, Bit> {
+        &self.valid_out
+    }
+    fn enable(&mut self) -> &mut Signal<In, Bit> {
+        &mut self.enable
+    }
+    fn clk(&mut self) -> &mut Signal<In, Clock> {
+        &mut self.clk
+    }
+    fn rstn(&mut self) -> &mut Signal<In, Bit> {
+        &mut self.rstn
+    }
+}
+
+// Árbol de $n entradas: $n/2 sumas registradas y el árbol $resto, que es el
+// de $n/2 entradas. T: $cota es que T se puede ensanchar tantas veces como
+// etapas tiene el árbol
+macro_rules! adder_tree {
+    ($arbol:ident, $n:literal, $resto:ident, $cota:ident: $cota_resto:path) => {
+        pub trait $cota: Widen<Wider: $cota_resto> {}
+
+        impl<T: Widen<Wider: $cota_resto>> $cota for T {}
+
+        #[derive(LogicBlock, Clone)]
+        pub struct $arbol<T: $cota> {
+            pub inputs: [Signal<In, T>; $n],
+            pub result: Signal<Out, <$resto<T::Wider> as SumTree>::Output>,
+            pub valid_in: Signal<In, Bit>,
+            pub valid_out: Signal<Out, Bit>,
+            pub enable: Signal<In, Bit>,
+            pub clk: Signal<In, Clock>,
+            pub rstn: Signal<In, Bit>,
+            sumas: [DFF<T::Wider>; $n / 2],
+            valido: DFF<Bit>,
+            resto: $resto<T::Wider>,
+        }
+
+        impl<T: $cota> Default for $arbol<T> {
+            fn default() -> Self {
+                Self {
+                    inputs: std::array::from_fn(|_| Default::default()),
+                    result: Default::default(),
+                    valid_in: Default::default(),
+                    valid_out: Default::default(),
+                    enable: Default::default(),
+                    clk: Default::default(),
+                    rstn: Default::default(),
+                    sumas: std::array::from_fn(|_| Default::default()),
+                    valido: Default::default(),
+                    resto: Default::default(),
+                }
+            }
+        }
+
+        impl<T: $cota> Logic for $arbol<T> {
+            #[hdl_gen]
+            fn update(&mut self) {
+                self.resto.clk.next = self.clk.val();
+                self.resto.rstn.next = self.rstn.val();
+                self.resto.enable.next = self.enable.val();
+                self.valido.clock.next = self.clk.val();
+                self.resto.valid_in.next = self.valido.q.val();
+                for k in 0..($n / 2) {
+                    self.sumas[k].clock.next = self.clk.val();
+                    self.resto.inputs[k].next = self.sumas[k].q.val();
+                }
+
+                // Parado, todo se mantiene
+                self.valido.d.next = self.valido.q.val();
+                for k in 0..($n / 2) {
+                    self.sumas[k].d.next = self.sumas[k].q.val();
+                }
+                if !self.rstn.val() {
+                    self.valido.d.next = false;
+                    for k in 0..($n / 2) {
+                        self.sumas[k].d.next = 0.into();
+                    }
+                } else if self.enable.val() {
+                    self.valido.d.next = self.valid_in.val();
+                    for k in 0..($n / 2) {
+                        self.sumas[k].d.next = signed_bit_cast_widen(self.inputs[2 * k].val())
+                            + signed_bit_cast_widen(self.inputs[2 * k + 1].val());
+                    }
+                }
+                self.result.next = self.resto.result.val();
+                self.valid_out.next = self.resto.valid_out.val();
+            }
+        }
+
+        impl<T: $cota> SumTree for $arbol<T> {
+            type Input = T;
+            type Output = <$resto<T::Wider> as SumTree>::Output;
+            const STAGES: usize = 1 + <$resto<T::Wider> as SumTree>::STAGES;
+            fn inputs(&mut self) -> &mut [Signal<In, T>] {
+                &mut self.inputs
+            }
+            fn result(&self) -> &Signal<Out, Self::Output> {
+                &self.result
+            }
+        fn valid_in(&mut self) -> &mut Signal<In, Bit> {
+            &mut self.valid_in
+        }
+        fn valid_out(&self) -> &Signal<Out, Bit> {
+            &self.valid_out
+        }
+        fn enable(&mut self) -> &mut Signal<In, Bit> {
+            &mut self.enable
+        }
+            fn clk(&mut self) -> &mut Signal<In, Clock> {
+                &mut self.clk
+            }
+            fn rstn(&mut self) -> &mut Signal<In, Bit> {
+                &mut self.rstn
+            }
+        }
+
+        impl<const W: usize> TreeOf<W> for Inputs<$n>
+        where
+            Signed<W>: $cota,
+        {
+            type Tree = $arbol<Signed<W>>;
+        }
+    };
+}
+
+adder_tree!(AdderTree2, 2, AdderTree1, Widen1: PortValue);
+adder_tree!(AdderTree4, 4, AdderTree2, Widen2: Widen1);
+adder_tree!(AdderTree8, 8, AdderTree4, Widen3: Widen2);
+adder_tree!(AdderTree16, 16, AdderTree8, Widen4: Widen3);
+adder_tree!(AdderTree32, 32, AdderTree16, Widen5: Widen4);
+adder_tree!(AdderTree64, 64, AdderTree32, Widen6: Widen5);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tfg_hdl_common::{TestVector, TestVectors};
+
+    // Testbench de un árbol de N entradas: reset y después cada caso con su
+    // suma esperada
+    fn casos<T: SumTree + 'static>(modulo: &str, casos: &[Vec<i64>]) -> TestVectors<T> {
+        let mut tb = TestVectors::<T>::new(modulo).clock("clk", |x| x.clk()).latency(T::STAGES - 1);
+        let rstn = tb.input("rstn", |x| x.rstn());
+        let enable = tb.input("enable", |x| x.enable());
+        let valid_in = tb.input("valid_in", |x| x.valid_in());
+        let inputs: Vec<_> = (0..T::default().inputs().len())
+            .map(|i| tb.input(&format!("inputs${}", i), move |x| &mut x.inputs()[i]))
+            .collect();
+        let result = tb.output("result", |x| x.result());
+        let valid_out = tb.output("valid_out", |x| x.valid_out());
+
+        tb.push(TestVector::new("reset").set(rstn, 0).set(enable, 1).set(valid_in, 1));
+        tb.push(TestVector::new("fin del reset").set(rstn, 1));
+        for (n, valores) in casos.iter().enumerate() {
+            let mut vector = TestVector::new(&format!("caso {}", n))
+                .expect(result, valores.iter().sum())
+                .expect(valid_out, 1);
+            for (&input, &valor) in inputs.iter().zip(valores) {
+                vector = vector.set(input, valor);
+            }
+            tb.push(vector);
+        }
+        tb
+    }
+
+    // Casos extremos y valores distintos en cada entrada
+    fn valores(n: usize) -> Vec<Vec<i64>> {
+        vec![
+            vec![-128; n],
+            vec![127; n],
+            (0..n).map(|i| i as i64 - 3).collect(),
+            (0..n).map(|i| if i % 2 == 0 { 127 } else { -128 }).collect(),
+            (0..n).map(|i| (i as i64 * 37) % 256 - 128).collect(),
+            vec![0; n],
+        ]
+    }
+
+    fn comprobar<T: SumTree + Send + 'static>(modulo: &str) -> anyhow::Result<()> {
+        let mut uut = T::default();
+        uut.connect_all();
+        let tb = casos::<T>(modulo, &valores(uut.inputs().len()));
+
+        tb.verify(&uut, 10_000)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_arbol_de_8() -> anyhow::Result<()> {
+        comprobar::<AdderTree<8, 8>>("AdderTree8")
+    }
+
+    #[test]
+    fn test_arboles_de_2_16_y_64() -> anyhow::Result<()> {
+        assert_eq!(AdderTree::<64, 8>::STAGES, 6);
+        comprobar::<AdderTree<2, 8>>("AdderTree2")?;
+        comprobar::<AdderTree<16, 8>>("AdderTree16")?;
+        comprobar::<AdderTree<64, 8>>("AdderTree64")
+    }
+
+    #[test]
+    fn test_anchuras_por_etapa() {
+        // Cada etapa tiene un bit más que la anterior y la salida W + log2(N)
+        assert_eq!(<AdderTree<2, 8> as SumTree>::Output::BITS, 9);
+        assert_eq!(<AdderTree<16, 8> as SumTree>::Output::BITS, 12);
+        assert_eq!(<AdderTree<64, 8> as SumTree>::Output::BITS, 14);
+        assert_eq!(<AdderTree<4, 20> as SumTree>::Output::BITS, 22);
+    }
+
+    #[test]
+    fn test_verilog_del_arbol() {
+        let mut uut = AdderTree::<16, 8>::default();
+        uut.connect_all();
+        let codigo = tfg_hdl_common::generate_named_verilog(&uut, "AdderTree16").unwrap();
+        // La primera etapa suma las entradas y pasa las sumas al árbol de 8,
+        // y cada etapa tiene un bit más que la anterior
+        assert!(codigo.contains("sumas$0$d = $signed(inputs$0) + $signed(inputs$1);"), "{}", codigo);
+        assert!(codigo.contains("sumas$7$d = $signed(inputs$14) + $signed(inputs$15);"));
+        assert!(codigo.contains("resto$inputs$0 = sumas$0$q;"));
+        for bits in [8, 9, 10, 11] {
+            assert!(codigo.contains(&format!("wire signed [{bits}:0] sumas$0$q;")));
+        }
+        assert!(codigo.contains("output reg signed [11:0] result;"));
+        assert!(codigo.contains("result = resto$result;"));
+        assert!(codigo.contains("resto$enable = enable;"));
+        assert!(codigo.contains("resto$valid_in = valido$q;"));
+        // No sobra ningún registro: 8 + 4 + 2 + 1
+        let registros = codigo.lines().filter(|l| l.starts_with("module ") && l.contains("$sumas$"));
+        assert_eq!(registros.count(), 15);
+    }
+}
@@ -0,0 +1,334 @@
+use crate::adder_tree::*;
+use rust_hdl::prelude::*;
+use tfg_hdl_common::{PipelineHandshake, PortValue};
+
+// AdderTree con interfaz valid/ready (como AXI-Stream) para poder ponerlo
+// detrás de una FIFO sin perder muestras.
+//
+// Un vector de entradas entra en el flanco en el que valid_in y ready_in están
+// a 1, y una suma sale en el flanco en el que valid_out y ready_out están a 1.
+// Por dentro es el mismo AdderTree, con los W + s bits de cada etapa, y un
+// PipelineHandshake que entrega las sumas a un SkidBuffer y para todo el árbol
+// con su enable mientras el consumidor no las acepta.
+//
+// Sin esperas sale una suma por ciclo, log2(N) ciclos después del flanco que
+// carga las entradas (uno más que AdderTree, por el registro de salida del
+// SkidBuffer). Como con AdderTree, cada N tiene su propio tipo y
+// AdderTreeStream<N, W> no compila si no hay AdderTree<N, W>.
+pub type AdderTreeStream<const N: usize, const W: usize> = <Inputs<N> as StreamOf<W>>::Stream;
+
+pub trait StreamOf<const W: usize> {
+    type Stream: SumTreeStream<Input = Signed<W>>;
+}
+
+// Lo que tienen en común los árboles con handshake de cualquier N, para
+// usarlos desde código genérico
+pub trait SumTreeStream: Block + Default + Clone {
+    type Input: PortValue;
+    type Output: PortValue;
+    // Ciclos desde que se acepta un vector hasta que su suma está en la
+    // salida, si no hay esperas
+    const LATENCY: usize;
+    fn inputs(&mut self) -> &mut [Signal<In, Self::Input>];
+    fn valid_in(&mut self) -> &mut Signal<In, Bit>;
+    fn ready_in(&self) -> &Signal<Out, Bit>;
+    fn result(&self) -> &Signal<Out, Self::Output>;
+    fn valid_out(&self) -> &Signal<Out, Bit>;
+    fn ready_out(&mut self) -> &mut Signal<In, Bit>;
+    fn clk(&mut self) -> &mut Signal<In, Clock>;
+    fn rstn(&mut self) -> &mut Signal<In, Bit>;
+}
+
+// El árbol $arbol de $n entradas con handshake
+macro_rules! adder_tree_stream {
+    ($stream:ident, $n:literal, $arbol:ident, $cota:ident) => {
+        #[derive(LogicBlock, Clone)]
+        pub struct $stream<T: $cota> {
+            pub inputs: [Signal<In, T>; $n],
+            pub valid_in: Signal<In, Bit>,
+            pub ready_in: Signal<Out, Bit>,
+            pub result: Signal<Out, <$arbol<T> as SumTree>::Output>,
+            pub valid_out: Signal<Out, Bit>,
+            pub ready_out: Signal<In, Bit>,
+            pub clk: Signal<In, Clock>,
+            pub rstn: Signal<In, Bit>,
+            arbol: $arbol<T>,
+            control: PipelineHandshake<<$arbol<T> as SumTree>::Output>,
+        }
+
+        impl<T: $cota> Default for $stream<T> {
+            fn default() -> Self {
+                Self {
+                    inputs: std::array::from_fn(|_| Default::default()),
+                    valid_in: Default::default(),
+                    ready_in: Default::default(),
+                    result: Default::default(),
+                    valid_out: Default::default(),
+                    ready_out: Default::default(),
+                    clk: Default::default(),
+                    rstn: Default::default(),
+                    arbol: Default::default(),
+                    control: Default::default(),
+                }
+            }
+        }
+
+        impl<T: $cota> Logic for $stream<T> {
+            #[hdl_gen]
+            fn update(&mut self) {
+                self.arbol.clk.next = self.clk.val();
+                self.arbol.rstn.next = self.rstn.val();
+                self.control.clk.next = self.clk.val();
+                self.control.rst.next = !self.rstn.val();
+
+                for k in 0..$n {
+                    self.arbol.inputs[k].next = self.inputs[k].val();
+                }
+                self.arbol.valid_in.next = self.valid_in.val();
+                self.arbol.enable.next = self.control.enable.val();
+                self.ready_in.next = self.control.ready_in.val();
+
+                self.control.data.next = self.arbol.result.val();
+                self.control.valid.next = self.arbol.valid_out.val();
+                self.control.ready_out.next = self.ready_out.val();
+                self.result.next = self.control.data_out.val();
+                self.valid_out.next = self.control.valid_out.val();
+            }
+        }
+
+        impl<T: $cota> SumTreeStream for $stream<T> {
+            type Input = T;
+            type Output = <$arbol<T> as SumTree>::Output;
+            const LATENCY: usize = <$arbol<T> as SumTree>::STAGES;
+            fn inputs(&mut self) -> &mut [Signal<In, T>] {
+                &mut self.inputs
+            }
+            fn valid_in(&mut self) -> &mut Signal<In, Bit> {
+                &mut self.valid_in
+            }
+            fn ready_in(&self) -> &Signal<Out, Bit> {
+                &self.ready_in
+            }
+            fn result(&self) -> &Signal<Out, Self::Output> {
+                &self.result
+            }
+            fn valid_out(&self) -> &Signal<Out, Bit> {
+                &self.valid_out
+            }
+            fn ready_out(&mut self) -> &mut Signal<In, Bit> {
+                &mut self.ready_out
+            }
+            fn clk(&mut self) -> &mut Signal<In, Clock> {
+                &mut self.clk
+            }
+            fn rstn(&mut self) -> &mut Signal<In, Bit> {
+                &mut self.rstn
+            }
+        }
+
+        impl<const W: usize> StreamOf<W> for Inputs<$n>
+        where
+            Signed<W>: $cota,
+        {
+            type Stream = $stream<Signed<W>>;
+        }
+    };
+}
+
+adder_tree_stream!(AdderTreeStream2, 2, AdderTree2, Widen1);
+adder_tree_stream!(AdderTreeStream4, 4, AdderTree4, Widen2);
+adder_tree_stream!(AdderTreeStream8, 8, AdderTree8, Widen3);
+adder_tree_stream!(AdderTreeStream16, 16, AdderTree16, Widen4);
+adder_tree_stream!(AdderTreeStream32, 32, AdderTree32, Widen5);
+adder_tree_stream!(AdderTreeStream64, 64, AdderTree64, Widen6);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tfg_hdl_common::{StreamModel, TestVector, TestVectors};
+
+    type Arbol<const N: usize> = AdderTreeStream<N, 8>;
+
+    fn flanco<T: SumTreeStream>(ep: &mut Sim<T>, mut x: Box<T>) -> Result<Box<T>, SimError> {
+        x.clk().next = Clock { clk: false };
+        x = ep.wait(1, x)?;
+        x.clk().next = Clock { clk: true };
+        ep.wait(1, x)
+    }
+
+    // Productor y consumidor con esperas pseudoaleatorias (xorshift; con
+    // semilla 0 no hay esperas). Cada ciclo en el que valid_out está a 1 se
+    // compara result con la siguiente suma que falta por recibir, así que se
+    // detecta tanto una suma perdida o desordenada como una salida que cambia
+    // estando parada. Devuelve los ciclos que ha tardado
+    fn transmitir<T: SumTreeStream + Send + 'static>(casos: Vec<Vec<i64>>, semilla: u64) -> usize {
+        let mut uut = T::default();
+        uut.connect_all();
+        let ciclos = Arc::new(Mutex::new(0));
+        let contador = ciclos.clone();
+
+        let mut sim = Simulation::<T>::new();
+        sim.add_testbench(move |mut ep: Sim<T>| {
+            let mut x = ep.init()?;
+            let mut azar = semilla;
+            let mut espera = move |cada: u64| {
+                azar ^= azar << 13;
+                azar ^= azar >> 7;
+                azar ^= azar << 17;
+                semilla != 0 && azar.is_multiple_of(cada)
+            };
+
+            x.rstn().next = false;
+            x = flanco(&mut ep, x)?;
+            x = flanco(&mut ep, x)?;
+            x.rstn().next = true;
+
+            let (mut enviados, mut recibidos, mut n) = (0, 0, 0);
+            let mut pendiente = false;
+            while recibidos < casos.len() {
+                sim_assert!(ep, n < 100 * casos.len(), x);
+                // Una vez puesto valid_in no se quita hasta que se acepta
+                let valido = enviados < casos.len() && (pendiente || !espera(4));
+                x.valid_in().next = valido;
+                if valido {
+                    for (input, &valor) in x.inputs().iter_mut().zip(&casos[enviados]) {
+                        input.next = T::Input::from_i64(valor);
+                    }
+                }
+                let ready_out = !espera(3);
+                x.ready_out().next = ready_out;
+
+                // Lo que se transfiere en este flanco
+                pendiente = valido && !x.ready_in().val();
+                if valido && x.ready_in().val() {
+                    enviados += 1;
+                }
+                if x.valid_out().val() {
+                    let suma: i64 = casos[recibidos].iter().sum();
+                    sim_assert_eq!(ep, x.result().val().to_i64(), suma, x);
+                    if ready_out {
+                        recibidos += 1;
+                    }
+                }
+                x = flanco(&mut ep, x)?;
+                n += 1;
+            }
+            *contador.lock().unwrap() = n;
+            ep.done(x)
+        });
+        sim.run(Box::new(uut), 1_000_000).unwrap();
+        let ciclos = *ciclos.lock().unwrap();
+        ciclos
+    }
+
+    // `cuantos` vectores de `n` entradas
+    fn casos(n: usize, cuantos: usize) -> Vec<Vec<i64>> {
+        (0..cuantos)
+            .map(|c| (0..n).map(|i| ((c * n + i) as i64 * 37) % 256 - 128).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_sin_esperas_una_suma_por_ciclo() {
+        let ciclos = transmitir::<Arbol<8>>(casos(8, 50), 0);
+        // Más el flanco en el que el consumidor se queda la última suma
+        assert_eq!(ciclos, 50 + Arbol::<8>::LATENCY + 1);
+    }
+
+    #[test]
+    fn test_con_esperas_no_se_pierden_sumas() {
+        for semilla in [1, 0x2545_f491, 0xdead_beef] {
+            transmitir::<Arbol<8>>(casos(8, 200), semilla);
+        }
+        transmitir::<Arbol<2>>(casos(2, 100), 7);
+        transmitir::<Arbol<16>>(casos(16, 100), 11);
+    }
+
+    // Lo mismo que transmitir pero como TestVectors, para comprobar también el
+    // handshake del Verilog con Icarus. Las salidas esperadas de cada ciclo
+    // salen de StreamModel, con una etapa por nivel del árbol
+    fn vectores<T: SumTreeStream + 'static>(modulo: &str, casos: &[Vec<i64>], semilla: u64) -> TestVectors<T> {
+        let mut tb = TestVectors::<T>::new(modulo).clock("clk", |x| x.clk());
+        let rstn = tb.input("rstn", |x| x.rstn());
+        let valid_in = tb.input("valid_in", |x| x.valid_in());
+        let ready_out = tb.input("ready_out", |x| x.ready_out());
+        let inputs: Vec<_> = (0..T::default().inputs().len())
+            .map(|i| tb.input(&format!("inputs${}", i), move |x| &mut x.inputs()[i]))
+            .collect();
+        let ready_in = tb.output("ready_in", |x| x.ready_in());
+        let valid_out = tb.output("valid_out", |x| x.valid_out());
+        let result = tb.output("result", |x| x.result());
+
+        let mut modelo = StreamModel::new(T::LATENCY);
+        tb.push(TestVector::new("reset").set(rstn, 0).set(valid_in, 0).set(ready_out, 0));
+        let (mut enviados, mut recibidos, mut azar) = (0, 0, semilla);
+        let mut pendiente = false;
+        while recibidos < casos.len() {
+            assert!(tb.len() < 100 * casos.len());
+            azar ^= azar << 13;
+            azar ^= azar >> 7;
+            azar ^= azar << 17;
+            // Una vez puesto valid_in no se quita hasta que se acepta
+            let valido = enviados < casos.len() && (pendiente || azar % 4 != 0);
+            let listo = (azar >> 8) % 3 != 0;
+            let mut vector = TestVector::new(&format!("ciclo {}", tb.len()))
+                .set(rstn, 1)
+                .set(valid_in, valido as i64)
+                .set(ready_out, listo as i64);
+            if valido {
+                for (&input, &valor) in inputs.iter().zip(&casos[enviados]) {
+                    vector = vector.set(input, valor);
+                }
+            }
+
+            // Lo que se transfiere en este flanco
+            if let Some(&suma) = modelo.output() {
+                assert_eq!(suma, casos[recibidos].iter().sum::<i64>());
+                recibidos += listo as usize;
+            }
+            let acepta = valido && modelo.ready_in();
+            pendiente = valido && !acepta;
+            modelo.step(valido.then(|| casos[enviados].iter().sum()), listo);
+            enviados += acepta as usize;
+
+            vector = vector
+                .expect(ready_in, modelo.ready_in() as i64)
+                .expect(valid_out, modelo.output().is_some() as i64);
+            if let Some(&suma) = modelo.output() {
+                vector = vector.expect(result, suma);
+            }
+            tb.push(vector);
+        }
+        tb
+    }
+
+    fn comprobar<T: SumTreeStream + Send + 'static>(modulo: &str, casos: &[Vec<i64>], semilla: u64) {
+        let mut uut = T::default();
+        uut.connect_all();
+        let tb = vectores::<T>(modulo, casos, semilla);
+
+        tb.verify(&uut, 100 * (tb.len() as u64 + 10)).unwrap();
+    }
+
+    #[test]
+    fn test_handshake_con_vectores() {
+        comprobar::<Arbol<8>>("AdderTreeStream8", &casos(8, 100), 1);
+        comprobar::<Arbol<8>>("AdderTreeStream8Esperas", &casos(8, 100), 0xdead_beef);
+        comprobar::<Arbol<2>>("AdderTreeStream2", &casos(2, 50), 7);
+    }
+
+    #[test]
+    fn test_verilog_del_arbol_con_handshake() {
+        let mut uut = Arbol::<8>::default();
+        uut.connect_all();
+        let codigo = tfg_hdl_common::generate_named_verilog(&uut, "AdderTreeStream8").unwrap();
+        // El árbol es AdderTree, con sus anchuras por etapa, parado por el
+        // enable del handshake
+        assert!(codigo.contains("arbol$enable = control$enable;"), "{}", codigo);
+        assert!(codigo.contains("control$valid = arbol$valid_out;"));
+        assert!(codigo.contains("ready_in = control$ready_in;"));
+        assert!(codigo.contains("reg signed [8:0] sumas$0$d;"));
+        assert!(codigo.contains("output reg signed [10:0] result;"));
+    }
+}
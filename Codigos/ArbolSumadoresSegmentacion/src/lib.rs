@@ -0,0 +1,153 @@
+use rust_hdl::prelude::*;
+use sumador::Sumador;
+
+mod adder_tree;
+mod adder_tree_stream;
+pub use adder_tree::{AdderTree, SumTree};
+pub use adder_tree_stream::{AdderTreeStream, SumTreeStream};
+// signed_bit_cast de RustHDL desborda la pila con -128
+use tfg_hdl_common::signed_bit_cast;
+
+// Definición del módulo Reducción en Árbol
+#[derive(LogicBlock, Clone, Default)]
+pub struct ArbolSumadoresSegmentacion {
+    pub inputs: [Signal<In, Signed<8>>; 8],  // Entradas de 8 elementos
+    pub result: Signal<Out, Signed<11>>,     // Resultado de la reducción
+    pub clk: Signal<In, Clock>,
+    pub rstn: Signal<In, Bit>,
+
+    // Etapa 1: los pares de entradas se suman con el Sumador del crate sumador
+    sumador1: Sumador,
+    sumador2: Sumador,
+    sumador3: Sumador,
+    sumador4: Sumador,
+    sum1: DFF<Signed<9>>,
+    sum2: DFF<Signed<9>>,
+    sum3: DFF<Signed<9>>,
+    sum4: DFF<Signed<9>>,
+
+    // Etapa 2
+    sum1_1: DFF<Signed<10>>,
+    sum1_2: DFF<Signed<10>>,
+
+    // Etapa 3
+    final_sum: DFF<Signed<11>>,
+}
+
+impl Logic for ArbolSumadoresSegmentacion {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.sum1.clock.next = self.clk.val();
+        self.sum2.clock.next = self.clk.val();
+        self.sum3.clock.next = self.clk.val();
+        self.sum4.clock.next = self.clk.val();
+        self.sum1_1.clock.next = self.clk.val();
+        self.sum1_2.clock.next = self.clk.val();
+        self.final_sum.clock.next = self.clk.val();
+
+        // Etapa 1: Sumar entradas en pares
+        self.sumador1.a.next = self.inputs[0].val();
+        self.sumador1.b.next = self.inputs[1].val();
+        self.sumador2.a.next = self.inputs[2].val();
+        self.sumador2.b.next = self.inputs[3].val();
+        self.sumador3.a.next = self.inputs[4].val();
+        self.sumador3.b.next = self.inputs[5].val();
+        self.sumador4.a.next = self.inputs[6].val();
+        self.sumador4.b.next = self.inputs[7].val();
+
+        if !self.rstn.val() {
+            self.sum1.d.next = 0.into();
+            self.sum2.d.next = 0.into();
+            self.sum3.d.next = 0.into();
+            self.sum4.d.next = 0.into();
+            self.sum1_1.d.next = 0.into();
+            self.sum1_2.d.next = 0.into();
+            self.final_sum.d.next = 0.into();
+            self.result.next = 0.into();
+        } else {
+            self.sum1.d.next = self.sumador1.sum.val();
+            self.sum2.d.next = self.sumador2.sum.val();
+            self.sum3.d.next = self.sumador3.sum.val();
+            self.sum4.d.next = self.sumador4.sum.val();
+
+            // Etapa 2: Sumar resultados intermedios
+            self.sum1_1.d.next = signed_bit_cast::<10,9>(self.sum1.q.val()) + signed_bit_cast::<10,9>(self.sum2.q.val());
+            self.sum1_2.d.next = signed_bit_cast::<10,9>(self.sum3.q.val()) + signed_bit_cast::<10,9>(self.sum4.q.val());
+
+            // Etapa 3: Sumar el resultado final
+            self.final_sum.d.next = signed_bit_cast::<11,10>(self.sum1_1.q.val()) + signed_bit_cast::<11,10>(self.sum1_2.q.val());
+
+            self.result.next = self.final_sum.q.val();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use tfg_hdl_common::{IcarusRunner, TestVector, TestVectors};
+
+    // Casos de prueba: (entradas, suma esperada, nombre del caso). Salen de
+    // aquí tanto el testbench de RustHDL como el de Verilog
+    const CASOS: [([i64; 8], i64, &str); 6] = [
+        ([0, -1, 2, 3, 4, 5, 6, -7], 12, "caso 0"),
+        ([-128, -128, -128, -128, -128, -128, -128, -128], -1024, "caso 1"),
+        ([10, 15, 20, 5, 30, 25, 12, 8], 125, "caso 2"),
+        ([127, 127, 127, 127, 127, 127, 127, 127], 1016, "caso 3"),
+        ([0, 0, 0, 0, 0, 0, 0, 0], 0, "default"),
+        ([0, 0, 0, 0, 0, 0, 0, 0], 0, "default"),
+    ];
+
+    fn casos() -> TestVectors<ArbolSumadoresSegmentacion> {
+        // El resultado sale 2 ciclos después de cargar las entradas (los
+        // registros de la etapa 1 cargan en el mismo flanco)
+        let mut tb = TestVectors::<ArbolSumadoresSegmentacion>::new("ArbolSumadoresSegmentacion")
+            .clock("clk", |x| &mut x.clk)
+            .latency(2);
+        let rstn = tb.input("rstn", |x| &mut x.rstn);
+        let inputs = [
+            tb.input("inputs$0", |x| &mut x.inputs[0]),
+            tb.input("inputs$1", |x| &mut x.inputs[1]),
+            tb.input("inputs$2", |x| &mut x.inputs[2]),
+            tb.input("inputs$3", |x| &mut x.inputs[3]),
+            tb.input("inputs$4", |x| &mut x.inputs[4]),
+            tb.input("inputs$5", |x| &mut x.inputs[5]),
+            tb.input("inputs$6", |x| &mut x.inputs[6]),
+            tb.input("inputs$7", |x| &mut x.inputs[7]),
+        ];
+        let result = tb.output("result", |x| &x.result);
+
+        //Reset
+        tb.push(TestVector::new("reset").set(rstn, 0));
+        tb.push(TestVector::new("fin del reset").set(rstn, 1));
+        for (valores, esperado, nombre) in CASOS {
+            let mut vector = TestVector::new(nombre).expect(result, esperado);
+            for (&input, valor) in inputs.iter().zip(valores) {
+                vector = vector.set(input, valor);
+            }
+            tb.push(vector);
+        }
+        tb
+    }
+
+    #[test]
+    fn test_reduccion_arbol() -> anyhow::Result<()> {
+        let mut uut = ArbolSumadoresSegmentacion::default();
+        uut.connect_all();
+        let tb = casos();
+
+        //Co-simulación: el mismo testbench en RustHDL y en Icarus, comparado ciclo a ciclo
+        if let Some(informe) = tb.cosimulate(&uut, &IcarusRunner::new())? {
+            println!("Salida Verilog:\n{}", informe.stdout);
+            tb.check_verilog_output(&informe.stdout)?;
+        }
+
+        let mut sim = Simulation::<ArbolSumadoresSegmentacion>::new();
+        tb.add_to(&mut sim);
+        sim.run_to_file(Box::new(uut), 100_000, "ArbolSumadoresSegmentacionWave.vcd")
+            .map_err(|e| anyhow!("{:?}", e))?;
+
+        Ok(())
+    }
+}
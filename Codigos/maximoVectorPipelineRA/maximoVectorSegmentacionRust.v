@@ -1,3 +1,5 @@
+// Módulo maximoVectorSegmentacion generado con RustHDL
+// Crate: maximoVectorPipelineRA v0.1.0
 
 
 module maximoVectorSegmentacion(inputs$0,inputs$1,inputs$2,inputs$3,inputs$4,inputs$5,inputs$6,inputs$7,result,clk,rst,valid_in,valid_out);
@@ -50,52 +52,52 @@ module maximoVectorSegmentacion(inputs$0,inputs$1,inputs$2,inputs$3,inputs$4,inp
     reg  valid_stage3$clock;
     
     // Sub module instances
-    top$max1 max1(
+    maximoVectorSegmentacion$max1 max1(
         .d(max1$d),
         .q(max1$q),
         .clock(max1$clock)
     );
-    top$max2 max2(
+    maximoVectorSegmentacion$max2 max2(
         .d(max2$d),
         .q(max2$q),
         .clock(max2$clock)
     );
-    top$max3 max3(
+    maximoVectorSegmentacion$max3 max3(
         .d(max3$d),
         .q(max3$q),
         .clock(max3$clock)
     );
-    top$max4 max4(
+    maximoVectorSegmentacion$max4 max4(
         .d(max4$d),
         .q(max4$q),
         .clock(max4$clock)
     );
-    top$valid_stage1 valid_stage1(
+    maximoVectorSegmentacion$valid_stage1 valid_stage1(
         .d(valid_stage1$d),
         .q(valid_stage1$q),
         .clock(valid_stage1$clock)
     );
-    top$max1_1 max1_1(
+    maximoVectorSegmentacion$max1_1 max1_1(
         .d(max1_1$d),
         .q(max1_1$q),
         .clock(max1_1$clock)
     );
-    top$max1_2 max1_2(
+    maximoVectorSegmentacion$max1_2 max1_2(
         .d(max1_2$d),
         .q(max1_2$q),
         .clock(max1_2$clock)
     );
-    top$valid_stage2 valid_stage2(
+    maximoVectorSegmentacion$valid_stage2 valid_stage2(
         .d(valid_stage2$d),
         .q(valid_stage2$q),
         .clock(valid_stage2$clock)
     );
-    top$final_max final_max(
+    maximoVectorSegmentacion$final_max final_max(
         .d(final_max$d),
         .q(final_max$q),
         .clock(final_max$clock)
     );
-    top$valid_stage3 valid_stage3(
+    maximoVectorSegmentacion$valid_stage3 valid_stage3(
         .d(valid_stage3$d),
         .q(valid_stage3$q),
         .clock(valid_stage3$clock)
@@ -186,10 +188,10 @@ module maximoVectorSegmentacion(inputs$0,inputs$1,inputs$2,inputs$3,inputs$4,inp
         end
     end
     
-endmodule // top
+endmodule // maximoVectorSegmentacion
 
 
-module top$final_max(d,q,clock);
+module maximoVectorSegmentacion$final_max(d,q,clock);
     
     // Module arguments
     input wire signed [7:0] d;
@@ -205,10 +207,10 @@ module top$final_max(d,q,clock);
        q <= d;
     end
           
-endmodule // top$final_max
+endmodule // maximoVectorSegmentacion$final_max
 
 
-module top$max1(d,q,clock);
+module maximoVectorSegmentacion$max1(d,q,clock);
     
     // Module arguments
     input wire signed [7:0] d;
@@ -224,10 +226,10 @@ module top$max1(d,q,clock);
        q <= d;
     end
           
-endmodule // top$max1
+endmodule // maximoVectorSegmentacion$max1
 
 
-module top$max1_1(d,q,clock);
+module maximoVectorSegmentacion$max1_1(d,q,clock);
     
     // Module arguments
     input wire signed [7:0] d;
@@ -243,10 +245,10 @@ module top$max1_1(d,q,clock);
        q <= d;
     end
           
-endmodule // top$max1_1
+endmodule // maximoVectorSegmentacion$max1_1
 
 
-module top$max1_2(d,q,clock);
+module maximoVectorSegmentacion$max1_2(d,q,clock);
     
     // Module arguments
     input wire signed [7:0] d;
@@ -262,10 +264,10 @@ module top$max1_2(d,q,clock);
        q <= d;
     end
           
-endmodule // top$max1_2
+endmodule // maximoVectorSegmentacion$max1_2
 
 
-module top$max2(d,q,clock);
+module maximoVectorSegmentacion$max2(d,q,clock);
     
     // Module arguments
     input wire signed [7:0] d;
@@ -281,10 +283,10 @@ module top$max2(d,q,clock);
        q <= d;
     end
           
-endmodule // top$max2
+endmodule // maximoVectorSegmentacion$max2
 
 
-module top$max3(d,q,clock);
+module maximoVectorSegmentacion$max3(d,q,clock);
     
     // Module arguments
     input wire signed [7:0] d;
@@ -300,10 +302,10 @@ module top$max3(d,q,clock);
        q <= d;
     end
           
-endmodule // top$max3
+endmodule // maximoVectorSegmentacion$max3
 
 
-module top$max4(d,q,clock);
+module maximoVectorSegmentacion$max4(d,q,clock);
     
     // Module arguments
     input wire signed [7:0] d;
@@ -319,10 +321,10 @@ module top$max4(d,q,clock);
        q <= d;
     end
           
-endmodule // top$max4
+endmodule // maximoVectorSegmentacion$max4
 
 
-module top$valid_stage1(d,q,clock);
+module maximoVectorSegmentacion$valid_stage1(d,q,clock);
     
     // Module arguments
     input wire  d;
@@ -338,10 +340,10 @@ module top$valid_stage1(d,q,clock);
        q <= d;
     end
           
-endmodule // top$valid_stage1
+endmodule // maximoVectorSegmentacion$valid_stage1
 
 
-module top$valid_stage2(d,q,clock);
+module maximoVectorSegmentacion$valid_stage2(d,q,clock);
     
     // Module arguments
     input wire  d;
@@ -357,10 +359,10 @@ module top$valid_stage2(d,q,clock);
        q <= d;
     end
           
-endmodule // top$valid_stage2
+endmodule // maximoVectorSegmentacion$valid_stage2
 
 
-module top$valid_stage3(d,q,clock);
+module maximoVectorSegmentacion$valid_stage3(d,q,clock);
     
     // Module arguments
     input wire  d;
@@ -376,4 +378,4 @@ module top$valid_stage3(d,q,clock);
        q <= d;
     end
           
-endmodule // top$valid_stage3
+endmodule // maximoVectorSegmentacion$valid_stage3
@@ -0,0 +1,185 @@
+use rust_hdl::prelude::*;
+
+mod reduce_tree;
+mod reduce_tree_stream;
+pub use reduce_tree::{Max, Min, ReduceOp, ReduceTree};
+pub use reduce_tree_stream::ReduceTreeStream;
+
+// Definición del módulo Reducción en Árbol
+#[derive(LogicBlock, Clone, Default)]
+#[allow(non_camel_case_types)]
+pub struct maximoVectorSegmentacion {
+    pub inputs: [Signal<In, Signed<8>>; 8],  // Entradas de 8 elementos
+    pub result: Signal<Out, Signed<8>>,     // Resultado de la reducción
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    pub valid_in: Signal<In, Bit>,
+    pub valid_out: Signal<Out, Bit>,
+
+    // Etapa 1
+    max1: DFF<Signed<8>>,
+    max2: DFF<Signed<8>>,
+    max3: DFF<Signed<8>>,
+    max4: DFF<Signed<8>>,
+    valid_stage1: DFF<Bit>,
+
+    // Etapa 2
+    max1_1: DFF<Signed<8>>,
+    max1_2: DFF<Signed<8>>,
+    valid_stage2: DFF<Bit>,
+
+    // Etapa 3
+    final_max: DFF<Signed<8>>,
+    valid_stage3: DFF<Bit>,
+}
+
+impl Logic for maximoVectorSegmentacion {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.max1.clock.next = self.clk.val();
+        self.max2.clock.next = self.clk.val();
+        self.max3.clock.next = self.clk.val();
+        self.max4.clock.next = self.clk.val();
+        self.max1_1.clock.next = self.clk.val();
+        self.max1_2.clock.next = self.clk.val();
+        self.final_max.clock.next = self.clk.val();
+        self.valid_stage1.clock.next = self.clk.val();
+        self.valid_stage2.clock.next = self.clk.val();
+        self.valid_stage3.clock.next = self.clk.val();
+
+        if self.rst.val() {
+            // Reset: limpiar todos los registros
+            self.max1.d.next = 0.into();
+            self.max2.d.next = 0.into();
+            self.max3.d.next = 0.into();
+            self.max4.d.next = 0.into();
+            self.max1_1.d.next = 0.into();
+            self.max1_2.d.next = 0.into();
+            self.final_max.d.next = 0.into();
+
+            self.valid_stage1.d.next = false;
+            self.valid_stage2.d.next = false;
+            self.valid_stage3.d.next = false;
+
+            self.result.next = 0.into();
+            self.valid_out.next = false;
+        } else {
+            // Solo si los datos de entrada son válidos, actualizamos la Etapa 1
+            if self.valid_in.val() {
+                if self.inputs[0].val() > self.inputs[1].val() {self.max1.d.next = self.inputs[0].val();}
+                else {self.max1.d.next = self.inputs[1].val();}
+                if self.inputs[2].val() > self.inputs[3].val() {self.max2.d.next = self.inputs[2].val();}
+                else {self.max2.d.next = self.inputs[3].val();}
+                if self.inputs[4].val() > self.inputs[5].val() {self.max3.d.next = self.inputs[4].val();}
+                else {self.max3.d.next = self.inputs[5].val();}
+                if self.inputs[6].val() > self.inputs[7].val() {self.max4.d.next = self.inputs[6].val();}
+                else {self.max4.d.next = self.inputs[7].val();}
+            }
+            else {
+                self.max1.d.next = 0.into();
+                self.max2.d.next = 0.into();
+                self.max3.d.next = 0.into();
+                self.max4.d.next = 0.into();
+            }
+            self.valid_stage1.d.next = self.valid_in.val();
+
+            // Etapa 2: Comparar registros
+            if self.max1.q.val() > self.max2.q.val() {self.max1_1.d.next = self.max1.q.val();}
+            else {self.max1_1.d.next = self.max2.q.val();}
+            if self.max3.q.val() > self.max4.q.val() {self.max1_2.d.next = self.max3.q.val();}
+            else {self.max1_2.d.next = self.max4.q.val();}
+            self.valid_stage2.d.next = self.valid_stage1.q.val();
+
+            // Etapa 3: Comparar los máximos intermedios
+            if self.max1_1.q.val() > self.max1_2.q.val() {self.final_max.d.next = self.max1_1.q.val();}
+            else {self.final_max.d.next = self.max1_2.q.val();}
+            self.valid_stage3.d.next = self.valid_stage2.q.val();
+
+            // Salida final
+            self.result.next = self.final_max.q.val();
+            self.valid_out.next = self.valid_stage3.q.val();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use tfg_hdl_common::{IcarusRunner, TestVector, TestVectors};
+
+    // Casos de prueba: (entradas, máximo esperado, nombre del caso). Salen de
+    // aquí tanto el testbench de RustHDL como el de Verilog
+    const CASOS: [([i64; 8], i64, &str); 3] = [
+        ([-50, -20, -100, -5, -30, -90, -10, -60], -5, "Ciclo 1 - Vector: [-50, -20, -100, -5, -30, -90, -10, -60]"),
+        ([-128, 0, 50, 120, -100, 50, 120, 127], 127, "Ciclo 2 - Vector: [-128, 0, 50, 120, -100, 50, 120, 127]"),
+        ([30, 50, -80, 120, 0, 60, 70, 110], 120, "Ciclo 3 - Vector: [30, 50, -80, 120, 0, 60, 70, 110]"),
+    ];
+
+    fn casos() -> TestVectors<maximoVectorSegmentacion> {
+        // El máximo sale 2 ciclos después de cargar las entradas
+        let mut tb = TestVectors::<maximoVectorSegmentacion>::new("maximoVectorSegmentacion")
+            .clock("clk", |x| &mut x.clk)
+            .latency(2);
+        let rst = tb.input("rst", |x| &mut x.rst);
+        let valid_in = tb.input("valid_in", |x| &mut x.valid_in);
+        let inputs = [
+            tb.input("inputs$0", |x| &mut x.inputs[0]),
+            tb.input("inputs$1", |x| &mut x.inputs[1]),
+            tb.input("inputs$2", |x| &mut x.inputs[2]),
+            tb.input("inputs$3", |x| &mut x.inputs[3]),
+            tb.input("inputs$4", |x| &mut x.inputs[4]),
+            tb.input("inputs$5", |x| &mut x.inputs[5]),
+            tb.input("inputs$6", |x| &mut x.inputs[6]),
+            tb.input("inputs$7", |x| &mut x.inputs[7]),
+        ];
+        let result = tb.output("result", |x| &x.result);
+        let valid_out = tb.output("valid_out", |x| &x.valid_out);
+
+        // Resetear el sistema
+        tb.push(TestVector::new("reset").set(rst, 1));
+        tb.push(TestVector::new("fin del reset").set(rst, 0));
+        for (valores, esperado, nombre) in CASOS {
+            let mut vector = TestVector::new(nombre)
+                .set(valid_in, 1)
+                .expect(result, esperado)
+                .expect(valid_out, 1);
+            for (&input, valor) in inputs.iter().zip(valores) {
+                vector = vector.set(input, valor);
+            }
+            tb.push(vector);
+        }
+        // Sin datos válidos el pipeline se vacía con ceros
+        for _ in 0..3 {
+            let mut vector = TestVector::new("default")
+                .set(valid_in, 0)
+                .expect(result, 0)
+                .expect(valid_out, 0);
+            for &input in &inputs {
+                vector = vector.set(input, 0);
+            }
+            tb.push(vector);
+        }
+        tb
+    }
+
+    #[test]
+    fn test_maximo_vector() -> anyhow::Result<()> {
+        let mut uut = maximoVectorSegmentacion::default();
+        uut.connect_all();
+        let tb = casos();
+
+        //Co-simulación: el mismo testbench en RustHDL y en Icarus, comparado ciclo a ciclo
+        if let Some(informe) = tb.cosimulate(&uut, &IcarusRunner::new())? {
+            println!("Salida Verilog:\n{}", informe.stdout);
+            tb.check_verilog_output(&informe.stdout)?;
+        }
+
+        let mut sim = Simulation::<maximoVectorSegmentacion>::new();
+        tb.add_to(&mut sim);
+        sim.run_to_file(Box::new(uut), 100_000, "maximoVectorSegmentacionWave.vcd")
+            .map_err(|e| anyhow!("{:?}", e))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+use rust_hdl::prelude::*;
+
+// Reducción segmentada de N entradas de W bits con el máximo o el mínimo,
+// que además de ganador da su posición (argmax/argmin). Es la misma
+// estructura de etapas que maximoVectorSegmentacion (max1..max4, max1_1,
+// max1_2, final_max), pero cada nodo guarda el valor, el índice de la entrada
+// de la que viene y si es válido.
+//
+// Como con los árboles de sumadores, el Rust estable no deja calcular log2(N) en el
+// tipo, así que la anchura del índice I se pasa como parámetro y se comprueba
+// al construir. Los N - 1 nodos forman un montículo: el nodo k compara los
+// nodos 2k + 1 y 2k + 2, la raíz es el nodo 0 y los nodos N/2 - 1..N - 1
+// comparan las entradas 2k + 2 - N y 2k + 3 - N. Van en Vec porque el Rust
+// estable no deja escribir [_; N - 1]. Con empate gana el índice más bajo. El
+// resultado sale log2(N) - 1 ciclos después del flanco que carga las entradas.
+// Con enable a 0 todas las etapas se paran a la vez, que es lo que usa
+// ReduceTreeStream; sin paradas, enable se deja a 1.
+#[derive(LogicBlock, Clone)]
+pub struct ReduceTree<const N: usize, const W: usize, const I: usize, Op: ReduceOp> {
+    pub inputs: [Signal<In, Signed<W>>; N],
+    pub result: Signal<Out, Signed<W>>,
+    pub index: Signal<Out, Bits<I>>,
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    pub valid_in: Signal<In, Bit>,
+    pub valid_out: Signal<Out, Bit>,
+    pub enable: Signal<In, Bit>,
+    valores: Vec<DFF<Signed<W>>>,
+    indices: Vec<DFF<Bits<I>>>,
+    validos: Vec<DFF<Bit>>,
+    // Posición de cada entrada, para cargar los índices de la primera etapa
+    posiciones: [Constant<Bits<I>>; N],
+    // 1 para el máximo, 0 para el mínimo
+    maximo: Constant<Bit>,
+    op: Op,
+}
+
+// Operación de la reducción. Max y Min no generan hardware: solo eligen el
+// valor de la constante `maximo`
+pub trait ReduceOp: Block + Clone + Default {
+    const MAX: bool;
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Max;
+
+#[derive(Clone, Debug, Default)]
+pub struct Min;
+
+impl ReduceOp for Max {
+    const MAX: bool = true;
+}
+
+impl ReduceOp for Min {
+    const MAX: bool = false;
+}
+
+// Bloque vacío para que el derive de LogicBlock acepte el campo `op`
+tfg_hdl_common::modo_sin_hardware!(Max, Min);
+
+impl<const N: usize, const W: usize, const I: usize, Op: ReduceOp> ReduceTree<N, W, I, Op> {
+    // Número de etapas de registros
+    pub const STAGES: usize = N.trailing_zeros() as usize;
+}
+
+impl<const N: usize, const W: usize, const I: usize, Op: ReduceOp> Default for ReduceTree<N, W, I, Op> {
+    fn default() -> Self {
+        const {
+            assert!(N >= 2 && N.is_power_of_two(), "N tiene que ser una potencia de 2");
+            assert!(I == N.trailing_zeros() as usize, "I tiene que ser log2(N)");
+        }
+        Self {
+            inputs: std::array::from_fn(|_| Default::default()),
+            result: Default::default(),
+            index: Default::default(),
+            clk: Default::default(),
+            rst: Default::default(),
+            valid_in: Default::default(),
+            valid_out: Default::default(),
+            enable: Default::default(),
+            valores: (1..N).map(|_| Default::default()).collect(),
+            indices: (1..N).map(|_| Default::default()).collect(),
+            validos: (1..N).map(|_| Default::default()).collect(),
+            posiciones: std::array::from_fn(|i| Constant::new((i as u64).into())),
+            maximo: Constant::new(Op::MAX),
+            op: Op::default(),
+        }
+    }
+}
+
+impl<const N: usize, const W: usize, const I: usize, Op: ReduceOp> Logic for ReduceTree<N, W, I, Op> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        for k in 0..(N - 1) {
+            self.valores[k].clock.next = self.clk.val();
+            self.indices[k].clock.next = self.clk.val();
+            self.validos[k].clock.next = self.clk.val();
+        }
+        // Parado, todo se mantiene
+        for k in 0..(N - 1) {
+            self.valores[k].d.next = self.valores[k].q.val();
+            self.indices[k].d.next = self.indices[k].q.val();
+            self.validos[k].d.next = self.validos[k].q.val();
+        }
+        self.result.next = self.valores[0].q.val();
+        self.index.next = self.indices[0].q.val();
+        self.valid_out.next = self.validos[0].q.val();
+        if self.rst.val() {
+            for k in 0..(N - 1) {
+                self.valores[k].d.next = 0.into();
+                self.indices[k].d.next = 0.into();
+                self.validos[k].d.next = false;
+            }
+            self.result.next = 0.into();
+            self.index.next = 0.into();
+            self.valid_out.next = false;
+        } else if self.enable.val() {
+            // Primera etapa: pares de entradas, solo si son válidas
+            // N entra como `n`: bucle de una vuelta, ver tfg_hdl_common
+            for n in N..(N + 1) {
+                for k in (N / 2 - 1)..(N - 1) {
+                    if !self.valid_in.val() {
+                        self.valores[k].d.next = 0.into();
+                        self.indices[k].d.next = 0.into();
+                    } else if (self.maximo.val() & (self.inputs[2 * k + 2 - n].val() >= self.inputs[2 * k + 3 - n].val()))
+                        | (!self.maximo.val() & (self.inputs[2 * k + 2 - n].val() <= self.inputs[2 * k + 3 - n].val()))
+                    {
+                        self.valores[k].d.next = self.inputs[2 * k + 2 - n].val();
+                        self.indices[k].d.next = self.posiciones[2 * k + 2 - n].val();
+                    } else {
+                        self.valores[k].d.next = self.inputs[2 * k + 3 - n].val();
+                        self.indices[k].d.next = self.posiciones[2 * k + 3 - n].val();
+                    }
+                    self.validos[k].d.next = self.valid_in.val();
+                }
+            }
+            // Resto de etapas: el ganador de cada pareja de nodos sigue con
+            // su índice
+            for k in 0..(N / 2 - 1) {
+                if (self.maximo.val() & (self.valores[2 * k + 1].q.val() >= self.valores[2 * k + 2].q.val()))
+                    | (!self.maximo.val() & (self.valores[2 * k + 1].q.val() <= self.valores[2 * k + 2].q.val()))
+                {
+                    self.valores[k].d.next = self.valores[2 * k + 1].q.val();
+                    self.indices[k].d.next = self.indices[2 * k + 1].q.val();
+                } else {
+                    self.valores[k].d.next = self.valores[2 * k + 2].q.val();
+                    self.indices[k].d.next = self.indices[2 * k + 2].q.val();
+                }
+                self.validos[k].d.next = self.validos[2 * k + 1].q.val();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tfg_hdl_common::{TestVector, TestVectors};
+
+    // Modelo de referencia: valor e índice del ganador, el primero si hay
+    // empate
+    fn ganador(valores: &[i64], maximo: bool) -> (i64, i64) {
+        let mut mejor = 0;
+        for (i, &v) in valores.iter().enumerate() {
+            let gana = if maximo { v > valores[mejor] } else { v < valores[mejor] };
+            if gana {
+                mejor = i;
+            }
+        }
+        (valores[mejor], mejor as i64)
+    }
+
+    fn valores<const N: usize>() -> Vec<[i64; N]> {
+        vec![
+            std::array::from_fn(|i| (i as i64 * 37) % 256 - 128),
+            std::array::from_fn(|i| (i as i64 * 91 + 50) % 256 - 128),
+            // Empates: tiene que salir la posición más baja
+            [5; N],
+            std::array::from_fn(|i| if i % 3 == 1 { 127 } else { -128 }),
+            std::array::from_fn(|i| i as i64 - (N as i64 / 2)),
+        ]
+    }
+
+    fn casos<const N: usize, const I: usize, Op: ReduceOp + Send + 'static>(
+        modulo: &str,
+    ) -> TestVectors<ReduceTree<N, 8, I, Op>> {
+        let mut tb = TestVectors::<ReduceTree<N, 8, I, Op>>::new(modulo)
+            .clock("clk", |x| &mut x.clk)
+            .latency(ReduceTree::<N, 8, I, Op>::STAGES - 1);
+        let rst = tb.input("rst", |x| &mut x.rst);
+        let valid_in = tb.input("valid_in", |x| &mut x.valid_in);
+        let enable = tb.input("enable", |x| &mut x.enable);
+        let inputs: Vec<_> = (0..N)
+            .map(|i| tb.input(&format!("inputs${}", i), move |x| &mut x.inputs[i]))
+            .collect();
+        let result = tb.output("result", |x| &x.result);
+        let index = tb.output("index", |x| &x.index);
+        let valid_out = tb.output("valid_out", |x| &x.valid_out);
+
+        tb.push(TestVector::new("reset").set(rst, 1).set(valid_in, 0).set(enable, 1));
+        tb.push(TestVector::new("fin del reset").set(rst, 0));
+        for (n, valores) in valores::<N>().iter().enumerate() {
+            let (valor, posicion) = ganador(valores, Op::MAX);
+            let mut vector = TestVector::new(&format!("caso {}", n))
+                .set(valid_in, 1)
+                .expect(result, valor)
+                .expect(index, posicion)
+                .expect(valid_out, 1);
+            for (&input, &valor) in inputs.iter().zip(valores) {
+                vector = vector.set(input, valor);
+            }
+            tb.push(vector);
+        }
+        // Sin entradas válidas la salida vuelve a 0 y valid_out a 0
+        tb.push(
+            TestVector::new("sin datos")
+                .set(valid_in, 0)
+                .expect(result, 0)
+                .expect(index, 0)
+                .expect(valid_out, 0),
+        );
+        tb
+    }
+
+    fn comprobar<const N: usize, const I: usize, Op: ReduceOp + Send + 'static>(modulo: &str) -> anyhow::Result<()> {
+        let mut uut = ReduceTree::<N, 8, I, Op>::default();
+        uut.connect_all();
+        let tb = casos::<N, I, Op>(modulo);
+
+        tb.verify(&uut, 10_000)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_argmax_de_8() -> anyhow::Result<()> {
+        comprobar::<8, 3, Max>("ArgMax8")
+    }
+
+    #[test]
+    fn test_argmin_de_8() -> anyhow::Result<()> {
+        comprobar::<8, 3, Min>("ArgMin8")
+    }
+
+    #[test]
+    fn test_otros_tamanos() -> anyhow::Result<()> {
+        comprobar::<2, 1, Max>("ArgMax2")?;
+        comprobar::<16, 4, Min>("ArgMin16")?;
+        comprobar::<32, 5, Max>("ArgMax32")
+    }
+
+    #[test]
+    fn test_verilog_de_la_reduccion() {
+        let mut uut = ReduceTree::<8, 8, 3, Min>::default();
+        uut.connect_all();
+        let codigo = tfg_hdl_common::generate_named_verilog(&uut, "ArgMin8").unwrap();
+        assert!(codigo.contains("localparam  maximo = 1'b0;"));
+        assert!(codigo.contains("indices$3$d = posiciones$0;"));
+        assert!(codigo.contains("valid_out = validos$0$q;"));
+        // Sólo los 7 nodos del árbol
+        assert!(!codigo.contains("valores$7"));
+        assert!(codigo.contains("(~maximo & (inputs$0 <= inputs$1))"));
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{ReduceOp, ReduceTree};
+use rust_hdl::prelude::*;
+use tfg_hdl_common::PipelineHandshake;
+
+// ReduceTree con interfaz valid/ready (como AXI-Stream) en lugar de sólo
+// valid_in/valid_out, para poder ponerlo detrás de una FIFO sin perder
+// muestras.
+//
+// Por dentro es el mismo ReduceTree, parado con su enable por el
+// PipelineHandshake que entrega los resultados a un SkidBuffer, igual que en
+// AdderTreeStream. El valor y el índice pasan juntos por el handshake en un
+// solo dato de P = W + I bits, con el valor en los bits altos; P se pasa como
+// parámetro y se comprueba al construir, como I en ReduceTree.
+//
+// Sin esperas sale un resultado por ciclo, log2(N) ciclos después del flanco
+// que carga las entradas. rst es activo a nivel alto, como en ReduceTree.
+#[derive(LogicBlock, Clone)]
+pub struct ReduceTreeStream<const N: usize, const W: usize, const I: usize, const P: usize, Op: ReduceOp> {
+    pub inputs: [Signal<In, Signed<W>>; N],
+    pub valid_in: Signal<In, Bit>,
+    pub ready_in: Signal<Out, Bit>,
+    pub result: Signal<Out, Signed<W>>,
+    pub index: Signal<Out, Bits<I>>,
+    pub valid_out: Signal<Out, Bit>,
+    pub ready_out: Signal<In, Bit>,
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    arbol: ReduceTree<N, W, I, Op>,
+    // Posición del valor dentro del dato del handshake
+    desplazamiento: Constant<Bits<P>>,
+    control: PipelineHandshake<Bits<P>>,
+}
+
+impl<const N: usize, const W: usize, const I: usize, const P: usize, Op: ReduceOp> ReduceTreeStream<N, W, I, P, Op> {
+    // Ciclos desde que se acepta un vector hasta que su resultado está en la
+    // salida, si no hay esperas
+    pub const LATENCY: usize = ReduceTree::<N, W, I, Op>::STAGES;
+}
+
+impl<const N: usize, const W: usize, const I: usize, const P: usize, Op: ReduceOp> Default for ReduceTreeStream<N, W, I, P, Op> {
+    fn default() -> Self {
+        const {
+            assert!(P == W + I, "P tiene que ser W + I");
+        }
+        Self {
+            inputs: std::array::from_fn(|_| Default::default()),
+            valid_in: Default::default(),
+            ready_in: Default::default(),
+            result: Default::default(),
+            index: Default::default(),
+            valid_out: Default::default(),
+            ready_out: Default::default(),
+            clk: Default::default(),
+            rst: Default::default(),
+            arbol: Default::default(),
+            desplazamiento: Constant::new((I as u64).into()),
+            control: Default::default(),
+        }
+    }
+}
+
+impl<const N: usize, const W: usize, const I: usize, const P: usize, Op: ReduceOp> Logic for ReduceTreeStream<N, W, I, P, Op> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.arbol.clk.next = self.clk.val();
+        self.arbol.rst.next = self.rst.val();
+        self.control.clk.next = self.clk.val();
+        self.control.rst.next = self.rst.val();
+
+        for k in 0..N {
+            self.arbol.inputs[k].next = self.inputs[k].val();
+        }
+        self.arbol.valid_in.next = self.valid_in.val();
+        self.arbol.enable.next = self.control.enable.val();
+        self.ready_in.next = self.control.ready_in.val();
+
+        self.control.data.next = (bit_cast::<P, W>(unsigned_cast(self.arbol.result.val())) << self.desplazamiento.val())
+            | bit_cast::<P, I>(self.arbol.index.val());
+        self.control.valid.next = self.arbol.valid_out.val();
+        self.control.ready_out.next = self.ready_out.val();
+        self.result.next = signed_cast(bit_cast::<W, P>(self.control.data_out.val() >> self.desplazamiento.val()));
+        self.index.next = bit_cast::<I, P>(self.control.data_out.val());
+        self.valid_out.next = self.control.valid_out.val();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Max, Min};
+    use std::sync::{Arc, Mutex};
+    use tfg_hdl_common::{StreamModel, TestVector, TestVectors};
+
+    type Arbol<const N: usize, const I: usize, const P: usize, Op> = ReduceTreeStream<N, 8, I, P, Op>;
+
+    fn flanco<const N: usize, const I: usize, const P: usize, Op: ReduceOp>(
+        ep: &mut Sim<Arbol<N, I, P, Op>>,
+        mut x: Box<Arbol<N, I, P, Op>>,
+    ) -> Result<Box<Arbol<N, I, P, Op>>, SimError> {
+        x.clk.next = Clock { clk: false };
+        x = ep.wait(1, x)?;
+        x.clk.next = Clock { clk: true };
+        ep.wait(1, x)
+    }
+
+    // Valor e índice del ganador, el primero si hay empate
+    fn ganador(valores: &[i64], maximo: bool) -> (i64, u64) {
+        let mut mejor = 0;
+        for (i, &v) in valores.iter().enumerate() {
+            if (maximo && v > valores[mejor]) || (!maximo && v < valores[mejor]) {
+                mejor = i;
+            }
+        }
+        (valores[mejor], mejor as u64)
+    }
+
+    // Productor y consumidor con esperas pseudoaleatorias, como en los tests
+    // de AdderTreeStream: cada ciclo con valid_out a 1 se compara la salida
+    // con el siguiente resultado que falta por recibir. Devuelve los ciclos
+    // que ha tardado
+    fn transmitir<const N: usize, const I: usize, const P: usize, Op: ReduceOp + Send + 'static>(
+        casos: Vec<[i64; N]>,
+        semilla: u64,
+    ) -> usize {
+        let mut uut = Arbol::<N, I, P, Op>::default();
+        uut.connect_all();
+        let ciclos = Arc::new(Mutex::new(0));
+        let contador = ciclos.clone();
+
+        let mut sim = Simulation::<Arbol<N, I, P, Op>>::new();
+        sim.add_testbench(move |mut ep: Sim<Arbol<N, I, P, Op>>| {
+            let mut x = ep.init()?;
+            let mut azar = semilla;
+            let mut espera = move |cada: u64| {
+                azar ^= azar << 13;
+                azar ^= azar >> 7;
+                azar ^= azar << 17;
+                semilla != 0 && azar.is_multiple_of(cada)
+            };
+
+            x.rst.next = true;
+            x = flanco(&mut ep, x)?;
+            x = flanco(&mut ep, x)?;
+            x.rst.next = false;
+
+            let (mut enviados, mut recibidos, mut n) = (0, 0, 0);
+            let mut pendiente = false;
+            while recibidos < casos.len() {
+                sim_assert!(ep, n < 100 * casos.len(), x);
+                // Una vez puesto valid_in no se quita hasta que se acepta
+                if enviados < casos.len() && (pendiente || !espera(4)) {
+                    x.valid_in.next = true;
+                    for (input, &valor) in x.inputs.iter_mut().zip(&casos[enviados]) {
+                        input.next = valor.into();
+                    }
+                } else {
+                    x.valid_in.next = false;
+                }
+                let ready_out = !espera(3);
+                x.ready_out.next = ready_out;
+
+                // Lo que se transfiere en este flanco
+                pendiente = x.valid_in.next && !x.ready_in.val();
+                if x.valid_in.next && x.ready_in.val() {
+                    enviados += 1;
+                }
+                if x.valid_out.val() {
+                    let (valor, posicion) = ganador(&casos[recibidos], Op::MAX);
+                    sim_assert_eq!(ep, x.result.val(), Signed::<8>::from(valor), x);
+                    sim_assert_eq!(ep, x.index.val(), Bits::<I>::from(posicion), x);
+                    if ready_out {
+                        recibidos += 1;
+                    }
+                }
+                x = flanco(&mut ep, x)?;
+                n += 1;
+            }
+            *contador.lock().unwrap() = n;
+            ep.done(x)
+        });
+        sim.run(Box::new(uut), 1_000_000).unwrap();
+        let ciclos = *ciclos.lock().unwrap();
+        ciclos
+    }
+
+    // Vectores variados y, de vez en cuando, con empates
+    fn casos<const N: usize>(cuantos: usize) -> Vec<[i64; N]> {
+        (0..cuantos)
+            .map(|c| std::array::from_fn(|i| if c % 5 == 0 { 9 } else { ((c * N + i) as i64 * 91) % 256 - 128 }))
+            .collect()
+    }
+
+    #[test]
+    fn test_sin_esperas_un_resultado_por_ciclo() {
+        let ciclos = transmitir::<8, 3, 11, Max>(casos(50), 0);
+        // Más el flanco en el que el consumidor se queda el último resultado
+        assert_eq!(ciclos, 50 + Arbol::<8, 3, 11, Max>::LATENCY + 1);
+    }
+
+    #[test]
+    fn test_con_esperas_no_se_pierden_resultados() {
+        for semilla in [1, 0x2545_f491, 0xdead_beef] {
+            transmitir::<8, 3, 11, Max>(casos(200), semilla);
+            transmitir::<8, 3, 11, Min>(casos(200), semilla);
+        }
+        transmitir::<2, 1, 9, Min>(casos(100), 7);
+        transmitir::<16, 4, 12, Max>(casos(100), 11);
+    }
+
+    // Lo mismo que transmitir pero como TestVectors, para comprobar también el
+    // handshake del Verilog con Icarus (ver AdderTreeStream). Las salidas
+    // esperadas de cada ciclo salen de StreamModel
+    fn vectores<const N: usize, const I: usize, const P: usize, Op: ReduceOp + Send + 'static>(
+        modulo: &str,
+        casos: &[[i64; N]],
+        semilla: u64,
+    ) -> TestVectors<Arbol<N, I, P, Op>> {
+        let mut tb = TestVectors::<Arbol<N, I, P, Op>>::new(modulo).clock("clk", |x| &mut x.clk);
+        let rst = tb.input("rst", |x| &mut x.rst);
+        let valid_in = tb.input("valid_in", |x| &mut x.valid_in);
+        let ready_out = tb.input("ready_out", |x| &mut x.ready_out);
+        let inputs: Vec<_> = (0..N)
+            .map(|i| tb.input(&format!("inputs${}", i), move |x| &mut x.inputs[i]))
+            .collect();
+        let ready_in = tb.output("ready_in", |x| &x.ready_in);
+        let valid_out = tb.output("valid_out", |x| &x.valid_out);
+        let result = tb.output("result", |x| &x.result);
+        let index = tb.output("index", |x| &x.index);
+
+        let mut modelo = StreamModel::new(Arbol::<N, I, P, Op>::LATENCY);
+        tb.push(TestVector::new("reset").set(rst, 1).set(valid_in, 0).set(ready_out, 0));
+        let (mut enviados, mut recibidos, mut azar) = (0, 0, semilla);
+        let mut pendiente = false;
+        while recibidos < casos.len() {
+            assert!(tb.len() < 100 * casos.len());
+            azar ^= azar << 13;
+            azar ^= azar >> 7;
+            azar ^= azar << 17;
+            // Una vez puesto valid_in no se quita hasta que se acepta
+            let valido = enviados < casos.len() && (pendiente || azar % 4 != 0);
+            let listo = (azar >> 8) % 3 != 0;
+            let mut vector = TestVector::new(&format!("ciclo {}", tb.len()))
+                .set(rst, 0)
+                .set(valid_in, valido as i64)
+                .set(ready_out, listo as i64);
+            if valido {
+                for (&input, &valor) in inputs.iter().zip(&casos[enviados]) {
+                    vector = vector.set(input, valor);
+                }
+            }
+
+            // Lo que se transfiere en este flanco
+            if let Some(&salida) = modelo.output() {
+                assert_eq!(salida, ganador(&casos[recibidos], Op::MAX));
+                recibidos += listo as usize;
+            }
+            let acepta = valido && modelo.ready_in();
+            pendiente = valido && !acepta;
+            modelo.step(valido.then(|| ganador(&casos[enviados], Op::MAX)), listo);
+            enviados += acepta as usize;
+
+            vector = vector
+                .expect(ready_in, modelo.ready_in() as i64)
+                .expect(valid_out, modelo.output().is_some() as i64);
+            if let Some(&(valor, posicion)) = modelo.output() {
+                vector = vector.expect(result, valor).expect(index, posicion as i64);
+            }
+            tb.push(vector);
+        }
+        tb
+    }
+
+    fn comprobar<const N: usize, const I: usize, const P: usize, Op: ReduceOp + Send + 'static>(
+        modulo: &str,
+        casos: &[[i64; N]],
+        semilla: u64,
+    ) {
+        let mut uut = Arbol::<N, I, P, Op>::default();
+        uut.connect_all();
+        let tb = vectores::<N, I, P, Op>(modulo, casos, semilla);
+
+        tb.verify(&uut, 100 * (tb.len() as u64 + 10)).unwrap();
+    }
+
+    #[test]
+    fn test_handshake_con_vectores() {
+        comprobar::<8, 3, 11, Max>("ArgMaxStream8", &casos(100), 1);
+        comprobar::<8, 3, 11, Min>("ArgMinStream8", &casos(100), 0xdead_beef);
+        comprobar::<2, 1, 9, Max>("ArgMaxStream2", &casos(50), 7);
+    }
+
+    #[test]
+    fn test_verilog_de_la_reduccion_con_handshake() {
+        let mut uut = Arbol::<8, 3, 11, Max>::default();
+        uut.connect_all();
+        let codigo = tfg_hdl_common::generate_named_verilog(&uut, "ArgMaxStream8").unwrap();
+        // ReduceTree, parado por el enable del handshake
+        assert!(codigo.contains("arbol$enable = control$enable;"), "{}", codigo);
+        assert!(codigo.contains("control$valid = arbol$valid_out;"));
+        assert!(codigo.contains("ready_in = control$ready_in;"));
+        // Valor e índice en el mismo dato
+        assert!(codigo.contains("result = $signed(((control$data_out >> desplazamiento) & 8'hff));"));
+        assert!(codigo.contains("index = ((control$data_out) & 3'h7);"));
+    }
+}
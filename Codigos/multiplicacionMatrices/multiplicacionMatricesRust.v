@@ -1,3 +1,5 @@
+// Módulo multiplicacionMatrices generado con RustHDL
+// Crate: multiplicacionMatrices v0.1.0
 
 
 module multiplicacionMatrices(rst,a$0,a$1,a$2,a$3,b$0,b$1,b$2,b$3,result$0,result$1,result$2,result$3);
@@ -33,4 +35,4 @@ module multiplicacionMatrices(rst,a$0,a$1,a$2,a$3,b$0,b$1,b$2,b$3,result$0,resul
         end
     end
     
-endmodule // top
+endmodule // multiplicacionMatrices
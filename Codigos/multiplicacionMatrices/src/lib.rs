@@ -0,0 +1,230 @@
+use rust_hdl::prelude::*;
+// signed_bit_cast de RustHDL desborda la pila con -8
+use tfg_hdl_common::signed_bit_cast;
+
+mod matmul;
+pub use matmul::{accumulator_width, MatMul};
+pub use tfg_hdl_common::{Booth, MulAlgorithm, Operator, ShiftAdd, Wallace};
+
+// === Módulo de multiplicación de matrices 2x2 ===
+#[derive(LogicBlock, Default, Clone)]
+#[allow(non_camel_case_types)]
+pub struct multiplicacionMatrices {
+    pub rst: Signal<In, Bit>,
+    // Entradas: 2 matrices de 2x2 (4 elementos cada una)
+    pub a: [Signal<In, Signed<4>>; 4],
+    pub b: [Signal<In, Signed<4>>; 4],
+    // Salida: matriz 2x2 resultado
+    pub result: [Signal<Out, Signed<32>>; 4],
+}
+
+impl Logic for multiplicacionMatrices {
+    #[hdl_gen]
+    fn update(&mut self) {
+        if self.rst.val() {
+           self.result[0].next = 0.into(); self.result[1].next = 0.into(); self.result[2].next = 0.into(); self.result[3].next = 0.into(); 
+        }
+        else {
+            self.result[0].next = (signed_bit_cast::<16, 4>(self.a[0].val()) * signed_bit_cast::<16, 4>(self.b[0].val())) + (signed_bit_cast::<16, 4>(self.a[1].val()) * signed_bit_cast::<16, 4>(self.b[2].val()));
+            self.result[1].next = (signed_bit_cast::<16, 4>(self.a[0].val()) * signed_bit_cast::<16, 4>(self.b[1].val())) + (signed_bit_cast::<16, 4>(self.a[1].val()) * signed_bit_cast::<16, 4>(self.b[3].val()));
+            self.result[2].next = (signed_bit_cast::<16, 4>(self.a[2].val()) * signed_bit_cast::<16, 4>(self.b[0].val())) + (signed_bit_cast::<16, 4>(self.a[3].val()) * signed_bit_cast::<16, 4>(self.b[2].val()));
+            self.result[3].next = (signed_bit_cast::<16, 4>(self.a[2].val()) * signed_bit_cast::<16, 4>(self.b[1].val())) + (signed_bit_cast::<16, 4>(self.a[3].val()) * signed_bit_cast::<16, 4>(self.b[3].val()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use tfg_hdl_common::{TestVector, TestVectors};
+
+    // Casos dirigidos: (A, B, C esperado). Salen de aquí tanto el testbench
+    // de RustHDL como el de Verilog
+    type Caso = ([i64; 4], [i64; 4], [i64; 4], &'static str);
+    const CASOS: [Caso; 6] = [
+        // [1 2; 3 4] x [5 6; 7 -8]
+        ([1, 2, 3, 4], [5, 6, 7, -8], [19, -10, 43, -14], "Multiplicación 1"),
+        // [-1 0; 0 -1] x [2 3; 4 5]
+        ([-1, 0, 0, -1], [2, 3, 4, 5], [-2, -3, -4, -5], "Multiplicación 2"),
+        // [3 1; 0 2] x [1 0; 2 1]
+        ([3, 1, 0, 2], [1, 0, 2, 1], [5, 1, 4, 2], "Multiplicación 3"),
+        // [2 2; 1 3] x [1 4; 2 0]
+        ([2, 2, 1, 3], [1, 4, 2, 0], [6, 8, 7, 4], "Multiplicación 4"),
+        // [-8 -8; -8 -8] x [-8 -8; -8 -8]
+        ([-8, -8, -8, -8], [-8, -8, -8, -8], [128, 128, 128, 128], "Multiplicación 5: Máximo"),
+        // [-8 -8; -8 -8] x [7 7; 7 7]
+        ([-8, -8, -8, -8], [7, 7, 7, 7], [-112, -112, -112, -112], "Multiplicación 6: Mínimo"),
+    ];
+
+    fn casos() -> TestVectors<multiplicacionMatrices> {
+        let mut tb = TestVectors::<multiplicacionMatrices>::new("multiplicacionMatrices");
+        let rst = tb.input("rst", |x| &mut x.rst);
+        let a = [
+            tb.input("a$0", |x| &mut x.a[0]),
+            tb.input("a$1", |x| &mut x.a[1]),
+            tb.input("a$2", |x| &mut x.a[2]),
+            tb.input("a$3", |x| &mut x.a[3]),
+        ];
+        let b = [
+            tb.input("b$0", |x| &mut x.b[0]),
+            tb.input("b$1", |x| &mut x.b[1]),
+            tb.input("b$2", |x| &mut x.b[2]),
+            tb.input("b$3", |x| &mut x.b[3]),
+        ];
+        let result = [
+            tb.output("result$0", |x| &x.result[0]),
+            tb.output("result$1", |x| &x.result[1]),
+            tb.output("result$2", |x| &x.result[2]),
+            tb.output("result$3", |x| &x.result[3]),
+        ];
+
+        tb.push(TestVector::new("reset").set(rst, 1).expect(result[0], 0));
+        for (va, vb, vc, nombre) in CASOS {
+            let mut vector = TestVector::new(nombre).set(rst, 0);
+            for i in 0..4 {
+                vector = vector.set(a[i], va[i]).set(b[i], vb[i]).expect(result[i], vc[i]);
+            }
+            tb.push(vector);
+        }
+        tb
+    }
+
+    // Test con RustHDL y comparación con Verilog
+    #[test]
+    fn test_multiplicacion_matrices_casos() -> anyhow::Result<()> {
+        let mut uut = multiplicacionMatrices::default();
+        uut.connect_all();
+        let tb = casos();
+
+        //Co-simulación: el mismo testbench en RustHDL y en Icarus, comparado ciclo a ciclo
+        if let Some(informe) = tb.verify(&uut, 1_000)? {
+            println!("(iverilog) Salida de Verilog:\n{}", informe.stdout);
+        }
+
+        Ok(())
+    }
+
+    // Barrido de RustHDL con todas las matrices de valores entre -2 y 2
+    #[test]
+    fn test_multiplicacion_matrices() -> anyhow::Result<()> {
+
+        let mut uut = multiplicacionMatrices::default();
+        uut.connect_all();
+
+        // Generamos la simulación y le añadimos el testbench
+        let mut sim = Simulation::<multiplicacionMatrices>::new();
+        sim.add_testbench(move |mut ep| {
+            let mut x = ep.init()?;
+            /*
+            // Definimos los casos de prueba (A, B, C esperado)
+            let test_cases = vec![
+                // Multiplicación 5 (Máximo)
+                (
+                    [-7, -7, -7, -7], [-7, -7, -7, -7], [98, 98, 98, 98]
+                ),
+                // Multiplicación 6 (Mínimo)
+                (
+                    [-7, -7, -7, -7], [7, 7, 7, 7], [-98, -98, -98, -98]
+                ),
+            ];
+
+            for (idx, (a_vals, b_vals, expected_c)) in test_cases.iter().enumerate() {
+                println!("\n=== Multiplicación {} ===", idx + 1);
+
+                // Cargamos la matriz A
+                for i in 0..4 {
+                    x.a[i].next = Signed::<4>::from(a_vals[i]);
+                }
+                // Cargamos la matriz B
+                for i in 0..4 {
+                    x.b[i].next = Signed::<4>::from(b_vals[i]);
+                }
+
+                let x_clone = x.clone();
+                let x = ep.wait(1, x_clone)?;
+
+                println!("A = | {} {} |\n    | {} {} |", a_vals[0], a_vals[1], a_vals[2], a_vals[3]);
+                println!("B = | {} {} |\n    | {} {} |", b_vals[0], b_vals[1], b_vals[2], b_vals[3]);
+                println!("C = | {} {} |", x.result[0].val().bigint(), x.result[1].val().bigint());
+                println!("    | {} {} | // Esperado: | {} {} | | {} {} |",
+                    x.result[2].val().bigint(), x.result[3].val().bigint(),
+                    expected_c[0], expected_c[1], expected_c[2], expected_c[3]);
+
+                // Aquí puedes hacer asserts si quieres (opcional)
+                sim_assert_eq!(ep, x.result[0].val(), Signed::<32>::from(expected_c[0]), x);
+                sim_assert_eq!(ep, x.result[1].val(), Signed::<32>::from(expected_c[1]), x);
+                sim_assert_eq!(ep, x.result[2].val(), Signed::<32>::from(expected_c[2]), x);
+                sim_assert_eq!(ep, x.result[3].val(), Signed::<32>::from(expected_c[3]), x);
+            }*/
+
+            println!("Iniciando simulación de multiplicación de matrices...");
+
+            let valores: Vec<i32> = (-2..=2).collect(); // Limita el rango por ahora
+
+            x.rst.next = true; 
+            ep.wait(1, x.clone())?;
+            x.rst.next = false;
+            ep.wait(1, x.clone())?;
+
+            for &a0 in &valores {
+                for &a1 in &valores {
+                    for &a2 in &valores {
+                        for &a3 in &valores {
+                            for &b0 in &valores {
+                                for &b1 in &valores {
+                                    for &b2 in &valores {
+                                        for &b3 in &valores {
+
+                                            x.a[0].next = Signed::<4>::from(a0 as i64);
+                                            x.a[1].next = Signed::<4>::from(a1 as i64);
+                                            x.a[2].next = Signed::<4>::from(a2 as i64);
+                                            x.a[3].next = Signed::<4>::from(a3 as i64);
+
+                                            x.b[0].next = Signed::<4>::from(b0 as i64);
+                                            x.b[1].next = Signed::<4>::from(b1 as i64);
+                                            x.b[2].next = Signed::<4>::from(b2 as i64);
+                                            x.b[3].next = Signed::<4>::from(b3 as i64);
+
+                                            let x_clone = x.clone();
+                                            let x = ep.wait(1, x_clone)?;
+
+                                            // Calculamos el resultado esperado en software para validar
+                                            let c0 = a0 * b0 + a1 * b2;
+                                            let c1 = a0 * b1 + a1 * b3;
+                                            let c2 = a2 * b0 + a3 * b2;
+                                            let c3 = a2 * b1 + a3 * b3;
+                                            /*
+                                            println!("\n=== Multiplicación de matrices ===");
+                                            println!("A = | {} {} |\n    | {} {} |", a0, a1, a2, a3);
+                                            println!("B = | {} {} |\n    | {} {} |", b0, b1, b2, b3);
+                                            println!("C = | {} {} |", x.result[0].val().bigint(), x.result[1].val().bigint());
+                                            println!("    | {} {} | // Esperado: | {} {} | | {} {} |",
+                                            x.result[2].val().bigint(), x.result[3].val().bigint(), c0, c1, c2, c3);
+                                            */
+                                            // Validaciones
+                                            sim_assert_eq!(ep, x.result[0].val(), Signed::<32>::from(c0 as i64), x);
+                                            sim_assert_eq!(ep, x.result[1].val(), Signed::<32>::from(c1 as i64), x);
+                                            sim_assert_eq!(ep, x.result[2].val(), Signed::<32>::from(c2 as i64), x);
+                                            sim_assert_eq!(ep, x.result[3].val(), Signed::<32>::from(c3 as i64), x);
+
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            ep.done(x)?;
+
+            Ok(())
+        });
+
+        sim.run_to_file(Box::new(uut), 1000000000, "multiplicacionMatricesWave.vcd")
+            .map_err(|err| anyhow!("{:?}", err))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,328 @@
+use rust_hdl::prelude::*;
+use tfg_hdl_common::{MulAlgorithm, Operator, SignedMul};
+
+// Multiplicador combinacional de matrices C = A·B, con A de MxK, B de KxN y
+// elementos con signo de W bits. Es multiplicacionMatrices sin fijar el
+// tamaño: cada elemento de C es la suma de los K productos a[i][k]·b[k][j],
+// con un multiplicador para cada producto.
+//
+// El Rust estable no deja calcular la anchura de la salida en el tipo, así
+// que se pasa como parámetro O y se comprueba al construir. Tiene que ser
+// accumulator_width(W, K), la mínima en la que caben todos los resultados.
+// Con tamaños fijos las macros matmul! y square_matmul! calculan O y los
+// productos de abajo a partir de los tamaños y de W:
+//
+//     matmul!(2, 3, 4, 8)     // MatMul<2, 3, 4, 8, 18, 6, 12, 8>
+//     square_matmul!(3, 8)    // MatMul<3, 3, 3, 8, 18, 9, 9, 9>
+//
+// A elige el algoritmo de los multiplicadores (por defecto el operador * de
+// Verilog) y se les puede pasar al final: matmul!(2, 3, 4, 8, Booth). Tiene
+// que ser combinacional, porque MatMul no tiene reloj; un Registered se
+// comprueba al construir.
+//
+// #[hdl_gen] sólo sabe resolver el primer índice de un array, así que, como
+// en SequentialMatMul, las matrices van por filas en puertos planos
+// (a[i·K + k], b[k·N + j] y result[i·N + j]), y MK = M·K, KN = K·N y MN = M·N
+// se pasan como parámetros porque tampoco se puede escribir [_; M * K].
+#[derive(LogicBlock, Clone)]
+pub struct MatMul<
+    const M: usize,
+    const K: usize,
+    const N: usize,
+    const W: usize,
+    const O: usize,
+    const MK: usize,
+    const KN: usize,
+    const MN: usize,
+    A: MulAlgorithm = Operator,
+> {
+    pub rst: Signal<In, Bit>,
+    pub a: [Signal<In, Signed<W>>; MK],
+    pub b: [Signal<In, Signed<W>>; KN],
+    pub result: [Signal<Out, Signed<O>>; MN],
+    // El producto k del elemento e de C está en el multiplicador e·K + k
+    multiplicadores: Vec<SignedMul<W, O, A>>,
+    suma: Signal<Local, Signed<O>>,
+    // No hay reloj: el clk de los multiplicadores, que son combinacionales,
+    // queda fijo a 0
+    sin_reloj: Constant<Clock>,
+}
+
+// Tipo del multiplicador de una matriz de MxK por otra de KxN con elementos de
+// W bits, con la salida de accumulator_width(W, K) bits y, si se da, el
+// algoritmo de los multiplicadores
+#[macro_export]
+macro_rules! matmul {
+    ($m:expr, $k:expr, $n:expr, $w:expr) => {
+        $crate::matmul!($m, $k, $n, $w, $crate::Operator)
+    };
+    ($m:expr, $k:expr, $n:expr, $w:expr, $a:ty) => {
+        $crate::MatMul<
+            { $m },
+            { $k },
+            { $n },
+            { $w },
+            { $crate::accumulator_width($w, $k) },
+            { $m * $k },
+            { $k * $n },
+            { $m * $n },
+            $a,
+        >
+    };
+}
+
+// Lo mismo para matrices cuadradas de NxN
+#[macro_export]
+macro_rules! square_matmul {
+    ($n:expr, $w:expr) => {
+        $crate::matmul!($n, $n, $n, $w)
+    };
+    ($n:expr, $w:expr, $a:ty) => {
+        $crate::matmul!($n, $n, $n, $w, $a)
+    };
+}
+
+// Anchura mínima de la salida: 2W bits por producto más ceil(log2 K) para la
+// suma de K productos
+pub const fn accumulator_width(w: usize, k: usize) -> usize {
+    2 * w + k.next_power_of_two().trailing_zeros() as usize
+}
+
+impl<
+        const M: usize,
+        const K: usize,
+        const N: usize,
+        const W: usize,
+        const O: usize,
+        const MK: usize,
+        const KN: usize,
+        const MN: usize,
+        A: MulAlgorithm,
+    > Default for MatMul<M, K, N, W, O, MK, KN, MN, A>
+{
+    fn default() -> Self {
+        const {
+            assert!(M >= 1 && K >= 1 && N >= 1, "las matrices no pueden estar vacías");
+            assert!(
+                MK == M * K && KN == K * N && MN == M * N,
+                "MK, KN y MN tienen que ser M·K, K·N y M·N"
+            );
+            assert!(O == accumulator_width(W, K), "O tiene que ser accumulator_width(W, K)");
+            assert!(A::LATENCY == 0, "los multiplicadores tienen que ser combinacionales");
+        }
+        Self {
+            rst: Default::default(),
+            a: std::array::from_fn(|_| Default::default()),
+            b: std::array::from_fn(|_| Default::default()),
+            result: std::array::from_fn(|_| Default::default()),
+            multiplicadores: (0..MN * K).map(|_| Default::default()).collect(),
+            suma: Default::default(),
+            sin_reloj: Constant::new(Clock { clk: false }),
+        }
+    }
+}
+
+impl<
+        const M: usize,
+        const K: usize,
+        const N: usize,
+        const W: usize,
+        const O: usize,
+        const MK: usize,
+        const KN: usize,
+        const MN: usize,
+        A: MulAlgorithm,
+    > Logic for MatMul<M, K, N, W, O, MK, KN, MN, A>
+{
+    #[hdl_gen]
+    fn update(&mut self) {
+        // K y N entran como `kk` y `nn`: bucles de una vuelta, ver tfg_hdl_common
+        for kk in K..(K + 1) {
+            for nn in N..(N + 1) {
+                for i in 0..M {
+                    for j in 0..N {
+                        for k in 0..K {
+                            self.multiplicadores[(i * nn + j) * kk + k].clk.next = self.sin_reloj.val();
+                            self.multiplicadores[(i * nn + j) * kk + k].a.next = self.a[i * kk + k].val();
+                            self.multiplicadores[(i * nn + j) * kk + k].b.next = self.b[k * nn + j].val();
+                        }
+                        // Con O = accumulator_width(W, K) la suma siempre cabe
+                        self.suma.next = 0.into();
+                        if !self.rst.val() {
+                            for k in 0..K {
+                                self.suma.next = self.suma.val() + self.multiplicadores[(i * nn + j) * kk + k].p.val();
+                            }
+                        }
+                        self.result[i * nn + j].next = self.suma.val();
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Booth, Wallace};
+    use tfg_hdl_common::{TestVector, TestVectors};
+
+    // Producto de referencia en i64
+    fn producto<const M: usize, const K: usize, const N: usize>(a: &[[i64; K]; M], b: &[[i64; N]; K]) -> [[i64; N]; M] {
+        std::array::from_fn(|i| std::array::from_fn(|j| (0..K).map(|k| a[i][k] * b[k][j]).sum()))
+    }
+
+    // Matrices A de MxK y B de KxN de un caso
+    type Pareja<const M: usize, const K: usize, const N: usize> = ([[i64; K]; M], [[i64; N]; K]);
+
+    // Testbench con reset y un caso por pareja de matrices
+    fn casos<
+        const M: usize,
+        const K: usize,
+        const N: usize,
+        const W: usize,
+        const O: usize,
+        const MK: usize,
+        const KN: usize,
+        const MN: usize,
+        A: MulAlgorithm,
+    >(
+        modulo: &str,
+        parejas: &[Pareja<M, K, N>],
+    ) -> TestVectors<MatMul<M, K, N, W, O, MK, KN, MN, A>> {
+        let mut tb = TestVectors::<MatMul<M, K, N, W, O, MK, KN, MN, A>>::new(modulo);
+        let rst = tb.input("rst", |x| &mut x.rst);
+        let a: Vec<_> = (0..MK)
+            .map(|e| tb.input(&format!("a${e}"), move |x| &mut x.a[e]))
+            .collect();
+        let b: Vec<_> = (0..KN)
+            .map(|e| tb.input(&format!("b${e}"), move |x| &mut x.b[e]))
+            .collect();
+        let result: Vec<_> = (0..MN)
+            .map(|e| tb.output(&format!("result${e}"), move |x| &x.result[e]))
+            .collect();
+
+        tb.push(TestVector::new("reset").set(rst, 1).expect(result[0], 0));
+        for (n, (va, vb)) in parejas.iter().enumerate() {
+            let vc = producto(va, vb);
+            let mut vector = TestVector::new(&format!("caso {}", n)).set(rst, 0);
+            for i in 0..M {
+                for k in 0..K {
+                    vector = vector.set(a[i * K + k], va[i][k]);
+                }
+                for j in 0..N {
+                    vector = vector.expect(result[i * N + j], vc[i][j]);
+                }
+            }
+            for k in 0..K {
+                for j in 0..N {
+                    vector = vector.set(b[k * N + j], vb[k][j]);
+                }
+            }
+            tb.push(vector);
+        }
+        tb
+    }
+
+    // Parejas de matrices con los extremos de W bits y valores variados
+    fn parejas<const M: usize, const K: usize, const N: usize, const W: usize>() -> Vec<Pareja<M, K, N>> {
+        let minimo = -(1_i64 << (W - 1));
+        let maximo = (1_i64 << (W - 1)) - 1;
+        let variado =
+            |semilla: usize, i: usize, j: usize| ((semilla * 31 + i * 7 + j * 13) as i64 % (2 * maximo + 2)) + minimo;
+        vec![
+            ([[minimo; K]; M], [[minimo; N]; K]),
+            ([[minimo; K]; M], [[maximo; N]; K]),
+            ([[maximo; K]; M], [[maximo; N]; K]),
+            (
+                std::array::from_fn(|i| std::array::from_fn(|k| variado(1, i, k))),
+                std::array::from_fn(|k| std::array::from_fn(|j| variado(2, k, j))),
+            ),
+            (
+                std::array::from_fn(|i| std::array::from_fn(|k| variado(3, i, k))),
+                std::array::from_fn(|k| std::array::from_fn(|j| variado(4, k, j))),
+            ),
+        ]
+    }
+
+    fn comprobar<
+        const M: usize,
+        const K: usize,
+        const N: usize,
+        const W: usize,
+        const O: usize,
+        const MK: usize,
+        const KN: usize,
+        const MN: usize,
+        A: MulAlgorithm,
+    >(
+        modulo: &str,
+        mut uut: MatMul<M, K, N, W, O, MK, KN, MN, A>,
+    ) -> anyhow::Result<()> {
+        uut.connect_all();
+        let tb = casos::<M, K, N, W, O, MK, KN, MN, A>(modulo, &parejas::<M, K, N, W>());
+
+        tb.verify(&uut, 1_000)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_anchura_del_acumulador() {
+        assert_eq!(accumulator_width(4, 1), 8);
+        assert_eq!(accumulator_width(4, 2), 9);
+        assert_eq!(accumulator_width(8, 3), 18);
+        assert_eq!(accumulator_width(8, 4), 18);
+        assert_eq!(accumulator_width(8, 5), 19);
+        // Caso peor: K productos de (-2^(W-1))·(-2^(W-1)) = 2^(2W-2) cada uno
+        for w in 2..16 {
+            for k in 1..20 {
+                let peor = k as i128 * (1_i128 << (2 * w - 2));
+                assert!(peor < 1_i128 << (accumulator_width(w, k) - 1), "W = {w}, K = {k}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_cuadradas() -> anyhow::Result<()> {
+        comprobar("MatMul2x2", <square_matmul!(2, 4)>::default())?;
+        comprobar("MatMul3x3", <square_matmul!(3, 8)>::default())?;
+        comprobar("MatMul4x4", <square_matmul!(4, 8)>::default())
+    }
+
+    #[test]
+    fn test_no_cuadradas() -> anyhow::Result<()> {
+        comprobar("MatMul2x3x4", <matmul!(2, 3, 4, 4)>::default())?;
+        comprobar("MatMul1x5x1", <matmul!(1, 5, 1, 6)>::default())?;
+        comprobar("MatMul3x1x2", <matmul!(3, 1, 2, 4)>::default())
+    }
+
+    // Los multiplicadores de Booth y de Wallace en vez del operador *
+    #[test]
+    fn test_booth_y_wallace() -> anyhow::Result<()> {
+        comprobar("MatMul3x3Booth", <square_matmul!(3, 8, Booth)>::default())?;
+        comprobar("MatMul2x3x4Wallace", <matmul!(2, 3, 4, 4, Wallace)>::default())
+    }
+
+    #[test]
+    fn test_tipos_de_las_macros() {
+        let _: MatMul<2, 3, 4, 8, 18, 6, 12, 8> = <matmul!(2, 3, 4, 8)>::default();
+        let _: MatMul<3, 3, 3, 8, 18, 9, 9, 9> = <square_matmul!(3, 8)>::default();
+        let _: MatMul<2, 3, 4, 8, 18, 6, 12, 8, Booth> = <matmul!(2, 3, 4, 8, Booth)>::default();
+        let _: MatMul<3, 3, 3, 8, 18, 9, 9, 9, Wallace> = <square_matmul!(3, 8, Wallace)>::default();
+    }
+
+    #[test]
+    fn test_verilog_de_matmul() {
+        let mut uut = MatMul::<2, 3, 2, 4, { accumulator_width(4, 3) }, 6, 6, 4>::default();
+        uut.connect_all();
+        let codigo = tfg_hdl_common::generate_named_verilog(&uut, "MatMul2x3x2").unwrap();
+        assert!(codigo.contains("input wire signed [3:0] a$5;"), "{}", codigo);
+        assert!(codigo.contains("output reg signed [9:0] result$3;"));
+        // El elemento C[1][0] (el 2) usa los multiplicadores 6, 7 y 8, con
+        // la fila 1 de A y la columna 0 de B
+        assert!(codigo.contains("multiplicadores$7$a = a$4;"));
+        assert!(codigo.contains("multiplicadores$7$b = b$2;"));
+        assert!(codigo.contains("suma = suma + multiplicadores$8$p;"));
+        assert!(codigo.contains("result$2 = suma;"));
+    }
+}
@@ -1,3 +1,5 @@
+// Módulo MultiplicacionMatricesSecuencial generado con RustHDL
+// Crate: multiplicacionMatricesSecuencial v0.1.0
 
 
 module MultiplicacionMatricesSecuencial(clk,rst,start,a$0,a$1,a$2,a$3,b$0,b$1,b$2,b$3,result$0,result$1,result$2,result$3,done);
@@ -40,17 +42,17 @@ module MultiplicacionMatricesSecuencial(clk,rst,start,a$0,a$1,a$2,a$3,b$0,b$1,b$
     reg  temp2$clk;
     
     // Sub module instances
-    top$state state(
+    MultiplicacionMatricesSecuencial$state state(
         .d(state$d),
         .q(state$q),
         .clk(state$clk)
     );
-    top$temp1 temp1(
+    MultiplicacionMatricesSecuencial$temp1 temp1(
         .d(temp1$d),
         .q(temp1$q),
         .clk(temp1$clk)
     );
-    top$temp2 temp2(
+    MultiplicacionMatricesSecuencial$temp2 temp2(
         .d(temp2$d),
         .q(temp2$q),
         .clk(temp2$clk)
@@ -116,10 +118,10 @@ module MultiplicacionMatricesSecuencial(clk,rst,start,a$0,a$1,a$2,a$3,b$0,b$1,b$
         end
     end
     
-endmodule // top
+endmodule // MultiplicacionMatricesSecuencial
 
 
-module top$state(d,q,clk);
+module MultiplicacionMatricesSecuencial$state(d,q,clk);
     
     // Module arguments
     input wire  [2:0] d;
@@ -140,10 +142,10 @@ module top$state(d,q,clk);
     end
     
     always @(posedge clk) q <= d;
-endmodule // top$state
+endmodule // MultiplicacionMatricesSecuencial$state
 
 
-module top$temp1(d,q,clk);
+module MultiplicacionMatricesSecuencial$temp1(d,q,clk);
     
     // Module arguments
     input wire signed [31:0] d;
@@ -156,10 +158,10 @@ module top$temp1(d,q,clk);
     end
     
     always @(posedge clk) q <= d;
-endmodule // top$temp1
+endmodule // MultiplicacionMatricesSecuencial$temp1
 
 
-module top$temp2(d,q,clk);
+module MultiplicacionMatricesSecuencial$temp2(d,q,clk);
     
     // Module arguments
     input wire signed [31:0] d;
@@ -172,4 +174,4 @@ module top$temp2(d,q,clk);
     end
     
     always @(posedge clk) q <= d;
-endmodule // top$temp2
+endmodule // MultiplicacionMatricesSecuencial$temp2
@@ -0,0 +1,664 @@
+// Módulo SequentialMatMul generado con RustHDL
+// Crate: multiplicacionMatricesSecuencial v0.1.0
+
+
+module SequentialMatMul(clk,rst,start,a$0,a$1,a$2,a$3,b$0,b$1,b$2,b$3,result$0,result$1,result$2,result$3,done,overflow);
+    
+    // Module arguments
+    input wire  clk;
+    input wire  rst;
+    input wire  start;
+    input wire signed [3:0] a$0;
+    input wire signed [3:0] a$1;
+    input wire signed [3:0] a$2;
+    input wire signed [3:0] a$3;
+    input wire signed [3:0] b$0;
+    input wire signed [3:0] b$1;
+    input wire signed [3:0] b$2;
+    input wire signed [3:0] b$3;
+    output reg signed [8:0] result$0;
+    output reg signed [8:0] result$1;
+    output reg signed [8:0] result$2;
+    output reg signed [8:0] result$3;
+    output reg  done;
+    output reg  overflow;
+    
+    // Constant declarations
+    localparam  fila_de$0 = 16'h0;
+    localparam  fila_de$1 = 16'h0;
+    localparam  fila_de$2 = 16'h1;
+    localparam  fila_de$3 = 16'h1;
+    localparam  columna_de$0 = 16'h0;
+    localparam  columna_de$1 = 16'h1;
+    localparam  columna_de$2 = 16'h0;
+    localparam  columna_de$3 = 16'h1;
+    localparam  paso_a$0 = 16'h0;
+    localparam  paso_a$1 = 16'h0;
+    localparam  paso_a$2 = 16'h0;
+    localparam  paso_a$3 = 16'h0;
+    localparam  paso_b$0 = 16'h0;
+    localparam  paso_b$1 = 16'h0;
+    localparam  paso_b$2 = 16'h0;
+    localparam  paso_b$3 = 16'h0;
+    localparam  ultimo = 16'h1;
+    localparam  ultimo_paso = 16'h0;
+    
+    // Stub signals
+    reg signed [3:0] multiplicadores$0$a;
+    reg signed [3:0] multiplicadores$0$b;
+    wire signed [8:0] multiplicadores$0$p;
+    reg  multiplicadores$0$clk;
+    reg signed [3:0] multiplicadores$1$a;
+    reg signed [3:0] multiplicadores$1$b;
+    wire signed [8:0] multiplicadores$1$p;
+    reg  multiplicadores$1$clk;
+    reg  [8:0] sumadores$0$a;
+    reg  [8:0] sumadores$0$b;
+    wire  [8:0] sumadores$0$sum;
+    wire  sumadores$0$overflow;
+    reg  [8:0] sumadores$1$a;
+    reg  [8:0] sumadores$1$b;
+    wire  [8:0] sumadores$1$sum;
+    wire  sumadores$1$overflow;
+    reg signed [8:0] resultados$0$d;
+    wire signed [8:0] resultados$0$q;
+    reg  resultados$0$clk;
+    reg signed [8:0] resultados$1$d;
+    wire signed [8:0] resultados$1$q;
+    reg  resultados$1$clk;
+    reg signed [8:0] resultados$2$d;
+    wire signed [8:0] resultados$2$q;
+    reg  resultados$2$clk;
+    reg signed [8:0] resultados$3$d;
+    wire signed [8:0] resultados$3$q;
+    reg  resultados$3$clk;
+    reg signed [8:0] suma$d;
+    wire signed [8:0] suma$q;
+    reg  suma$clk;
+    reg  ocupado$d;
+    wire  ocupado$q;
+    reg  ocupado$clk;
+    reg  terminado$d;
+    wire  terminado$q;
+    reg  terminado$clk;
+    reg  desborde$d;
+    wire  desborde$q;
+    reg  desborde$clk;
+    reg  [15:0] fila$d;
+    wire  [15:0] fila$q;
+    reg  fila$clk;
+    reg  [15:0] columna$d;
+    wire  [15:0] columna$q;
+    reg  columna$clk;
+    reg  [15:0] paso$d;
+    wire  [15:0] paso$q;
+    reg  paso$clk;
+    
+    // Local signals
+    reg signed [8:0] parcial$0;
+    reg signed [8:0] parcial$1;
+    reg signed [8:0] total;
+    reg  desborda;
+    
+    // Sub module instances
+    SequentialMatMul$multiplicadores$0 multiplicadores$0(
+        .a(multiplicadores$0$a),
+        .b(multiplicadores$0$b),
+        .p(multiplicadores$0$p),
+        .clk(multiplicadores$0$clk)
+    );
+    SequentialMatMul$multiplicadores$1 multiplicadores$1(
+        .a(multiplicadores$1$a),
+        .b(multiplicadores$1$b),
+        .p(multiplicadores$1$p),
+        .clk(multiplicadores$1$clk)
+    );
+    SequentialMatMul$sumadores$0 sumadores$0(
+        .a(sumadores$0$a),
+        .b(sumadores$0$b),
+        .sum(sumadores$0$sum),
+        .overflow(sumadores$0$overflow)
+    );
+    SequentialMatMul$sumadores$1 sumadores$1(
+        .a(sumadores$1$a),
+        .b(sumadores$1$b),
+        .sum(sumadores$1$sum),
+        .overflow(sumadores$1$overflow)
+    );
+    SequentialMatMul$resultados$0 resultados$0(
+        .d(resultados$0$d),
+        .q(resultados$0$q),
+        .clk(resultados$0$clk)
+    );
+    SequentialMatMul$resultados$1 resultados$1(
+        .d(resultados$1$d),
+        .q(resultados$1$q),
+        .clk(resultados$1$clk)
+    );
+    SequentialMatMul$resultados$2 resultados$2(
+        .d(resultados$2$d),
+        .q(resultados$2$q),
+        .clk(resultados$2$clk)
+    );
+    SequentialMatMul$resultados$3 resultados$3(
+        .d(resultados$3$d),
+        .q(resultados$3$q),
+        .clk(resultados$3$clk)
+    );
+    SequentialMatMul$suma suma(
+        .d(suma$d),
+        .q(suma$q),
+        .clk(suma$clk)
+    );
+    SequentialMatMul$ocupado ocupado(
+        .d(ocupado$d),
+        .q(ocupado$q),
+        .clk(ocupado$clk)
+    );
+    SequentialMatMul$terminado terminado(
+        .d(terminado$d),
+        .q(terminado$q),
+        .clk(terminado$clk)
+    );
+    SequentialMatMul$desborde desborde(
+        .d(desborde$d),
+        .q(desborde$q),
+        .clk(desborde$clk)
+    );
+    SequentialMatMul$fila fila(
+        .d(fila$d),
+        .q(fila$q),
+        .clk(fila$clk)
+    );
+    SequentialMatMul$columna columna(
+        .d(columna$d),
+        .q(columna$q),
+        .clk(columna$clk)
+    );
+    SequentialMatMul$paso paso(
+        .d(paso$d),
+        .q(paso$q),
+        .clk(paso$clk)
+    );
+    
+    // Update code
+    always @(*) begin
+        resultados$0$clk = clk;
+        result$0 = resultados$0$q;
+        resultados$1$clk = clk;
+        result$1 = resultados$1$q;
+        resultados$2$clk = clk;
+        result$2 = resultados$2$q;
+        resultados$3$clk = clk;
+        result$3 = resultados$3$q;
+        suma$clk = clk;
+        ocupado$clk = clk;
+        terminado$clk = clk;
+        desborde$clk = clk;
+        fila$clk = clk;
+        columna$clk = clk;
+        paso$clk = clk;
+        multiplicadores$0$clk = clk;
+        multiplicadores$1$clk = clk;
+        multiplicadores$0$a = 32'h0;
+        multiplicadores$0$b = 32'h0;
+        multiplicadores$1$a = 32'h0;
+        multiplicadores$1$b = 32'h0;
+        if (ocupado$q & (fila$q == fila_de$0) & (paso$q == paso_a$0)) begin
+            multiplicadores$0$a = a$0;
+        end
+        if (ocupado$q & (columna$q == columna_de$0) & (paso$q == paso_b$0)) begin
+            multiplicadores$0$b = b$0;
+        end
+        if (ocupado$q & (fila$q == fila_de$1) & (paso$q == paso_a$1)) begin
+            multiplicadores$1$a = a$1;
+        end
+        if (ocupado$q & (columna$q == columna_de$1) & (paso$q == paso_b$1)) begin
+            multiplicadores$0$b = b$1;
+        end
+        if (ocupado$q & (fila$q == fila_de$2) & (paso$q == paso_a$2)) begin
+            multiplicadores$0$a = a$2;
+        end
+        if (ocupado$q & (columna$q == columna_de$2) & (paso$q == paso_b$2)) begin
+            multiplicadores$1$b = b$2;
+        end
+        if (ocupado$q & (fila$q == fila_de$3) & (paso$q == paso_a$3)) begin
+            multiplicadores$1$a = a$3;
+        end
+        if (ocupado$q & (columna$q == columna_de$3) & (paso$q == paso_b$3)) begin
+            multiplicadores$1$b = b$3;
+        end
+        sumadores$0$a = $unsigned(suma$q);
+        sumadores$0$b = $unsigned(multiplicadores$0$p);
+        parcial$0 = $signed(sumadores$0$sum);
+        sumadores$1$a = $unsigned(parcial$0);
+        sumadores$1$b = $unsigned(multiplicadores$1$p);
+        parcial$1 = $signed(sumadores$1$sum);
+        total = parcial$1;
+        desborda = 1'b0;
+        if (sumadores$0$overflow) begin
+            desborda = 1'b1;
+        end
+        if (sumadores$1$overflow) begin
+            desborda = 1'b1;
+        end
+        resultados$0$d = resultados$0$q;
+        resultados$1$d = resultados$1$q;
+        resultados$2$d = resultados$2$q;
+        resultados$3$d = resultados$3$q;
+        suma$d = suma$q;
+        ocupado$d = ocupado$q;
+        terminado$d = 1'b0;
+        desborde$d = desborde$q;
+        fila$d = fila$q;
+        columna$d = columna$q;
+        paso$d = paso$q;
+        if (rst) begin
+            resultados$0$d = 32'h0;
+            resultados$1$d = 32'h0;
+            resultados$2$d = 32'h0;
+            resultados$3$d = 32'h0;
+            suma$d = 32'h0;
+            ocupado$d = 1'b0;
+            desborde$d = 1'b0;
+            fila$d = 32'h0;
+            columna$d = 32'h0;
+            paso$d = 32'h0;
+        end
+        else if (~ocupado$q) begin
+            if (start) begin
+                ocupado$d = 1'b1;
+                desborde$d = 1'b0;
+            end
+        end
+        else if (paso$q != ultimo_paso) begin
+            desborde$d = desborde$q | desborda;
+            suma$d = total;
+            paso$d = paso$q + 32'h1;
+        end
+        else begin
+            desborde$d = desborde$q | desborda;
+            if ((fila$q == fila_de$0) & (columna$q == columna_de$0)) begin
+                resultados$0$d = total;
+            end
+            if ((fila$q == fila_de$1) & (columna$q == columna_de$1)) begin
+                resultados$1$d = total;
+            end
+            if ((fila$q == fila_de$2) & (columna$q == columna_de$2)) begin
+                resultados$2$d = total;
+            end
+            if ((fila$q == fila_de$3) & (columna$q == columna_de$3)) begin
+                resultados$3$d = total;
+            end
+            suma$d = 32'h0;
+            paso$d = 32'h0;
+            if (columna$q != ultimo) begin
+                columna$d = columna$q + 32'h1;
+            end
+            else begin
+                columna$d = 32'h0;
+                if (fila$q != ultimo) begin
+                    fila$d = fila$q + 32'h1;
+                end
+                else begin
+                    fila$d = 32'h0;
+                    ocupado$d = 1'b0;
+                    terminado$d = 1'b1;
+                end
+            end
+        end
+        done = terminado$q;
+        overflow = desborde$q;
+    end
+    
+endmodule // SequentialMatMul
+
+
+module SequentialMatMul$columna(d,q,clk);
+    
+    // Module arguments
+    input wire  [15:0] d;
+    output reg  [15:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 16'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SequentialMatMul$columna
+
+
+module SequentialMatMul$desborde(d,q,clk);
+    
+    // Module arguments
+    input wire  d;
+    output reg  q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 1'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SequentialMatMul$desborde
+
+
+module SequentialMatMul$fila(d,q,clk);
+    
+    // Module arguments
+    input wire  [15:0] d;
+    output reg  [15:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 16'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SequentialMatMul$fila
+
+
+module SequentialMatMul$multiplicadores$0(a,b,p,clk);
+    
+    // Module arguments
+    input wire signed [3:0] a;
+    input wire signed [3:0] b;
+    output reg signed [8:0] p;
+    input wire  clk;
+    
+    // Stub signals
+    reg  [3:0] nucleo$a;
+    reg  [3:0] nucleo$b;
+    wire  [8:0] nucleo$resul;
+    
+    // Sub module instances
+    SequentialMatMul$multiplicadores$0$nucleo nucleo(
+        .a(nucleo$a),
+        .b(nucleo$b),
+        .resul(nucleo$resul)
+    );
+    
+    // Update code (custom)
+    always @(*) begin
+        nucleo$a = a;
+        nucleo$b = b;
+        p = nucleo$resul;
+    end
+endmodule // SequentialMatMul$multiplicadores$0
+
+
+module SequentialMatMul$multiplicadores$0$nucleo(a,b,resul);
+    
+    // Module arguments
+    input wire  [3:0] a;
+    input wire  [3:0] b;
+    output reg  [8:0] resul;
+    
+    // Update code (custom)
+    always @(*) resul = $signed(a) * $signed(b);
+endmodule // SequentialMatMul$multiplicadores$0$nucleo
+
+
+module SequentialMatMul$multiplicadores$1(a,b,p,clk);
+    
+    // Module arguments
+    input wire signed [3:0] a;
+    input wire signed [3:0] b;
+    output reg signed [8:0] p;
+    input wire  clk;
+    
+    // Stub signals
+    reg  [3:0] nucleo$a;
+    reg  [3:0] nucleo$b;
+    wire  [8:0] nucleo$resul;
+    
+    // Sub module instances
+    SequentialMatMul$multiplicadores$1$nucleo nucleo(
+        .a(nucleo$a),
+        .b(nucleo$b),
+        .resul(nucleo$resul)
+    );
+    
+    // Update code (custom)
+    always @(*) begin
+        nucleo$a = a;
+        nucleo$b = b;
+        p = nucleo$resul;
+    end
+endmodule // SequentialMatMul$multiplicadores$1
+
+
+module SequentialMatMul$multiplicadores$1$nucleo(a,b,resul);
+    
+    // Module arguments
+    input wire  [3:0] a;
+    input wire  [3:0] b;
+    output reg  [8:0] resul;
+    
+    // Update code (custom)
+    always @(*) resul = $signed(a) * $signed(b);
+endmodule // SequentialMatMul$multiplicadores$1$nucleo
+
+
+module SequentialMatMul$ocupado(d,q,clk);
+    
+    // Module arguments
+    input wire  d;
+    output reg  q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 1'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SequentialMatMul$ocupado
+
+
+module SequentialMatMul$paso(d,q,clk);
+    
+    // Module arguments
+    input wire  [15:0] d;
+    output reg  [15:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 16'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SequentialMatMul$paso
+
+
+module SequentialMatMul$resultados$0(d,q,clk);
+    
+    // Module arguments
+    input wire signed [8:0] d;
+    output reg signed [8:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 9'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SequentialMatMul$resultados$0
+
+
+module SequentialMatMul$resultados$1(d,q,clk);
+    
+    // Module arguments
+    input wire signed [8:0] d;
+    output reg signed [8:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 9'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SequentialMatMul$resultados$1
+
+
+module SequentialMatMul$resultados$2(d,q,clk);
+    
+    // Module arguments
+    input wire signed [8:0] d;
+    output reg signed [8:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 9'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SequentialMatMul$resultados$2
+
+
+module SequentialMatMul$resultados$3(d,q,clk);
+    
+    // Module arguments
+    input wire signed [8:0] d;
+    output reg signed [8:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 9'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SequentialMatMul$resultados$3
+
+
+module SequentialMatMul$suma(d,q,clk);
+    
+    // Module arguments
+    input wire signed [8:0] d;
+    output reg signed [8:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 9'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SequentialMatMul$suma
+
+
+module SequentialMatMul$sumadores$0(a,b,sum,overflow);
+    
+    // Module arguments
+    input wire  [8:0] a;
+    input wire  [8:0] b;
+    output reg  [8:0] sum;
+    output reg  overflow;
+    
+    // Constant declarations
+    localparam  maximo = 9'b11111111;
+    localparam  minimo = 9'b100000000;
+    localparam  con_signo = 1'b1;
+    localparam  saturar = 1'b0;
+    
+    // Local signals
+    reg  [8:0] suma;
+    
+    // Update code
+    always @(*) begin
+        suma = a + b;
+        sum = suma;
+        overflow = 1'b0;
+        if (con_signo) begin
+            if ((a[8] == b[8]) & (suma[8] != a[8])) begin
+                overflow = 1'b1;
+                if (saturar) begin
+                    if (a[8]) begin
+                        sum = minimo;
+                    end
+                    else begin
+                        sum = maximo;
+                    end
+                end
+            end
+        end
+        else if (suma < a) begin
+            overflow = 1'b1;
+            if (saturar) begin
+                sum = maximo;
+            end
+        end
+    end
+    
+endmodule // SequentialMatMul$sumadores$0
+
+
+module SequentialMatMul$sumadores$1(a,b,sum,overflow);
+    
+    // Module arguments
+    input wire  [8:0] a;
+    input wire  [8:0] b;
+    output reg  [8:0] sum;
+    output reg  overflow;
+    
+    // Constant declarations
+    localparam  maximo = 9'b11111111;
+    localparam  minimo = 9'b100000000;
+    localparam  con_signo = 1'b1;
+    localparam  saturar = 1'b0;
+    
+    // Local signals
+    reg  [8:0] suma;
+    
+    // Update code
+    always @(*) begin
+        suma = a + b;
+        sum = suma;
+        overflow = 1'b0;
+        if (con_signo) begin
+            if ((a[8] == b[8]) & (suma[8] != a[8])) begin
+                overflow = 1'b1;
+                if (saturar) begin
+                    if (a[8]) begin
+                        sum = minimo;
+                    end
+                    else begin
+                        sum = maximo;
+                    end
+                end
+            end
+        end
+        else if (suma < a) begin
+            overflow = 1'b1;
+            if (saturar) begin
+                sum = maximo;
+            end
+        end
+    end
+    
+endmodule // SequentialMatMul$sumadores$1
+
+
+module SequentialMatMul$terminado(d,q,clk);
+    
+    // Module arguments
+    input wire  d;
+    output reg  q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 1'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SequentialMatMul$terminado
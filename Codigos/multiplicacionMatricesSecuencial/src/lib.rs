@@ -0,0 +1,399 @@
+use rust_hdl::prelude::*;
+
+// Flip-Flop común a todos los diseños
+use tfg_hdl_common::{EdgeDFF, FsmError, StateMachine};
+// signed_bit_cast de RustHDL desborda la pila con -8
+use tfg_hdl_common::signed_bit_cast;
+
+mod sequential;
+mod systolic;
+pub use sequential::SequentialMatMul;
+pub use systolic::SystolicMatMul;
+
+// Matrices de prueba y modelo de referencia que usan los tests de
+// SequentialMatMul y SystolicMatMul
+#[cfg(test)]
+mod pruebas {
+    use tfg_hdl_common::{overflow_add, OverflowMode, TwosComplement};
+
+    pub type Matriz<const N: usize> = [[i64; N]; N];
+
+    // A·B con los productos de cada elemento sumados en orden en `ancho`
+    // bits, como los acumuladores de los diseños, y si alguna suma se ha
+    // desbordado
+    pub fn producto<const N: usize, M: OverflowMode>(a: &Matriz<N>, b: &Matriz<N>, ancho: usize) -> (Matriz<N>, bool) {
+        let mut desborda = false;
+        let c = std::array::from_fn(|i| {
+            std::array::from_fn(|j| {
+                (0..N).fold(0, |suma, k| {
+                    let (suma, ahora) = overflow_add::<TwosComplement, M>(ancho, suma, a[i][k] * b[k][j]);
+                    desborda |= ahora;
+                    suma
+                })
+            })
+        });
+        (c, desborda)
+    }
+
+    // Dos matrices de 4 bits con signo con valores variados, distintas para
+    // cada semilla
+    pub fn matrices<const N: usize>(semilla: i64) -> (Matriz<N>, Matriz<N>) {
+        let valor = |i: usize, j: usize, s: i64| ((i * N + j) as i64 * 7 + s * 3) % 16 - 8;
+        (
+            std::array::from_fn(|i| std::array::from_fn(|j| valor(i, j, semilla))),
+            std::array::from_fn(|i| std::array::from_fn(|j| valor(j, i, semilla + 5))),
+        )
+    }
+}
+
+// Máquina de estados para la multiplicación de matrices 2x2
+#[derive(Copy, Clone, PartialEq, Eq, Debug, LogicState)]
+enum State {
+    Idle,
+    Calc1,
+    Calc2,
+    Calc3,
+    Calc4,
+    Done,
+}
+
+#[derive(LogicBlock, Clone)]
+pub struct MultiplicacionMatricesSecuencial {
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    pub start: Signal<In, Bit>,
+    pub a: [Signal<In, Signed<4>>; 4],
+    pub b: [Signal<In, Signed<4>>; 4],
+    pub result: [Signal<Out, Signed<32>>; 4],
+    pub done: Signal<Out, Bit>,
+    state: EdgeDFF<State>,
+    temp1: EdgeDFF<Signed<32>>,
+    temp2: EdgeDFF<Signed<32>>,
+}
+
+impl Default for MultiplicacionMatricesSecuencial {
+    fn default() -> Self {
+        Self {
+            clk: Default::default(),
+            rst: Default::default(),
+            start: Default::default(),
+            a: Default::default(),
+            //a[1]: Default::default(),
+            //a[2]: Default::default(),
+            //a[3]: Default::default(),
+            b: Default::default(),
+            //b[1]: Default::default(),
+            //b[2]: Default::default(),
+            //b[3]: Default::default(),
+            done: Default::default(),
+            result: Default::default(),
+            //result[1]: Default::default(),
+            //result[2]: Default::default(),
+            //result[3]: Default::default(),
+            state: EdgeDFF::new(State::Idle),
+            temp1: EdgeDFF::new(0.into()),
+            temp2: EdgeDFF::new(0.into()),
+        }
+    }
+}
+
+impl MultiplicacionMatricesSecuencial {
+    // Estados y transiciones de `state`, sacados del update (State no es
+    // público, así que se extrae desde aquí)
+    pub fn state_machine(&self) -> Result<StateMachine, FsmError> {
+        StateMachine::extract::<State, _>(self, "state")
+    }
+}
+
+impl Logic for MultiplicacionMatricesSecuencial {
+    #[hdl_gen]
+    fn update(&mut self) {
+        // Enlazamos el reloj
+        self.state.clk.next = self.clk.val();
+        self.temp1.clk.next = self.clk.val();
+        self.temp2.clk.next = self.clk.val();
+
+        self.done.next = false;
+        
+        // Si el reset está activo, volvemos al estado Idle
+        if self.rst.val() {
+            // Inicializamos el estado
+            self.result[0].next = 0.into();
+            self.result[1].next = 0.into();
+            self.result[2].next = 0.into();
+            self.result[3].next = 0.into();
+            self.state.d.next = State::Idle;
+            self.temp1.d.next = 0.into();
+            self.temp2.d.next = 0.into();
+        } else {
+            match self.state.q.val() {
+                State::Idle => {
+                    if self.start.val() {
+                        self.state.d.next = State::Calc1;
+                    }
+                }
+                State::Calc1 => {
+                    self.temp1.d.next = signed_bit_cast::<16, 4>(self.a[0].val()) * signed_bit_cast::<16, 4>(self.b[0].val());
+                    self.temp2.d.next = signed_bit_cast::<16, 4>(self.a[1].val()) * signed_bit_cast::<16, 4>(self.b[2].val());
+                    self.state.d.next = State::Calc2;
+                }
+                State::Calc2 => {
+                    self.result[0].next = self.temp1.q.val() + self.temp2.q.val();
+                    self.temp1.d.next = signed_bit_cast::<16, 4>(self.a[0].val()) * signed_bit_cast::<16, 4>(self.b[1].val());
+                    self.temp2.d.next = signed_bit_cast::<16, 4>(self.a[1].val()) * signed_bit_cast::<16, 4>(self.b[3].val());
+                    self.state.d.next = State::Calc3;
+                }
+                State::Calc3 => {
+                    self.result[1].next = self.temp1.q.val() + self.temp2.q.val();
+                    self.temp1.d.next = signed_bit_cast::<16, 4>(self.a[2].val()) * signed_bit_cast::<16, 4>(self.b[0].val());
+                    self.temp2.d.next = signed_bit_cast::<16, 4>(self.a[3].val()) * signed_bit_cast::<16, 4>(self.b[2].val());
+                    self.state.d.next = State::Calc4;
+                }
+                State::Calc4 => {
+                    self.result[2].next = self.temp1.q.val() + self.temp2.q.val();
+                    self.temp1.d.next = signed_bit_cast::<16, 4>(self.a[2].val()) * signed_bit_cast::<16, 4>(self.b[1].val());
+                    self.temp2.d.next = signed_bit_cast::<16, 4>(self.a[3].val()) * signed_bit_cast::<16, 4>(self.b[3].val());
+                    self.state.d.next = State::Done;
+                }
+                State::Done => {
+                    self.result[3].next = self.temp1.q.val() + self.temp2.q.val();
+                    self.done.next = true;
+                    self.state.d.next = State::Idle;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use tfg_hdl_common::{TestVector, TestVectors};
+
+    // Casos dirigidos: (A, B, C esperado). Salen de aquí tanto el testbench
+    // de RustHDL como el de Verilog
+    type Caso = ([i64; 4], [i64; 4], [i64; 4], &'static str);
+    const CASOS: [Caso; 4] = [
+        // Caso 1: Máximo producto posible
+        ([-8, -8, -8, -8], [-8, -8, -8, -8], [128, 128, 128, 128], "Test Máximo"),
+        // Caso 2: Mínimo producto posible
+        ([-8, -8, -8, -8], [7, 7, 7, 7], [-112, -112, -112, -112], "Test Mínimo"),
+        // Caso 3: Mix de positivos y negativos
+        ([4, -3, -2, 5], [-4, 2, 3, -5], [-25, 23, 23, -29], "Test Mix"),
+        // Caso 4: Multiplicación con ceros
+        ([0, 5, -3, 0], [0, 7, -1, 0], [-5, 0, 0, -21], "Test Ceros"),
+    ];
+
+    fn casos() -> TestVectors<MultiplicacionMatricesSecuencial> {
+        let mut tb = TestVectors::<MultiplicacionMatricesSecuencial>::new("MultiplicacionMatricesSecuencial")
+            .clock("clk", |x| &mut x.clk);
+        let rst = tb.input("rst", |x| &mut x.rst);
+        let start = tb.input("start", |x| &mut x.start);
+        let a = [
+            tb.input("a$0", |x| &mut x.a[0]),
+            tb.input("a$1", |x| &mut x.a[1]),
+            tb.input("a$2", |x| &mut x.a[2]),
+            tb.input("a$3", |x| &mut x.a[3]),
+        ];
+        let b = [
+            tb.input("b$0", |x| &mut x.b[0]),
+            tb.input("b$1", |x| &mut x.b[1]),
+            tb.input("b$2", |x| &mut x.b[2]),
+            tb.input("b$3", |x| &mut x.b[3]),
+        ];
+        let result = [
+            tb.output("result$0", |x| &x.result[0]),
+            tb.output("result$1", |x| &x.result[1]),
+            tb.output("result$2", |x| &x.result[2]),
+            tb.output("result$3", |x| &x.result[3]),
+        ];
+        let done = tb.output("done", |x| &x.done);
+
+        // Inicialización
+        tb.push(TestVector::new("reset").set(rst, 1));
+        for (va, vb, vc, nombre) in CASOS {
+            let mut vector = TestVector::new(nombre).set(rst, 0).set(start, 1);
+            for (i, (&ea, &eb)) in a.iter().zip(&b).enumerate() {
+                vector = vector.set(ea, va[i]).set(eb, vb[i]);
+            }
+            tb.push(vector);
+            // Calc1..Calc4 y Done: el resultado completo está en el estado
+            // Done, y hace falta un ciclo más para volver a Idle
+            for ciclo in 0..5 {
+                let mut vector = TestVector::new(nombre).set(start, 0);
+                if ciclo == 3 {
+                    vector = vector.expect(done, 1);
+                    for (&salida, &esperado) in result.iter().zip(&vc) {
+                        vector = vector.expect(salida, esperado);
+                    }
+                }
+                tb.push(vector);
+            }
+        }
+        tb
+    }
+
+    // Test con RustHDL y comparación con Verilog
+    #[test]
+    fn test_multiplicacion_matrices_sec_casos() -> anyhow::Result<()> {
+        let mut uut = MultiplicacionMatricesSecuencial::default();
+        uut.connect_all();
+        let tb = casos();
+
+        //Co-simulación: el mismo testbench en RustHDL y en Icarus, comparado ciclo a ciclo
+        if let Some(informe) = tb.verify(&uut, 10_000)? {
+            println!("(iverilog) Salida de Verilog:\n{}", informe.stdout);
+        }
+
+        Ok(())
+    }
+
+    // Idle espera a start y después los cuatro pasos van seguidos hasta Done
+    #[test]
+    fn test_maquina_de_estados() -> anyhow::Result<()> {
+        let fsm = MultiplicacionMatricesSecuencial::default().state_machine()?;
+        assert_eq!(fsm.initial(), "Idle");
+        let aristas: Vec<(Option<&str>, &str, &str)> = fsm
+            .transitions
+            .iter()
+            .map(|t| (t.from.as_deref(), t.to.as_str(), t.condition.as_str()))
+            .collect();
+        assert_eq!(
+            aristas,
+            [
+                (None, "Idle", "rst"),
+                (Some("Idle"), "Calc1", "!rst && start"),
+                (Some("Calc1"), "Calc2", "!rst"),
+                (Some("Calc2"), "Calc3", "!rst"),
+                (Some("Calc3"), "Calc4", "!rst"),
+                (Some("Calc4"), "Done", "!rst"),
+                (Some("Done"), "Idle", "!rst"),
+            ]
+        );
+        assert_eq!(fsm.report(), "");
+        Ok(())
+    }
+
+    // Test secuencial de multiplicación de matrices: barrido de RustHDL con
+    // todas las matrices de valores entre -2 y 2
+    #[test]
+    fn test_multiplicacion_matrices_sec() -> anyhow::Result<()> {
+        let mut uut = MultiplicacionMatricesSecuencial::default();
+        uut.connect_all();
+
+        let mut sim = Simulation::<MultiplicacionMatricesSecuencial>::new();
+        sim.add_testbench(move |mut ep| {
+            let mut x = ep.init()?;
+
+            // Definimos el rango de valores (-2 a 2)
+            let valores: Vec<i32> = (-2..=2).collect();
+
+            // Recorremos todas las combinaciones posibles de los valores
+            for &a0 in &valores {
+                for &a1 in &valores {
+                    for &a2 in &valores {
+                        for &a3 in &valores {
+                            for &b0 in &valores {
+                                for &b1 in &valores {
+                                    for &b2 in &valores {
+                                        for &b3 in &valores {
+
+
+                                            //Reset
+                                            x.rst.next = false;
+                                            x.clk.next = Clock { clk: false };
+                                            let x_clone = x.clone();
+                                            x = ep.wait(1, x_clone)?;
+
+                                            x.clk.next = Clock { clk: true };
+                                            let x_clone = x.clone();
+                                            x = ep.wait(1, x_clone)?;
+
+                                            x.clk.next = Clock { clk: false };
+                                            let x_clone = x.clone();
+                                            x = ep.wait(1, x_clone)?;
+
+                                            x.clk.next = Clock { clk: true };
+                                            let x_clone = x.clone();
+                                            x = ep.wait(1, x_clone)?;
+
+                                            let mut sol0 = Signed::<32>::from(0_i64);
+                                            let mut sol1 = Signed::<32>::from(0_i64);
+                                            let mut sol2 = Signed::<32>::from(0_i64);
+                                            let mut sol3 = Signed::<32>::from(0_i64);
+
+                                            // Asignamos los valores de la matriz A
+                                            x.a[0].next = Signed::<4>::from(a0 as i64);
+                                            x.a[1].next = Signed::<4>::from(a1 as i64);
+                                            x.a[2].next = Signed::<4>::from(a2 as i64);
+                                            x.a[3].next = Signed::<4>::from(a3 as i64);
+
+                                            // Asignamos los valores de la matriz B
+                                            x.b[0].next = Signed::<4>::from(b0 as i64);
+                                            x.b[1].next = Signed::<4>::from(b1 as i64);
+                                            x.b[2].next = Signed::<4>::from(b2 as i64);
+                                            x.b[3].next = Signed::<4>::from(b3 as i64);
+
+                                            x.start.next = true;
+                                            // Simulamos un ciclo de reloj
+                                            for _cycle in 0..5 {
+
+                                                x.clk.next = Clock { clk: false };
+                                                let x_clone = x.clone();
+                                                x = ep.wait(1, x_clone)?;
+
+                                                x.clk.next = Clock { clk: true };
+                                                let x_clone = x.clone();
+                                                x = ep.wait(1, x_clone)?;
+
+                                                sol0 = x.result[0].val();
+                                                sol1 = x.result[1].val();
+                                                sol2 = x.result[2].val();
+                                                sol3 = x.result[3].val();
+                                                x.start.next = false;
+                                            }
+
+                                            // Calculamos el resultado esperado para la multiplicación de matrices
+                                            let c0 = a0 * b0 + a1 * b2;
+                                            let c1 = a0 * b1 + a1 * b3;
+                                            let c2 = a2 * b0 + a3 * b2;
+                                            let c3 = a2 * b1 + a3 * b3;
+
+                                            /*if x.done.val() {
+                                            // Imprimimos los resultados para la verificación
+                                                println!("\n=== Multiplicación de matrices ===");
+                                                println!("A = | {} {} |\n    | {} {} |", a0, a1, a2, a3);
+                                                println!("B = | {} {} |\n    | {} {} |", b0, b1, b2, b3);
+                                                println!("C = | {} {} |", sol0.bigint(), sol1.bigint());
+                                                println!("    | {} {} | // Esperado: | {} {} | | {} {} |",
+                                                    sol2.bigint(), sol3.bigint(),
+                                                    c0, c1, c2, c3);
+                                            }*/
+
+                                            // Validamos los resultados contra el valor esperado
+                                            sim_assert_eq!(ep, sol0, Signed::<32>::from(c0 as i64), x);
+                                            sim_assert_eq!(ep, sol1, Signed::<32>::from(c1 as i64), x);
+                                            sim_assert_eq!(ep, sol2, Signed::<32>::from(c2 as i64), x);
+                                            sim_assert_eq!(ep, sol3, Signed::<32>::from(c3 as i64), x);
+
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            ep.done(x)?;
+
+            Ok(())
+        });
+
+        sim.run_to_file(Box::new(uut), 1000000000, "multiplicacionMatricesSecuencialWave.vcd")
+            .map_err(|err| anyhow!("{:?}", err))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,441 @@
+use rust_hdl::prelude::*;
+
+// Flip-Flop común a todos los diseños
+use tfg_hdl_common::{EdgeDFF, MulAlgorithm, Operator, OverflowAdder, OverflowMode, SignedMul, TwosComplement, Wrap};
+
+// Multiplicación secuencial de matrices NxN con P multiplicadores, controlada
+// por contadores en vez de por un estado para cada cálculo.
+//
+// Los elementos de C se calculan de uno en uno, por filas. Para cada uno, los
+// P multiplicadores hacen en cada ciclo P de los N productos de la fila de A
+// por la columna de B (el multiplicador p hace los k con k % P == p), así que
+// cada elemento tarda ceil(N / P) ciclos: con P = 1 un producto por ciclo,
+// con P = N el elemento entero en un ciclo. Los productos del ciclo se suman
+// al acumulador `suma`, y en el último paso el elemento se guarda en su
+// registro de resultado.
+//
+// Como MultiplicacionMatricesSecuencial, la operación empieza en el flanco en
+// el que start está a 1 estando parado, y a y b tienen que mantenerse hasta
+// que acaba. done se pone a 1 durante un ciclo, justo después del flanco en
+// el que se escribe el último elemento, y result se mantiene hasta la
+// siguiente multiplicación.
+//
+// Las matrices van por filas en puertos planos (a[i·N + k]); NN = N·N se pasa
+// como parámetro porque el Rust estable no deja escribir [_; N * N], y O puede
+// ser como mucho 2W + ceil(log2 N), que es lo que hace falta para que no se
+// desborde nunca. Con menos bits (al menos 2W) overflow se pone a 1 si alguna
+// suma de algún elemento no cabe, hasta el siguiente start, y con
+// M = Saturate esas sumas se quedan en el máximo o el mínimo. Se comprueban
+// al construir. A elige el algoritmo de los multiplicadores (por defecto el
+// operador * de Verilog). Con un multiplicador registrado (Registered) cada
+// producto llega A::LATENCY ciclos después que sus operandos, así que cada
+// elemento tarda esos ciclos de más y sus últimos pasos sólo recogen
+// productos. Mientras está parado los operandos están a 0, para que ningún
+// producto que quede en los registros se sume al primer elemento.
+#[derive(LogicBlock, Clone)]
+pub struct SequentialMatMul<
+    const N: usize,
+    const P: usize,
+    const W: usize,
+    const O: usize,
+    const NN: usize,
+    A: MulAlgorithm = Operator,
+    M: OverflowMode = Wrap,
+> {
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    pub start: Signal<In, Bit>,
+    pub a: [Signal<In, Signed<W>>; NN],
+    pub b: [Signal<In, Signed<W>>; NN],
+    pub result: [Signal<Out, Signed<O>>; NN],
+    pub done: Signal<Out, Bit>,
+    pub overflow: Signal<Out, Bit>,
+    multiplicadores: [SignedMul<W, O, A>; P],
+    // Sumador de cada producto, en cadena desde el acumulador
+    sumadores: [OverflowAdder<O, TwosComplement, M>; P],
+    resultados: [EdgeDFF<Signed<O>>; NN],
+    suma: EdgeDFF<Signed<O>>,
+    ocupado: EdgeDFF<Bit>,
+    terminado: EdgeDFF<Bit>,
+    desborde: EdgeDFF<Bit>,
+    // Elemento de C que se está calculando y paso dentro de él
+    fila: EdgeDFF<Bits<16>>,
+    columna: EdgeDFF<Bits<16>>,
+    paso: EdgeDFF<Bits<16>>,
+    // Suma de los productos del ciclo, acumulada multiplicador a multiplicador
+    parcial: [Signal<Local, Signed<O>>; P],
+    total: Signal<Local, Signed<O>>,
+    desborda: Signal<Local, Bit>,
+    // Para cada posición e de una matriz: su fila e / N, su columna e % N y
+    // el paso en el que se usa como elemento de A ((e % N) / P) y de B
+    // ((e / N) / P)
+    fila_de: [Constant<Bits<16>>; NN],
+    columna_de: [Constant<Bits<16>>; NN],
+    paso_a: [Constant<Bits<16>>; NN],
+    paso_b: [Constant<Bits<16>>; NN],
+    ultimo: Constant<Bits<16>>,
+    ultimo_paso: Constant<Bits<16>>,
+}
+
+impl<
+        const N: usize,
+        const P: usize,
+        const W: usize,
+        const O: usize,
+        const NN: usize,
+        A: MulAlgorithm,
+        M: OverflowMode,
+    > SequentialMatMul<N, P, W, O, NN, A, M>
+{
+    // Ciclos que tarda cada elemento de C
+    pub const STEPS: usize = N.div_ceil(P) + A::LATENCY;
+    // Ciclos desde el flanco de start hasta el ciclo en el que sube done
+    pub const LATENCY: usize = NN * Self::STEPS;
+}
+
+impl<
+        const N: usize,
+        const P: usize,
+        const W: usize,
+        const O: usize,
+        const NN: usize,
+        A: MulAlgorithm,
+        M: OverflowMode,
+    > Default for SequentialMatMul<N, P, W, O, NN, A, M>
+{
+    fn default() -> Self {
+        const {
+            assert!(N >= 1 && NN < 1 << 16, "N fuera de rango");
+            assert!(P >= 1 && P <= N, "hace falta entre 1 y N multiplicadores");
+            assert!(NN == N * N, "NN tiene que ser N·N");
+            assert!(
+                O >= 2 * W && O <= 2 * W + N.next_power_of_two().trailing_zeros() as usize,
+                "O tiene que estar entre 2W y 2W + ceil(log2 N)"
+            );
+        }
+        let constante = |v: usize| Constant::new((v as u64).into());
+        Self {
+            clk: Default::default(),
+            rst: Default::default(),
+            start: Default::default(),
+            a: std::array::from_fn(|_| Default::default()),
+            b: std::array::from_fn(|_| Default::default()),
+            result: std::array::from_fn(|_| Default::default()),
+            done: Default::default(),
+            overflow: Default::default(),
+            multiplicadores: std::array::from_fn(|_| Default::default()),
+            sumadores: std::array::from_fn(|_| Default::default()),
+            resultados: std::array::from_fn(|_| EdgeDFF::new(0.into())),
+            suma: EdgeDFF::new(0.into()),
+            ocupado: EdgeDFF::new(false),
+            terminado: EdgeDFF::new(false),
+            desborde: EdgeDFF::new(false),
+            fila: EdgeDFF::new(0.into()),
+            columna: EdgeDFF::new(0.into()),
+            paso: EdgeDFF::new(0.into()),
+            parcial: std::array::from_fn(|_| Default::default()),
+            total: Default::default(),
+            desborda: Default::default(),
+            fila_de: std::array::from_fn(|e| constante(e / N)),
+            columna_de: std::array::from_fn(|e| constante(e % N)),
+            paso_a: std::array::from_fn(|e| constante(e % N / P)),
+            paso_b: std::array::from_fn(|e| constante(e / N / P)),
+            ultimo: constante(N - 1),
+            ultimo_paso: constante(Self::STEPS - 1),
+        }
+    }
+}
+
+impl<
+        const N: usize,
+        const P: usize,
+        const W: usize,
+        const O: usize,
+        const NN: usize,
+        A: MulAlgorithm,
+        M: OverflowMode,
+    > Logic for SequentialMatMul<N, P, W, O, NN, A, M>
+{
+    #[hdl_gen]
+    fn update(&mut self) {
+        // Enlazamos el reloj
+        for e in 0..NN {
+            self.resultados[e].clk.next = self.clk.val();
+            self.result[e].next = self.resultados[e].q.val();
+        }
+        self.suma.clk.next = self.clk.val();
+        self.ocupado.clk.next = self.clk.val();
+        self.terminado.clk.next = self.clk.val();
+        self.desborde.clk.next = self.clk.val();
+        self.fila.clk.next = self.clk.val();
+        self.columna.clk.next = self.clk.val();
+        self.paso.clk.next = self.clk.val();
+        for p in 0..P {
+            self.multiplicadores[p].clk.next = self.clk.val();
+        }
+
+        // Operandos de cada multiplicador: A[fila][k] y B[k][columna] con k =
+        // paso·P + p. Si k se sale de la matriz (N no múltiplo de P), en los
+        // pasos que sólo esperan al multiplicador o estando parado quedan a 0.
+        // N y P entran como `n` y `m`: bucles de una vuelta, ver tfg_hdl_common
+        for p in 0..P {
+            self.multiplicadores[p].a.next = 0.into();
+            self.multiplicadores[p].b.next = 0.into();
+        }
+        for n in N..(N + 1) {
+            for m in P..(P + 1) {
+                for e in 0..NN {
+                    if self.ocupado.q.val()
+                        & (self.fila.q.val() == self.fila_de[e].val())
+                        & (self.paso.q.val() == self.paso_a[e].val())
+                    {
+                        self.multiplicadores[e % n % m].a.next = self.a[e].val();
+                    }
+                    if self.ocupado.q.val()
+                        & (self.columna.q.val() == self.columna_de[e].val())
+                        & (self.paso.q.val() == self.paso_b[e].val())
+                    {
+                        self.multiplicadores[e / n % m].b.next = self.b[e].val();
+                    }
+                }
+            }
+        }
+        // Los sumadores trabajan en Bits (con signo), así que los valores se
+        // pasan con unsigned_cast y signed_cast
+        self.sumadores[0].a.next = unsigned_cast(self.suma.q.val());
+        self.sumadores[0].b.next = unsigned_cast(self.multiplicadores[0].p.val());
+        self.parcial[0].next = signed_cast(self.sumadores[0].sum.val());
+        for p in 1..P {
+            self.sumadores[p].a.next = unsigned_cast(self.parcial[p - 1].val());
+            self.sumadores[p].b.next = unsigned_cast(self.multiplicadores[p].p.val());
+            self.parcial[p].next = signed_cast(self.sumadores[p].sum.val());
+        }
+        for m in P..(P + 1) {
+            self.total.next = self.parcial[m - 1].val();
+        }
+        self.desborda.next = false;
+        for p in 0..P {
+            if self.sumadores[p].overflow.val() {
+                self.desborda.next = true;
+            }
+        }
+
+        // Por defecto los registros se mantienen
+        for e in 0..NN {
+            self.resultados[e].d.next = self.resultados[e].q.val();
+        }
+        self.suma.d.next = self.suma.q.val();
+        self.ocupado.d.next = self.ocupado.q.val();
+        self.terminado.d.next = false;
+        self.desborde.d.next = self.desborde.q.val();
+        self.fila.d.next = self.fila.q.val();
+        self.columna.d.next = self.columna.q.val();
+        self.paso.d.next = self.paso.q.val();
+
+        if self.rst.val() {
+            for e in 0..NN {
+                self.resultados[e].d.next = 0.into();
+            }
+            self.suma.d.next = 0.into();
+            self.ocupado.d.next = false;
+            self.desborde.d.next = false;
+            self.fila.d.next = 0.into();
+            self.columna.d.next = 0.into();
+            self.paso.d.next = 0.into();
+        } else if !self.ocupado.q.val() {
+            if self.start.val() {
+                self.ocupado.d.next = true;
+                self.desborde.d.next = false;
+            }
+        } else if self.paso.q.val() != self.ultimo_paso.val() {
+            self.desborde.d.next = self.desborde.q.val() | self.desborda.val();
+            self.suma.d.next = self.total.val();
+            self.paso.d.next = self.paso.q.val() + 1;
+        } else {
+            // Último paso: se escribe el elemento y se pasa al siguiente
+            self.desborde.d.next = self.desborde.q.val() | self.desborda.val();
+            for e in 0..NN {
+                if (self.fila.q.val() == self.fila_de[e].val()) & (self.columna.q.val() == self.columna_de[e].val()) {
+                    self.resultados[e].d.next = self.total.val();
+                }
+            }
+            self.suma.d.next = 0.into();
+            self.paso.d.next = 0.into();
+            if self.columna.q.val() != self.ultimo.val() {
+                self.columna.d.next = self.columna.q.val() + 1;
+            } else {
+                self.columna.d.next = 0.into();
+                if self.fila.q.val() != self.ultimo.val() {
+                    self.fila.d.next = self.fila.q.val() + 1;
+                } else {
+                    self.fila.d.next = 0.into();
+                    self.ocupado.d.next = false;
+                    self.terminado.d.next = true;
+                }
+            }
+        }
+
+        self.done.next = self.terminado.q.val();
+        self.overflow.next = self.desborde.q.val();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pruebas::{matrices, producto};
+    use tfg_hdl_common::{Booth, Input, Output, Registered, Saturate, TestVector, TestVectors, Wallace};
+
+    type Multiplicador<const N: usize, const P: usize, const O: usize, const NN: usize, A = Operator, M = Wrap> =
+        SequentialMatMul<N, P, 4, O, NN, A, M>;
+
+    struct Puertos {
+        rst: Input,
+        start: Input,
+        a: Vec<Input>,
+        b: Vec<Input>,
+        result: Vec<Output>,
+        done: Output,
+        overflow: Output,
+    }
+
+    // Casos extremos y unos cuantos mezclados, con start en cada uno y
+    // comprobando done en todos los ciclos hasta el resultado
+    fn comprobar<const N: usize, const P: usize, const O: usize, const NN: usize>(modulo: &str) -> anyhow::Result<()> {
+        comprobar_con::<N, P, O, NN, Operator, Wrap>(modulo)
+    }
+
+    fn comprobar_con<
+        const N: usize,
+        const P: usize,
+        const O: usize,
+        const NN: usize,
+        A: MulAlgorithm,
+        M: OverflowMode,
+    >(
+        modulo: &str,
+    ) -> anyhow::Result<()> {
+        let mut uut = Multiplicador::<N, P, O, NN, A, M>::default();
+        uut.connect_all();
+
+        let mut tb = TestVectors::<Multiplicador<N, P, O, NN, A, M>>::new(modulo).clock("clk", |x| &mut x.clk);
+        let p = Puertos {
+            rst: tb.input("rst", |x| &mut x.rst),
+            start: tb.input("start", |x| &mut x.start),
+            a: (0..NN)
+                .map(|e| tb.input(&format!("a${e}"), move |x| &mut x.a[e]))
+                .collect(),
+            b: (0..NN)
+                .map(|e| tb.input(&format!("b${e}"), move |x| &mut x.b[e]))
+                .collect(),
+            result: (0..NN)
+                .map(|e| tb.output(&format!("result${e}"), move |x| &x.result[e]))
+                .collect(),
+            done: tb.output("done", |x| &x.done),
+            overflow: tb.output("overflow", |x| &x.overflow),
+        };
+        tb.push(TestVector::new("reset").set(p.rst, 1).set(p.start, 0));
+
+        let mut casos = vec![
+            ("mínimos", [[-8; N]; N], [[-8; N]; N]),
+            ("mínimo por máximo", [[-8; N]; N], [[7; N]; N]),
+        ];
+        for semilla in 0..3 {
+            let (a, b) = matrices::<N>(semilla);
+            casos.push(("mezcla", a, b));
+        }
+        // Uno que nunca se desborda, para ver que start borra overflow
+        casos.push(("pequeños", [[1; N]; N], [[-1; N]; N]));
+        for (nombre, va, vb) in casos {
+            let mut vector = TestVector::new(nombre).set(p.rst, 0).set(p.start, 1).expect(p.done, 0);
+            for e in 0..NN {
+                vector = vector.set(p.a[e], va[e / N][e % N]).set(p.b[e], vb[e / N][e % N]);
+            }
+            tb.push(vector);
+            // start se ignora mientras calcula
+            let (c, desborda) = producto::<N, M>(&va, &vb, O);
+            for ciclo in 0..Multiplicador::<N, P, O, NN, A, M>::LATENCY {
+                let mut vector = TestVector::new(nombre).set(p.start, (ciclo == 0) as i64);
+                if ciclo + 1 == Multiplicador::<N, P, O, NN, A, M>::LATENCY {
+                    vector = vector.expect(p.done, 1).expect(p.overflow, desborda as i64);
+                    for (e, &salida) in p.result.iter().enumerate() {
+                        vector = vector.expect(salida, c[e / N][e % N]);
+                    }
+                } else {
+                    vector = vector.expect(p.done, 0);
+                }
+                tb.push(vector);
+            }
+        }
+
+        tb.verify(&uut, 100_000)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_secuencial_2x2() -> anyhow::Result<()> {
+        comprobar::<2, 1, 9, 4>("SequentialMatMul2x1")?;
+        comprobar::<2, 2, 9, 4>("SequentialMatMul2x2")
+    }
+
+    #[test]
+    fn test_secuencial_1_3_y_4() -> anyhow::Result<()> {
+        comprobar::<1, 1, 8, 1>("SequentialMatMul1x1")?;
+        comprobar::<3, 1, 10, 9>("SequentialMatMul3x1")?;
+        // 3 no es múltiplo de 2: el segundo paso sólo usa un multiplicador
+        comprobar::<3, 2, 10, 9>("SequentialMatMul3x2")?;
+        comprobar::<3, 3, 10, 9>("SequentialMatMul3x3")?;
+        comprobar::<4, 2, 10, 16>("SequentialMatMul4x2")?;
+        comprobar::<4, 4, 10, 16>("SequentialMatMul4x4")
+    }
+
+    // Los mismos casos con los multiplicadores de Booth y de Wallace
+    #[test]
+    fn test_secuencial_booth_y_wallace() -> anyhow::Result<()> {
+        comprobar_con::<3, 2, 10, 9, Booth, Wrap>("SequentialMatMul3x2Booth")?;
+        comprobar_con::<3, 3, 10, 9, Wallace, Wrap>("SequentialMatMul3x3Wallace")
+    }
+
+    // Con el producto registrado cada elemento tarda A::LATENCY ciclos más
+    #[test]
+    fn test_secuencial_con_multiplicador_registrado() -> anyhow::Result<()> {
+        comprobar_con::<3, 2, 10, 9, Registered<Booth>, Wrap>("SequentialMatMul3x2BoothRegistrado")?;
+        comprobar_con::<2, 1, 8, 4, Registered<Registered<Wallace>>, Saturate>("SequentialMatMul2x1Registrado2")?;
+        assert_eq!(Multiplicador::<3, 2, 10, 9, Registered<Booth>>::LATENCY, 27);
+        Ok(())
+    }
+
+    // Con O = 2W = 8 bits los casos extremos se desbordan (3·64 = 192), y
+    // con P > 1 el desbordamiento puede estar en cualquier sumador de la
+    // cadena
+    #[test]
+    fn test_desbordamiento() -> anyhow::Result<()> {
+        comprobar_con::<3, 1, 8, 9, Operator, Wrap>("SequentialMatMul3x1Wrap")?;
+        comprobar_con::<3, 1, 8, 9, Operator, Saturate>("SequentialMatMul3x1Saturate")?;
+        comprobar_con::<3, 2, 8, 9, Booth, Saturate>("SequentialMatMul3x2Saturate")?;
+        comprobar_con::<3, 3, 9, 9, Operator, Saturate>("SequentialMatMul3x3Saturate")
+    }
+
+    // Más multiplicadores, menos ciclos
+    #[test]
+    fn test_latencia() {
+        assert_eq!(Multiplicador::<4, 1, 10, 16>::LATENCY, 64);
+        assert_eq!(Multiplicador::<4, 2, 10, 16>::LATENCY, 32);
+        assert_eq!(Multiplicador::<4, 4, 10, 16>::LATENCY, 16);
+        assert_eq!(Multiplicador::<3, 2, 10, 9>::LATENCY, 18);
+    }
+
+    #[test]
+    fn test_verilog_secuencial() {
+        let mut uut = Multiplicador::<3, 2, 10, 9>::default();
+        uut.connect_all();
+        let codigo = tfg_hdl_common::generate_named_verilog(&uut, "SequentialMatMul3x2").unwrap();
+        // A[1][2] va al multiplicador 0 en el paso 1, y B[2][1] también
+        assert!(codigo.contains("multiplicadores$0$a = a$5;"), "{}", codigo);
+        assert!(codigo.contains("multiplicadores$0$b = b$7;"));
+        // La suma de cada multiplicador pasa por su sumador con desbordamiento
+        assert!(codigo.contains("sumadores$1$a = $unsigned(parcial$0);"), "{}", codigo);
+        assert!(codigo.contains("parcial$1 = $signed(sumadores$1$sum);"));
+        assert!(codigo.contains("desborde$d = desborde$q | desborda;"));
+    }
+}
@@ -0,0 +1,593 @@
+use rust_hdl::prelude::*;
+
+// Flip-Flop común a todos los diseños
+use tfg_hdl_common::{EdgeDFF, MulAlgorithm, Operator, OverflowAdder, OverflowMode, SignedMul, TwosComplement, Wrap};
+
+// Multiplicación de matrices NxN con un array sistólico output-stationary:
+// una rejilla de NxN celdas en la que la celda (i, j) acumula result[i][j].
+//
+// Las matrices entran por columnas de A y filas de B: en cada dato aceptado
+// (valid_in y ready a 1) a_in[i] = A[i][k] y b_in[j] = B[k][j], para k de 0 a
+// N - 1. Los elementos de A avanzan por las filas hacia la derecha y los de B
+// por las columnas hacia abajo; la fila i de A entra con i ciclos de retraso
+// y la columna j de B con j (registros `retardo_a` y `retardo_b`), de modo que
+// A[i][k] y B[k][j] coinciden en la celda (i, j) i + j ciclos después de
+// aceptarse. Los ciclos sin dato válido meten ceros, que no cambian las sumas.
+//
+// `paso` cuenta los datos aceptados y, tras el último, los 2N - 2 ciclos que
+// tarda en vaciarse el array, más los A::LATENCY que tarda en salir el último
+// producto si el multiplicador es registrado (Registered). Entonces done se
+// pone a 1 un ciclo y result tiene el producto, que se mantiene hasta que
+// empieza la siguiente multiplicación. Mientras se vacía el array ready está
+// a 0.
+//
+// Como en el resto de diseños con arrays los puertos son planos y por filas
+// (result[i·N + j]), y como el Rust estable no deja escribir [_; N * N] el
+// tamaño NN = N·N se pasa como parámetro, igual que la anchura O de la salida,
+// que puede ser como mucho 2W + ceil(log2 N), lo justo para que no se desborde
+// nunca. Con menos bits (al menos 2W) overflow dice, junto con done y hasta la
+// siguiente multiplicación, si alguna celda se ha desbordado, y con
+// M = Saturate las celdas se quedan en el máximo o el mínimo. Las dos se
+// comprueban al construir. A elige el algoritmo de los multiplicadores de las
+// celdas.
+#[derive(LogicBlock, Clone)]
+pub struct SystolicMatMul<
+    const N: usize,
+    const W: usize,
+    const O: usize,
+    const NN: usize,
+    A: MulAlgorithm = Operator,
+    M: OverflowMode = Wrap,
+> {
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    pub a_in: [Signal<In, Signed<W>>; N],
+    pub b_in: [Signal<In, Signed<W>>; N],
+    pub valid_in: Signal<In, Bit>,
+    pub ready: Signal<Out, Bit>,
+    pub result: [Signal<Out, Signed<O>>; NN],
+    pub done: Signal<Out, Bit>,
+    pub overflow: Signal<Out, Bit>,
+    celdas: [CeldaSistolica<W, O, A, M>; NN],
+    // Líneas de retardo de las filas de A y de las columnas de B, una detrás
+    // de otra: la de la fila i tiene i registros y empieza en i·(i - 1)/2
+    retardo_a: Vec<EdgeDFF<Signed<W>>>,
+    retardo_b: Vec<EdgeDFF<Signed<W>>>,
+    paso: EdgeDFF<Bits<16>>,
+    acepta: Signal<Local, Bit>,
+    entrada_a: [Signal<Local, Signed<W>>; N],
+    entrada_b: [Signal<Local, Signed<W>>; N],
+    // N y 3N - 2 + A::LATENCY, los valores de `paso` al acabar la carga y al
+    // terminar
+    carga: Constant<Bits<16>>,
+    fin: Constant<Bits<16>>,
+}
+
+impl<const N: usize, const W: usize, const O: usize, const NN: usize, A: MulAlgorithm, M: OverflowMode>
+    SystolicMatMul<N, W, O, NN, A, M>
+{
+    // Ciclos desde el flanco que acepta el primer dato hasta el ciclo en el
+    // que sube done, si los datos llegan seguidos
+    pub const LATENCY: usize = 3 * N - 3 + A::LATENCY;
+
+    // Registros de cada línea de retardo: 1 + 2 + ... + (N - 1)
+    const RETARDOS: usize = N * (N - 1) / 2;
+}
+
+impl<const N: usize, const W: usize, const O: usize, const NN: usize, A: MulAlgorithm, M: OverflowMode> Default
+    for SystolicMatMul<N, W, O, NN, A, M>
+{
+    fn default() -> Self {
+        const {
+            assert!(N >= 1 && 3 * N + A::LATENCY < 1 << 16, "N fuera de rango");
+            assert!(NN == N * N, "NN tiene que ser N·N");
+            assert!(
+                O >= 2 * W && O <= 2 * W + N.next_power_of_two().trailing_zeros() as usize,
+                "O tiene que estar entre 2W y 2W + ceil(log2 N)"
+            );
+        }
+        Self {
+            clk: Default::default(),
+            rst: Default::default(),
+            a_in: std::array::from_fn(|_| Default::default()),
+            b_in: std::array::from_fn(|_| Default::default()),
+            valid_in: Default::default(),
+            ready: Default::default(),
+            result: std::array::from_fn(|_| Default::default()),
+            done: Default::default(),
+            overflow: Default::default(),
+            celdas: std::array::from_fn(|_| Default::default()),
+            retardo_a: (0..Self::RETARDOS).map(|_| EdgeDFF::new(0.into())).collect(),
+            retardo_b: (0..Self::RETARDOS).map(|_| EdgeDFF::new(0.into())).collect(),
+            paso: EdgeDFF::new(0.into()),
+            acepta: Default::default(),
+            entrada_a: std::array::from_fn(|_| Default::default()),
+            entrada_b: std::array::from_fn(|_| Default::default()),
+            carga: Constant::new((N as u64).into()),
+            fin: Constant::new(((3 * N - 2 + A::LATENCY) as u64).into()),
+        }
+    }
+}
+
+impl<const N: usize, const W: usize, const O: usize, const NN: usize, A: MulAlgorithm, M: OverflowMode> Logic
+    for SystolicMatMul<N, W, O, NN, A, M>
+{
+    #[hdl_gen]
+    fn update(&mut self) {
+        // Enlazamos el reloj
+        for k in 0..NN {
+            self.celdas[k].clk.next = self.clk.val();
+            self.celdas[k].rst.next = self.rst.val();
+            self.result[k].next = self.celdas[k].acc.val();
+        }
+        for k in 0..Self::RETARDOS {
+            self.retardo_a[k].clk.next = self.clk.val();
+            self.retardo_b[k].clk.next = self.clk.val();
+        }
+        self.paso.clk.next = self.clk.val();
+
+        // Alguna celda se ha desbordado
+        self.overflow.next = false;
+        for k in 0..NN {
+            if self.celdas[k].overflow.val() {
+                self.overflow.next = true;
+            }
+        }
+
+        // Control: carga de N datos, vaciado y done
+        self.ready.next = self.paso.q.val() < self.carga.val();
+        self.done.next = self.paso.q.val() == self.fin.val();
+        self.acepta.next = self.valid_in.val() & (self.paso.q.val() < self.carga.val());
+        if self.rst.val() | (self.paso.q.val() == self.fin.val()) {
+            self.paso.d.next = 0.into();
+        } else if self.acepta.val() | (self.paso.q.val() >= self.carga.val()) {
+            self.paso.d.next = self.paso.q.val() + 1;
+        } else {
+            self.paso.d.next = self.paso.q.val();
+        }
+
+        // El primer dato de una multiplicación borra los acumuladores
+        for k in 0..NN {
+            self.celdas[k].clear.next = self.acepta.val() & (self.paso.q.val() == 0);
+        }
+
+        // Sin dato válido entran ceros
+        for i in 0..N {
+            if self.acepta.val() {
+                self.entrada_a[i].next = self.a_in[i].val();
+                self.entrada_b[i].next = self.b_in[i].val();
+            } else {
+                self.entrada_a[i].next = 0.into();
+                self.entrada_b[i].next = 0.into();
+            }
+        }
+
+        // Líneas de retardo: cada registro toma el anterior salvo el primero
+        // de cada línea, que toma la entrada. Como #[hdl_gen] no deja que un
+        // bucle acabe en la variable de otro, se encadenan todos y luego se
+        // cambia el primero de cada línea
+        for k in 1..Self::RETARDOS {
+            self.retardo_a[k].d.next = self.retardo_a[k - 1].q.val();
+            self.retardo_b[k].d.next = self.retardo_b[k - 1].q.val();
+        }
+        for i in 1..N {
+            self.retardo_a[i * (i - 1) / 2].d.next = self.entrada_a[i].val();
+            self.retardo_b[i * (i - 1) / 2].d.next = self.entrada_b[i].val();
+        }
+
+        // Bordes del array: la fila i de A y la columna j de B llegan con i y
+        // j ciclos de retraso, del último registro de su línea
+        self.celdas[0].a_in.next = self.entrada_a[0].val();
+        self.celdas[0].b_in.next = self.entrada_b[0].val();
+
+        // N entra como `n`: bucle de una vuelta, ver tfg_hdl_common
+        for n in N..(N + 1) {
+            for i in 1..N {
+                self.celdas[i * n].a_in.next = self.retardo_a[i * (i + 1) / 2 - 1].q.val();
+                self.celdas[i].b_in.next = self.retardo_b[i * (i + 1) / 2 - 1].q.val();
+            }
+            // Interior: A viene de la celda de la izquierda y B de la de arriba
+            for i in 0..N {
+                for j in 1..N {
+                    self.celdas[i * n + j].a_in.next = self.celdas[i * n + j - 1].a_out.val();
+                    self.celdas[j * n + i].b_in.next = self.celdas[j * n + i - n].b_out.val();
+                }
+            }
+        }
+    }
+}
+
+// Celda del array: en cada flanco acumula a_in·b_in y pasa a_in a la derecha
+// y b_in hacia abajo. Con clear el acumulador y overflow empiezan de nuevo.
+// Con un multiplicador registrado lo que se acumula es el producto de a_in y
+// b_in de A::LATENCY ciclos antes
+#[derive(LogicBlock, Clone)]
+struct CeldaSistolica<const W: usize, const O: usize, A: MulAlgorithm, M: OverflowMode> {
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    pub clear: Signal<In, Bit>,
+    pub a_in: Signal<In, Signed<W>>,
+    pub b_in: Signal<In, Signed<W>>,
+    pub a_out: Signal<Out, Signed<W>>,
+    pub b_out: Signal<Out, Signed<W>>,
+    pub acc: Signal<Out, Signed<O>>,
+    pub overflow: Signal<Out, Bit>,
+    a: EdgeDFF<Signed<W>>,
+    b: EdgeDFF<Signed<W>>,
+    suma: EdgeDFF<Signed<O>>,
+    desborde: EdgeDFF<Bit>,
+    producto: SignedMul<W, O, A>,
+    sumador: OverflowAdder<O, TwosComplement, M>,
+}
+
+impl<const W: usize, const O: usize, A: MulAlgorithm, M: OverflowMode> Default for CeldaSistolica<W, O, A, M> {
+    fn default() -> Self {
+        Self {
+            clk: Default::default(),
+            rst: Default::default(),
+            clear: Default::default(),
+            a_in: Default::default(),
+            b_in: Default::default(),
+            a_out: Default::default(),
+            b_out: Default::default(),
+            acc: Default::default(),
+            overflow: Default::default(),
+            a: EdgeDFF::new(0.into()),
+            b: EdgeDFF::new(0.into()),
+            suma: EdgeDFF::new(0.into()),
+            desborde: EdgeDFF::new(false),
+            producto: Default::default(),
+            sumador: Default::default(),
+        }
+    }
+}
+
+impl<const W: usize, const O: usize, A: MulAlgorithm, M: OverflowMode> Logic for CeldaSistolica<W, O, A, M> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.a.clk.next = self.clk.val();
+        self.b.clk.next = self.clk.val();
+        self.suma.clk.next = self.clk.val();
+        self.desborde.clk.next = self.clk.val();
+        self.producto.clk.next = self.clk.val();
+
+        self.producto.a.next = self.a_in.val();
+        self.producto.b.next = self.b_in.val();
+
+        // Con clear el producto se suma a 0 en vez de al acumulador
+        self.sumador.a.next = unsigned_cast(self.suma.q.val());
+        if self.clear.val() {
+            self.sumador.a.next = 0.into();
+        }
+        self.sumador.b.next = unsigned_cast(self.producto.p.val());
+
+        if self.rst.val() {
+            self.a.d.next = 0.into();
+            self.b.d.next = 0.into();
+            self.suma.d.next = 0.into();
+            self.desborde.d.next = false;
+        } else {
+            self.a.d.next = self.a_in.val();
+            self.b.d.next = self.b_in.val();
+            self.suma.d.next = signed_cast(self.sumador.sum.val());
+            if self.clear.val() {
+                self.desborde.d.next = self.sumador.overflow.val();
+            } else {
+                self.desborde.d.next = self.desborde.q.val() | self.sumador.overflow.val();
+            }
+        }
+
+        self.a_out.next = self.a.q.val();
+        self.b_out.next = self.b.q.val();
+        self.acc.next = self.suma.q.val();
+        self.overflow.next = self.desborde.q.val();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pruebas::{matrices, producto, Matriz};
+    use anyhow::anyhow;
+    use std::sync::{Arc, Mutex};
+    use tfg_hdl_common::{Booth, Input, Output, Registered, Saturate, TestVector, TestVectors, Wallace};
+
+
+    struct Puertos {
+        rst: Input,
+        valid_in: Input,
+        a_in: Vec<Input>,
+        b_in: Vec<Input>,
+        ready: Output,
+        result: Vec<Output>,
+        done: Output,
+        overflow: Output,
+    }
+
+    fn banco<const N: usize, const O: usize, const NN: usize, A: MulAlgorithm, M: OverflowMode>(
+        modulo: &str,
+    ) -> (TestVectors<SystolicMatMul<N, 4, O, NN, A, M>>, Puertos) {
+        let mut tb = TestVectors::<SystolicMatMul<N, 4, O, NN, A, M>>::new(modulo).clock("clk", |x| &mut x.clk);
+        let puertos = Puertos {
+            rst: tb.input("rst", |x| &mut x.rst),
+            valid_in: tb.input("valid_in", |x| &mut x.valid_in),
+            a_in: (0..N)
+                .map(|i| tb.input(&format!("a_in${i}"), move |x| &mut x.a_in[i]))
+                .collect(),
+            b_in: (0..N)
+                .map(|j| tb.input(&format!("b_in${j}"), move |x| &mut x.b_in[j]))
+                .collect(),
+            ready: tb.output("ready", |x| &x.ready),
+            result: (0..NN)
+                .map(|k| tb.output(&format!("result${k}"), move |x| &x.result[k]))
+                .collect(),
+            done: tb.output("done", |x| &x.done),
+            overflow: tb.output("overflow", |x| &x.overflow),
+        };
+        tb.push(TestVector::new("reset").set(puertos.rst, 1).set(puertos.valid_in, 0));
+        (tb, puertos)
+    }
+
+    // Añade una multiplicación completa: los N datos (con `huecos` ciclos sin
+    // dato antes de cada uno) y el vaciado, comprobando ready y done en cada
+    // ciclo y el resultado cuando sube done
+    fn multiplicar<const N: usize, const O: usize, const NN: usize, A: MulAlgorithm, M: OverflowMode>(
+        tb: &mut TestVectors<SystolicMatMul<N, 4, O, NN, A, M>>,
+        p: &Puertos,
+        nombre: &str,
+        a: &Matriz<N>,
+        b: &Matriz<N>,
+        huecos: usize,
+    ) {
+        let mut vectores = vec![];
+        for k in 0..N {
+            for _ in 0..huecos {
+                vectores.push(
+                    TestVector::new(nombre)
+                        .set(p.rst, 0)
+                        .set(p.valid_in, 0)
+                        .expect(p.ready, 1),
+                );
+            }
+            let mut vector = TestVector::new(&format!("{nombre}, dato {k}"))
+                .set(p.rst, 0)
+                .set(p.valid_in, 1);
+            for i in 0..N {
+                vector = vector.set(p.a_in[i], a[i][k]).set(p.b_in[i], b[k][i]);
+            }
+            vectores.push(vector);
+        }
+        for _ in 0..2 * N - 2 + A::LATENCY {
+            vectores.push(
+                TestVector::new(&format!("{nombre}, vaciado"))
+                    .set(p.valid_in, 0)
+                    .expect(p.ready, 0),
+            );
+        }
+        // done sólo en el último ciclo, con el resultado
+        let (c, desborda) = producto::<N, M>(a, b, O);
+        let ultimo = vectores.len() - 1;
+        for (n, mut vector) in vectores.into_iter().enumerate() {
+            vector = vector.expect(p.done, (n == ultimo) as i64);
+            if n == ultimo {
+                for (k, &salida) in p.result.iter().enumerate() {
+                    vector = vector.expect(salida, c[k / N][k % N]);
+                }
+                vector = vector.expect(p.overflow, desborda as i64);
+            }
+            tb.push(vector);
+        }
+        tb.push(
+            TestVector::new(&format!("{nombre}, listo"))
+                .expect(p.ready, 1)
+                .expect(p.done, 0),
+        );
+    }
+
+    fn comprobar<const N: usize, const O: usize, const NN: usize>(modulo: &str) -> anyhow::Result<()> {
+        comprobar_con::<N, O, NN, Operator, Wrap>(modulo)
+    }
+
+    fn comprobar_con<const N: usize, const O: usize, const NN: usize, A: MulAlgorithm, M: OverflowMode>(
+        modulo: &str,
+    ) -> anyhow::Result<()> {
+        let mut uut = SystolicMatMul::<N, 4, O, NN, A, M>::default();
+        uut.connect_all();
+        let (mut tb, p) = banco::<N, O, NN, A, M>(modulo);
+        // Casos extremos, seguidas y con huecos entre los datos
+        multiplicar(&mut tb, &p, "mínimos", &[[-8; N]; N], &[[-8; N]; N], 0);
+        multiplicar(&mut tb, &p, "mínimo por máximo", &[[-8; N]; N], &[[7; N]; N], 0);
+        for semilla in 0..3 {
+            let (a, b) = matrices::<N>(semilla);
+            multiplicar(&mut tb, &p, &format!("caso {semilla}"), &a, &b, semilla as usize);
+        }
+        // Uno que nunca se desborda, para ver que se borra overflow
+        multiplicar(&mut tb, &p, "pequeños", &[[1; N]; N], &[[-1; N]; N], 1);
+
+        tb.verify(&uut, 100_000)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_sistolico_2x2() -> anyhow::Result<()> {
+        comprobar::<2, 9, 4>("SystolicMatMul2")
+    }
+
+    #[test]
+    fn test_sistolico_1_3_y_4() -> anyhow::Result<()> {
+        comprobar::<1, 8, 1>("SystolicMatMul1")?;
+        comprobar::<3, 10, 9>("SystolicMatMul3")?;
+        comprobar::<4, 10, 16>("SystolicMatMul4")
+    }
+
+    // Las celdas con los multiplicadores de Booth y de Wallace
+    #[test]
+    fn test_sistolico_booth_y_wallace() -> anyhow::Result<()> {
+        comprobar_con::<3, 10, 9, Booth, Wrap>("SystolicMatMul3Booth")?;
+        comprobar_con::<3, 10, 9, Wallace, Wrap>("SystolicMatMul3Wallace")
+    }
+
+    // Con el producto registrado el vaciado tarda A::LATENCY ciclos más
+    #[test]
+    fn test_sistolico_con_multiplicador_registrado() -> anyhow::Result<()> {
+        comprobar_con::<3, 10, 9, Registered<Booth>, Wrap>("SystolicMatMul3BoothRegistrado")?;
+        comprobar_con::<2, 8, 4, Registered<Registered<Wallace>>, Saturate>("SystolicMatMul2Registrado2")?;
+        assert_eq!(SystolicMatMul::<3, 4, 10, 9, Registered<Booth>>::LATENCY, 7);
+        Ok(())
+    }
+
+    // Con O = 2W = 8 bits los casos extremos se desbordan (3·64 = 192)
+    #[test]
+    fn test_desbordamiento() -> anyhow::Result<()> {
+        comprobar_con::<3, 8, 9, Operator, Wrap>("SystolicMatMul3Wrap")?;
+        comprobar_con::<3, 8, 9, Operator, Saturate>("SystolicMatMul3Saturate")?;
+        comprobar_con::<4, 9, 16, Wallace, Saturate>("SystolicMatMul4Saturate")
+    }
+
+    // A·B = C, el caso mixto de MultiplicacionMatricesSecuencial, por filas
+    const A: [i64; 4] = [4, -3, -2, 5];
+    const B: [i64; 4] = [-4, 2, 3, -5];
+    const C: [i64; 4] = [-25, 23, 23, -29];
+
+    // Simula `uut` y cuenta los flancos de reloj que hay desde el primero
+    // después del reset (el de start o el del primer dato) hasta que `listo`
+    // (done a 1 con C en result). `entrada` pone las entradas de cada ciclo,
+    // contados desde 0
+    fn ciclos_hasta_done<T: Block + Send + 'static>(
+        uut: T,
+        reloj: fn(&mut T, bool),
+        rst: fn(&mut T, bool),
+        entrada: fn(&mut T, usize),
+        listo: fn(&T) -> bool,
+    ) -> anyhow::Result<usize> {
+        let medida = Arc::new(Mutex::new(None));
+        let resultado = medida.clone();
+        let mut sim = Simulation::<T>::new();
+        sim.add_testbench(move |mut ep: Sim<T>| {
+            let mut x = ep.init()?;
+            let flanco = |ep: &mut Sim<T>, mut x: Box<T>| {
+                reloj(&mut x, false);
+                x = ep.wait(1, x)?;
+                reloj(&mut x, true);
+                ep.wait(1, x)
+            };
+            rst(&mut x, true);
+            x = flanco(&mut ep, x)?;
+            rst(&mut x, false);
+            for ciclo in 0..100 {
+                entrada(&mut x, ciclo);
+                x = flanco(&mut ep, x)?;
+                if listo(&x) {
+                    *resultado.lock().unwrap() = Some(ciclo);
+                    break;
+                }
+            }
+            ep.done(x)
+        });
+        sim.run(Box::new(uut), 10_000).map_err(|e| anyhow!("{:?}", e))?;
+        let ciclos = *medida.lock().unwrap();
+        ciclos.ok_or_else(|| anyhow!("done no se ha puesto a 1"))
+    }
+
+    fn es_c<const O: usize>(result: &[Signal<Out, Signed<O>>]) -> bool {
+        result.iter().zip(C).all(|(r, c)| r.val() == Signed::<O>::from(c))
+    }
+
+    // Ciclos hasta done medidos en simulación, con 2x2 de 4 bits en todos
+    #[test]
+    fn test_latencia() -> anyhow::Result<()> {
+        let mut combinacional = multiplicacion_matrices::multiplicacionMatrices::default();
+        combinacional.connect_all();
+        let combinacional = ciclos_hasta_done(
+            combinacional,
+            |_, _| {},
+            |x, v| x.rst.next = v,
+            |x, _| {
+                for e in 0..4 {
+                    x.a[e].next = A[e].into();
+                    x.b[e].next = B[e].into();
+                }
+            },
+            |x| es_c(&x.result),
+        )?;
+
+        let mut secuencial = crate::MultiplicacionMatricesSecuencial::default();
+        secuencial.connect_all();
+        let secuencial = ciclos_hasta_done(
+            secuencial,
+            |x, v| x.clk.next = Clock { clk: v },
+            |x, v| x.rst.next = v,
+            |x, ciclo| {
+                x.start.next = ciclo == 0;
+                for e in 0..4 {
+                    x.a[e].next = A[e].into();
+                    x.b[e].next = B[e].into();
+                }
+            },
+            |x| x.done.val() && es_c(&x.result),
+        )?;
+
+        let mut generico = crate::SequentialMatMul::<2, 1, 4, 9, 4>::default();
+        generico.connect_all();
+        let generico = ciclos_hasta_done(
+            generico,
+            |x, v| x.clk.next = Clock { clk: v },
+            |x, v| x.rst.next = v,
+            |x, ciclo| {
+                x.start.next = ciclo == 0;
+                for e in 0..4 {
+                    x.a[e].next = A[e].into();
+                    x.b[e].next = B[e].into();
+                }
+            },
+            |x| x.done.val() && es_c(&x.result),
+        )?;
+
+        let mut sistolico = SystolicMatMul::<2, 4, 9, 4>::default();
+        sistolico.connect_all();
+        let sistolico = ciclos_hasta_done(
+            sistolico,
+            |x, v| x.clk.next = Clock { clk: v },
+            |x, v| x.rst.next = v,
+            // Columna k de A y fila k de B, seguidas
+            |x, k| {
+                x.valid_in.next = k < 2;
+                for i in 0..2 {
+                    x.a_in[i].next = A[i * 2 + k % 2].into();
+                    x.b_in[i].next = B[(k % 2) * 2 + i].into();
+                }
+            },
+            |x| x.done.val() && es_c(&x.result),
+        )?;
+
+        // La lógica combinacional da C en el mismo ciclo, la máquina de
+        // estados tarda los cuatro pasos de Calc1..Calc4, el secuencial
+        // genérico con un multiplicador un ciclo por producto y el sistólico
+        // 3N - 3
+        assert_eq!(combinacional, 0);
+        assert_eq!(secuencial, 4);
+        assert_eq!(generico, crate::SequentialMatMul::<2, 1, 4, 9, 4>::LATENCY);
+        assert_eq!(generico, 8);
+        assert_eq!(sistolico, SystolicMatMul::<2, 4, 9, 4>::LATENCY);
+        assert_eq!(sistolico, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verilog_sistolico() {
+        let mut uut = SystolicMatMul::<3, 4, 10, 9>::default();
+        uut.connect_all();
+        let codigo = tfg_hdl_common::generate_named_verilog(&uut, "SystolicMatMul3").unwrap();
+        // La celda (2, 1) recibe A de la (2, 0) y B de la (1, 1)
+        assert!(codigo.contains("celdas$7$a_in = celdas$6$a_out;"), "{}", codigo);
+        assert!(codigo.contains("celdas$7$b_in = celdas$4$b_out;"));
+        // La fila 2 de A entra con dos ciclos de retraso, por los registros 1
+        // y 2 de retardo_a (el 0 es el de la fila 1), y no sobra ninguno
+        assert!(codigo.contains("retardo_a$1$d = entrada_a$2;"));
+        assert!(codigo.contains("retardo_a$2$d = retardo_a$1$q;"));
+        assert!(codigo.contains("celdas$6$a_in = retardo_a$2$q;"));
+        assert!(codigo.contains("retardo_b$2$clk = clk;"));
+        assert!(!codigo.contains("retardo_a$3"));
+        assert!(codigo.contains("always @(*) resul = $signed(a) * $signed(b);"));
+    }
+}
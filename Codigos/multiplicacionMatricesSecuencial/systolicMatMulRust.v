@@ -0,0 +1,1304 @@
+// Módulo SystolicMatMul generado con RustHDL
+// Crate: multiplicacionMatricesSecuencial v0.1.0
+
+
+module SystolicMatMul(clk,rst,a_in$0,a_in$1,b_in$0,b_in$1,valid_in,ready,result$0,result$1,result$2,result$3,done,overflow);
+    
+    // Module arguments
+    input wire  clk;
+    input wire  rst;
+    input wire signed [3:0] a_in$0;
+    input wire signed [3:0] a_in$1;
+    input wire signed [3:0] b_in$0;
+    input wire signed [3:0] b_in$1;
+    input wire  valid_in;
+    output reg  ready;
+    output reg signed [8:0] result$0;
+    output reg signed [8:0] result$1;
+    output reg signed [8:0] result$2;
+    output reg signed [8:0] result$3;
+    output reg  done;
+    output reg  overflow;
+    
+    // Constant declarations
+    localparam  carga = 16'h2;
+    localparam  fin = 16'h4;
+    
+    // Stub signals
+    reg  celdas$0$clk;
+    reg  celdas$0$rst;
+    reg  celdas$0$clear;
+    reg signed [3:0] celdas$0$a_in;
+    reg signed [3:0] celdas$0$b_in;
+    wire signed [3:0] celdas$0$a_out;
+    wire signed [3:0] celdas$0$b_out;
+    wire signed [8:0] celdas$0$acc;
+    wire  celdas$0$overflow;
+    reg  celdas$1$clk;
+    reg  celdas$1$rst;
+    reg  celdas$1$clear;
+    reg signed [3:0] celdas$1$a_in;
+    reg signed [3:0] celdas$1$b_in;
+    wire signed [3:0] celdas$1$a_out;
+    wire signed [3:0] celdas$1$b_out;
+    wire signed [8:0] celdas$1$acc;
+    wire  celdas$1$overflow;
+    reg  celdas$2$clk;
+    reg  celdas$2$rst;
+    reg  celdas$2$clear;
+    reg signed [3:0] celdas$2$a_in;
+    reg signed [3:0] celdas$2$b_in;
+    wire signed [3:0] celdas$2$a_out;
+    wire signed [3:0] celdas$2$b_out;
+    wire signed [8:0] celdas$2$acc;
+    wire  celdas$2$overflow;
+    reg  celdas$3$clk;
+    reg  celdas$3$rst;
+    reg  celdas$3$clear;
+    reg signed [3:0] celdas$3$a_in;
+    reg signed [3:0] celdas$3$b_in;
+    wire signed [3:0] celdas$3$a_out;
+    wire signed [3:0] celdas$3$b_out;
+    wire signed [8:0] celdas$3$acc;
+    wire  celdas$3$overflow;
+    reg signed [3:0] retardo_a$0$d;
+    wire signed [3:0] retardo_a$0$q;
+    reg  retardo_a$0$clk;
+    reg signed [3:0] retardo_b$0$d;
+    wire signed [3:0] retardo_b$0$q;
+    reg  retardo_b$0$clk;
+    reg  [15:0] paso$d;
+    wire  [15:0] paso$q;
+    reg  paso$clk;
+    
+    // Local signals
+    reg  acepta;
+    reg signed [3:0] entrada_a$0;
+    reg signed [3:0] entrada_a$1;
+    reg signed [3:0] entrada_b$0;
+    reg signed [3:0] entrada_b$1;
+    
+    // Sub module instances
+    SystolicMatMul$celdas$0 celdas$0(
+        .clk(celdas$0$clk),
+        .rst(celdas$0$rst),
+        .clear(celdas$0$clear),
+        .a_in(celdas$0$a_in),
+        .b_in(celdas$0$b_in),
+        .a_out(celdas$0$a_out),
+        .b_out(celdas$0$b_out),
+        .acc(celdas$0$acc),
+        .overflow(celdas$0$overflow)
+    );
+    SystolicMatMul$celdas$1 celdas$1(
+        .clk(celdas$1$clk),
+        .rst(celdas$1$rst),
+        .clear(celdas$1$clear),
+        .a_in(celdas$1$a_in),
+        .b_in(celdas$1$b_in),
+        .a_out(celdas$1$a_out),
+        .b_out(celdas$1$b_out),
+        .acc(celdas$1$acc),
+        .overflow(celdas$1$overflow)
+    );
+    SystolicMatMul$celdas$2 celdas$2(
+        .clk(celdas$2$clk),
+        .rst(celdas$2$rst),
+        .clear(celdas$2$clear),
+        .a_in(celdas$2$a_in),
+        .b_in(celdas$2$b_in),
+        .a_out(celdas$2$a_out),
+        .b_out(celdas$2$b_out),
+        .acc(celdas$2$acc),
+        .overflow(celdas$2$overflow)
+    );
+    SystolicMatMul$celdas$3 celdas$3(
+        .clk(celdas$3$clk),
+        .rst(celdas$3$rst),
+        .clear(celdas$3$clear),
+        .a_in(celdas$3$a_in),
+        .b_in(celdas$3$b_in),
+        .a_out(celdas$3$a_out),
+        .b_out(celdas$3$b_out),
+        .acc(celdas$3$acc),
+        .overflow(celdas$3$overflow)
+    );
+    SystolicMatMul$retardo_a$0 retardo_a$0(
+        .d(retardo_a$0$d),
+        .q(retardo_a$0$q),
+        .clk(retardo_a$0$clk)
+    );
+    SystolicMatMul$retardo_b$0 retardo_b$0(
+        .d(retardo_b$0$d),
+        .q(retardo_b$0$q),
+        .clk(retardo_b$0$clk)
+    );
+    SystolicMatMul$paso paso(
+        .d(paso$d),
+        .q(paso$q),
+        .clk(paso$clk)
+    );
+    
+    // Update code
+    always @(*) begin
+        celdas$0$clk = clk;
+        celdas$0$rst = rst;
+        result$0 = celdas$0$acc;
+        celdas$1$clk = clk;
+        celdas$1$rst = rst;
+        result$1 = celdas$1$acc;
+        celdas$2$clk = clk;
+        celdas$2$rst = rst;
+        result$2 = celdas$2$acc;
+        celdas$3$clk = clk;
+        celdas$3$rst = rst;
+        result$3 = celdas$3$acc;
+        retardo_a$0$clk = clk;
+        retardo_b$0$clk = clk;
+        paso$clk = clk;
+        overflow = 1'b0;
+        if (celdas$0$overflow) begin
+            overflow = 1'b1;
+        end
+        if (celdas$1$overflow) begin
+            overflow = 1'b1;
+        end
+        if (celdas$2$overflow) begin
+            overflow = 1'b1;
+        end
+        if (celdas$3$overflow) begin
+            overflow = 1'b1;
+        end
+        ready = paso$q < carga;
+        done = paso$q == fin;
+        acepta = valid_in & (paso$q < carga);
+        if (rst | (paso$q == fin)) begin
+            paso$d = 32'h0;
+        end
+        else if (acepta | (paso$q >= carga)) begin
+            paso$d = paso$q + 32'h1;
+        end
+        else begin
+            paso$d = paso$q;
+        end
+        celdas$0$clear = acepta & (paso$q == 32'h0);
+        celdas$1$clear = acepta & (paso$q == 32'h0);
+        celdas$2$clear = acepta & (paso$q == 32'h0);
+        celdas$3$clear = acepta & (paso$q == 32'h0);
+        if (acepta) begin
+            entrada_a$0 = a_in$0;
+            entrada_b$0 = b_in$0;
+        end
+        else begin
+            entrada_a$0 = 32'h0;
+            entrada_b$0 = 32'h0;
+        end
+        if (acepta) begin
+            entrada_a$1 = a_in$1;
+            entrada_b$1 = b_in$1;
+        end
+        else begin
+            entrada_a$1 = 32'h0;
+            entrada_b$1 = 32'h0;
+        end
+        retardo_a$0$d = entrada_a$1;
+        retardo_b$0$d = entrada_b$1;
+        celdas$0$a_in = entrada_a$0;
+        celdas$0$b_in = entrada_b$0;
+        celdas$2$a_in = retardo_a$0$q;
+        celdas$1$b_in = retardo_b$0$q;
+        celdas$1$a_in = celdas$0$a_out;
+        celdas$2$b_in = celdas$0$b_out;
+        celdas$3$a_in = celdas$2$a_out;
+        celdas$3$b_in = celdas$1$b_out;
+    end
+    
+endmodule // SystolicMatMul
+
+
+module SystolicMatMul$celdas$0(clk,rst,clear,a_in,b_in,a_out,b_out,acc,overflow);
+    
+    // Module arguments
+    input wire  clk;
+    input wire  rst;
+    input wire  clear;
+    input wire signed [3:0] a_in;
+    input wire signed [3:0] b_in;
+    output reg signed [3:0] a_out;
+    output reg signed [3:0] b_out;
+    output reg signed [8:0] acc;
+    output reg  overflow;
+    
+    // Stub signals
+    reg signed [3:0] a$d;
+    wire signed [3:0] a$q;
+    reg  a$clk;
+    reg signed [3:0] b$d;
+    wire signed [3:0] b$q;
+    reg  b$clk;
+    reg signed [8:0] suma$d;
+    wire signed [8:0] suma$q;
+    reg  suma$clk;
+    reg  desborde$d;
+    wire  desborde$q;
+    reg  desborde$clk;
+    reg signed [3:0] producto$a;
+    reg signed [3:0] producto$b;
+    wire signed [8:0] producto$p;
+    reg  producto$clk;
+    reg  [8:0] sumador$a;
+    reg  [8:0] sumador$b;
+    wire  [8:0] sumador$sum;
+    wire  sumador$overflow;
+    
+    // Sub module instances
+    SystolicMatMul$celdas$0$a a(
+        .d(a$d),
+        .q(a$q),
+        .clk(a$clk)
+    );
+    SystolicMatMul$celdas$0$b b(
+        .d(b$d),
+        .q(b$q),
+        .clk(b$clk)
+    );
+    SystolicMatMul$celdas$0$suma suma(
+        .d(suma$d),
+        .q(suma$q),
+        .clk(suma$clk)
+    );
+    SystolicMatMul$celdas$0$desborde desborde(
+        .d(desborde$d),
+        .q(desborde$q),
+        .clk(desborde$clk)
+    );
+    SystolicMatMul$celdas$0$producto producto(
+        .a(producto$a),
+        .b(producto$b),
+        .p(producto$p),
+        .clk(producto$clk)
+    );
+    SystolicMatMul$celdas$0$sumador sumador(
+        .a(sumador$a),
+        .b(sumador$b),
+        .sum(sumador$sum),
+        .overflow(sumador$overflow)
+    );
+    
+    // Update code
+    always @(*) begin
+        a$clk = clk;
+        b$clk = clk;
+        suma$clk = clk;
+        desborde$clk = clk;
+        producto$clk = clk;
+        producto$a = a_in;
+        producto$b = b_in;
+        sumador$a = $unsigned(suma$q);
+        if (clear) begin
+            sumador$a = 32'h0;
+        end
+        sumador$b = $unsigned(producto$p);
+        if (rst) begin
+            a$d = 32'h0;
+            b$d = 32'h0;
+            suma$d = 32'h0;
+            desborde$d = 1'b0;
+        end
+        else begin
+            a$d = a_in;
+            b$d = b_in;
+            suma$d = $signed(sumador$sum);
+            if (clear) begin
+                desborde$d = sumador$overflow;
+            end
+            else begin
+                desborde$d = desborde$q | sumador$overflow;
+            end
+        end
+        a_out = a$q;
+        b_out = b$q;
+        acc = suma$q;
+        overflow = desborde$q;
+    end
+    
+endmodule // SystolicMatMul$celdas$0
+
+
+module SystolicMatMul$celdas$0$a(d,q,clk);
+    
+    // Module arguments
+    input wire signed [3:0] d;
+    output reg signed [3:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 4'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$0$a
+
+
+module SystolicMatMul$celdas$0$b(d,q,clk);
+    
+    // Module arguments
+    input wire signed [3:0] d;
+    output reg signed [3:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 4'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$0$b
+
+
+module SystolicMatMul$celdas$0$desborde(d,q,clk);
+    
+    // Module arguments
+    input wire  d;
+    output reg  q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 1'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$0$desborde
+
+
+module SystolicMatMul$celdas$0$producto(a,b,p,clk);
+    
+    // Module arguments
+    input wire signed [3:0] a;
+    input wire signed [3:0] b;
+    output reg signed [8:0] p;
+    input wire  clk;
+    
+    // Stub signals
+    reg  [3:0] nucleo$a;
+    reg  [3:0] nucleo$b;
+    wire  [8:0] nucleo$resul;
+    
+    // Sub module instances
+    SystolicMatMul$celdas$0$producto$nucleo nucleo(
+        .a(nucleo$a),
+        .b(nucleo$b),
+        .resul(nucleo$resul)
+    );
+    
+    // Update code (custom)
+    always @(*) begin
+        nucleo$a = a;
+        nucleo$b = b;
+        p = nucleo$resul;
+    end
+endmodule // SystolicMatMul$celdas$0$producto
+
+
+module SystolicMatMul$celdas$0$producto$nucleo(a,b,resul);
+    
+    // Module arguments
+    input wire  [3:0] a;
+    input wire  [3:0] b;
+    output reg  [8:0] resul;
+    
+    // Update code (custom)
+    always @(*) resul = $signed(a) * $signed(b);
+endmodule // SystolicMatMul$celdas$0$producto$nucleo
+
+
+module SystolicMatMul$celdas$0$suma(d,q,clk);
+    
+    // Module arguments
+    input wire signed [8:0] d;
+    output reg signed [8:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 9'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$0$suma
+
+
+module SystolicMatMul$celdas$0$sumador(a,b,sum,overflow);
+    
+    // Module arguments
+    input wire  [8:0] a;
+    input wire  [8:0] b;
+    output reg  [8:0] sum;
+    output reg  overflow;
+    
+    // Constant declarations
+    localparam  maximo = 9'b11111111;
+    localparam  minimo = 9'b100000000;
+    localparam  con_signo = 1'b1;
+    localparam  saturar = 1'b0;
+    
+    // Local signals
+    reg  [8:0] suma;
+    
+    // Update code
+    always @(*) begin
+        suma = a + b;
+        sum = suma;
+        overflow = 1'b0;
+        if (con_signo) begin
+            if ((a[8] == b[8]) & (suma[8] != a[8])) begin
+                overflow = 1'b1;
+                if (saturar) begin
+                    if (a[8]) begin
+                        sum = minimo;
+                    end
+                    else begin
+                        sum = maximo;
+                    end
+                end
+            end
+        end
+        else if (suma < a) begin
+            overflow = 1'b1;
+            if (saturar) begin
+                sum = maximo;
+            end
+        end
+    end
+    
+endmodule // SystolicMatMul$celdas$0$sumador
+
+
+module SystolicMatMul$celdas$1(clk,rst,clear,a_in,b_in,a_out,b_out,acc,overflow);
+    
+    // Module arguments
+    input wire  clk;
+    input wire  rst;
+    input wire  clear;
+    input wire signed [3:0] a_in;
+    input wire signed [3:0] b_in;
+    output reg signed [3:0] a_out;
+    output reg signed [3:0] b_out;
+    output reg signed [8:0] acc;
+    output reg  overflow;
+    
+    // Stub signals
+    reg signed [3:0] a$d;
+    wire signed [3:0] a$q;
+    reg  a$clk;
+    reg signed [3:0] b$d;
+    wire signed [3:0] b$q;
+    reg  b$clk;
+    reg signed [8:0] suma$d;
+    wire signed [8:0] suma$q;
+    reg  suma$clk;
+    reg  desborde$d;
+    wire  desborde$q;
+    reg  desborde$clk;
+    reg signed [3:0] producto$a;
+    reg signed [3:0] producto$b;
+    wire signed [8:0] producto$p;
+    reg  producto$clk;
+    reg  [8:0] sumador$a;
+    reg  [8:0] sumador$b;
+    wire  [8:0] sumador$sum;
+    wire  sumador$overflow;
+    
+    // Sub module instances
+    SystolicMatMul$celdas$1$a a(
+        .d(a$d),
+        .q(a$q),
+        .clk(a$clk)
+    );
+    SystolicMatMul$celdas$1$b b(
+        .d(b$d),
+        .q(b$q),
+        .clk(b$clk)
+    );
+    SystolicMatMul$celdas$1$suma suma(
+        .d(suma$d),
+        .q(suma$q),
+        .clk(suma$clk)
+    );
+    SystolicMatMul$celdas$1$desborde desborde(
+        .d(desborde$d),
+        .q(desborde$q),
+        .clk(desborde$clk)
+    );
+    SystolicMatMul$celdas$1$producto producto(
+        .a(producto$a),
+        .b(producto$b),
+        .p(producto$p),
+        .clk(producto$clk)
+    );
+    SystolicMatMul$celdas$1$sumador sumador(
+        .a(sumador$a),
+        .b(sumador$b),
+        .sum(sumador$sum),
+        .overflow(sumador$overflow)
+    );
+    
+    // Update code
+    always @(*) begin
+        a$clk = clk;
+        b$clk = clk;
+        suma$clk = clk;
+        desborde$clk = clk;
+        producto$clk = clk;
+        producto$a = a_in;
+        producto$b = b_in;
+        sumador$a = $unsigned(suma$q);
+        if (clear) begin
+            sumador$a = 32'h0;
+        end
+        sumador$b = $unsigned(producto$p);
+        if (rst) begin
+            a$d = 32'h0;
+            b$d = 32'h0;
+            suma$d = 32'h0;
+            desborde$d = 1'b0;
+        end
+        else begin
+            a$d = a_in;
+            b$d = b_in;
+            suma$d = $signed(sumador$sum);
+            if (clear) begin
+                desborde$d = sumador$overflow;
+            end
+            else begin
+                desborde$d = desborde$q | sumador$overflow;
+            end
+        end
+        a_out = a$q;
+        b_out = b$q;
+        acc = suma$q;
+        overflow = desborde$q;
+    end
+    
+endmodule // SystolicMatMul$celdas$1
+
+
+module SystolicMatMul$celdas$1$a(d,q,clk);
+    
+    // Module arguments
+    input wire signed [3:0] d;
+    output reg signed [3:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 4'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$1$a
+
+
+module SystolicMatMul$celdas$1$b(d,q,clk);
+    
+    // Module arguments
+    input wire signed [3:0] d;
+    output reg signed [3:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 4'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$1$b
+
+
+module SystolicMatMul$celdas$1$desborde(d,q,clk);
+    
+    // Module arguments
+    input wire  d;
+    output reg  q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 1'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$1$desborde
+
+
+module SystolicMatMul$celdas$1$producto(a,b,p,clk);
+    
+    // Module arguments
+    input wire signed [3:0] a;
+    input wire signed [3:0] b;
+    output reg signed [8:0] p;
+    input wire  clk;
+    
+    // Stub signals
+    reg  [3:0] nucleo$a;
+    reg  [3:0] nucleo$b;
+    wire  [8:0] nucleo$resul;
+    
+    // Sub module instances
+    SystolicMatMul$celdas$1$producto$nucleo nucleo(
+        .a(nucleo$a),
+        .b(nucleo$b),
+        .resul(nucleo$resul)
+    );
+    
+    // Update code (custom)
+    always @(*) begin
+        nucleo$a = a;
+        nucleo$b = b;
+        p = nucleo$resul;
+    end
+endmodule // SystolicMatMul$celdas$1$producto
+
+
+module SystolicMatMul$celdas$1$producto$nucleo(a,b,resul);
+    
+    // Module arguments
+    input wire  [3:0] a;
+    input wire  [3:0] b;
+    output reg  [8:0] resul;
+    
+    // Update code (custom)
+    always @(*) resul = $signed(a) * $signed(b);
+endmodule // SystolicMatMul$celdas$1$producto$nucleo
+
+
+module SystolicMatMul$celdas$1$suma(d,q,clk);
+    
+    // Module arguments
+    input wire signed [8:0] d;
+    output reg signed [8:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 9'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$1$suma
+
+
+module SystolicMatMul$celdas$1$sumador(a,b,sum,overflow);
+    
+    // Module arguments
+    input wire  [8:0] a;
+    input wire  [8:0] b;
+    output reg  [8:0] sum;
+    output reg  overflow;
+    
+    // Constant declarations
+    localparam  maximo = 9'b11111111;
+    localparam  minimo = 9'b100000000;
+    localparam  con_signo = 1'b1;
+    localparam  saturar = 1'b0;
+    
+    // Local signals
+    reg  [8:0] suma;
+    
+    // Update code
+    always @(*) begin
+        suma = a + b;
+        sum = suma;
+        overflow = 1'b0;
+        if (con_signo) begin
+            if ((a[8] == b[8]) & (suma[8] != a[8])) begin
+                overflow = 1'b1;
+                if (saturar) begin
+                    if (a[8]) begin
+                        sum = minimo;
+                    end
+                    else begin
+                        sum = maximo;
+                    end
+                end
+            end
+        end
+        else if (suma < a) begin
+            overflow = 1'b1;
+            if (saturar) begin
+                sum = maximo;
+            end
+        end
+    end
+    
+endmodule // SystolicMatMul$celdas$1$sumador
+
+
+module SystolicMatMul$celdas$2(clk,rst,clear,a_in,b_in,a_out,b_out,acc,overflow);
+    
+    // Module arguments
+    input wire  clk;
+    input wire  rst;
+    input wire  clear;
+    input wire signed [3:0] a_in;
+    input wire signed [3:0] b_in;
+    output reg signed [3:0] a_out;
+    output reg signed [3:0] b_out;
+    output reg signed [8:0] acc;
+    output reg  overflow;
+    
+    // Stub signals
+    reg signed [3:0] a$d;
+    wire signed [3:0] a$q;
+    reg  a$clk;
+    reg signed [3:0] b$d;
+    wire signed [3:0] b$q;
+    reg  b$clk;
+    reg signed [8:0] suma$d;
+    wire signed [8:0] suma$q;
+    reg  suma$clk;
+    reg  desborde$d;
+    wire  desborde$q;
+    reg  desborde$clk;
+    reg signed [3:0] producto$a;
+    reg signed [3:0] producto$b;
+    wire signed [8:0] producto$p;
+    reg  producto$clk;
+    reg  [8:0] sumador$a;
+    reg  [8:0] sumador$b;
+    wire  [8:0] sumador$sum;
+    wire  sumador$overflow;
+    
+    // Sub module instances
+    SystolicMatMul$celdas$2$a a(
+        .d(a$d),
+        .q(a$q),
+        .clk(a$clk)
+    );
+    SystolicMatMul$celdas$2$b b(
+        .d(b$d),
+        .q(b$q),
+        .clk(b$clk)
+    );
+    SystolicMatMul$celdas$2$suma suma(
+        .d(suma$d),
+        .q(suma$q),
+        .clk(suma$clk)
+    );
+    SystolicMatMul$celdas$2$desborde desborde(
+        .d(desborde$d),
+        .q(desborde$q),
+        .clk(desborde$clk)
+    );
+    SystolicMatMul$celdas$2$producto producto(
+        .a(producto$a),
+        .b(producto$b),
+        .p(producto$p),
+        .clk(producto$clk)
+    );
+    SystolicMatMul$celdas$2$sumador sumador(
+        .a(sumador$a),
+        .b(sumador$b),
+        .sum(sumador$sum),
+        .overflow(sumador$overflow)
+    );
+    
+    // Update code
+    always @(*) begin
+        a$clk = clk;
+        b$clk = clk;
+        suma$clk = clk;
+        desborde$clk = clk;
+        producto$clk = clk;
+        producto$a = a_in;
+        producto$b = b_in;
+        sumador$a = $unsigned(suma$q);
+        if (clear) begin
+            sumador$a = 32'h0;
+        end
+        sumador$b = $unsigned(producto$p);
+        if (rst) begin
+            a$d = 32'h0;
+            b$d = 32'h0;
+            suma$d = 32'h0;
+            desborde$d = 1'b0;
+        end
+        else begin
+            a$d = a_in;
+            b$d = b_in;
+            suma$d = $signed(sumador$sum);
+            if (clear) begin
+                desborde$d = sumador$overflow;
+            end
+            else begin
+                desborde$d = desborde$q | sumador$overflow;
+            end
+        end
+        a_out = a$q;
+        b_out = b$q;
+        acc = suma$q;
+        overflow = desborde$q;
+    end
+    
+endmodule // SystolicMatMul$celdas$2
+
+
+module SystolicMatMul$celdas$2$a(d,q,clk);
+    
+    // Module arguments
+    input wire signed [3:0] d;
+    output reg signed [3:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 4'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$2$a
+
+
+module SystolicMatMul$celdas$2$b(d,q,clk);
+    
+    // Module arguments
+    input wire signed [3:0] d;
+    output reg signed [3:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 4'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$2$b
+
+
+module SystolicMatMul$celdas$2$desborde(d,q,clk);
+    
+    // Module arguments
+    input wire  d;
+    output reg  q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 1'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$2$desborde
+
+
+module SystolicMatMul$celdas$2$producto(a,b,p,clk);
+    
+    // Module arguments
+    input wire signed [3:0] a;
+    input wire signed [3:0] b;
+    output reg signed [8:0] p;
+    input wire  clk;
+    
+    // Stub signals
+    reg  [3:0] nucleo$a;
+    reg  [3:0] nucleo$b;
+    wire  [8:0] nucleo$resul;
+    
+    // Sub module instances
+    SystolicMatMul$celdas$2$producto$nucleo nucleo(
+        .a(nucleo$a),
+        .b(nucleo$b),
+        .resul(nucleo$resul)
+    );
+    
+    // Update code (custom)
+    always @(*) begin
+        nucleo$a = a;
+        nucleo$b = b;
+        p = nucleo$resul;
+    end
+endmodule // SystolicMatMul$celdas$2$producto
+
+
+module SystolicMatMul$celdas$2$producto$nucleo(a,b,resul);
+    
+    // Module arguments
+    input wire  [3:0] a;
+    input wire  [3:0] b;
+    output reg  [8:0] resul;
+    
+    // Update code (custom)
+    always @(*) resul = $signed(a) * $signed(b);
+endmodule // SystolicMatMul$celdas$2$producto$nucleo
+
+
+module SystolicMatMul$celdas$2$suma(d,q,clk);
+    
+    // Module arguments
+    input wire signed [8:0] d;
+    output reg signed [8:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 9'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$2$suma
+
+
+module SystolicMatMul$celdas$2$sumador(a,b,sum,overflow);
+    
+    // Module arguments
+    input wire  [8:0] a;
+    input wire  [8:0] b;
+    output reg  [8:0] sum;
+    output reg  overflow;
+    
+    // Constant declarations
+    localparam  maximo = 9'b11111111;
+    localparam  minimo = 9'b100000000;
+    localparam  con_signo = 1'b1;
+    localparam  saturar = 1'b0;
+    
+    // Local signals
+    reg  [8:0] suma;
+    
+    // Update code
+    always @(*) begin
+        suma = a + b;
+        sum = suma;
+        overflow = 1'b0;
+        if (con_signo) begin
+            if ((a[8] == b[8]) & (suma[8] != a[8])) begin
+                overflow = 1'b1;
+                if (saturar) begin
+                    if (a[8]) begin
+                        sum = minimo;
+                    end
+                    else begin
+                        sum = maximo;
+                    end
+                end
+            end
+        end
+        else if (suma < a) begin
+            overflow = 1'b1;
+            if (saturar) begin
+                sum = maximo;
+            end
+        end
+    end
+    
+endmodule // SystolicMatMul$celdas$2$sumador
+
+
+module SystolicMatMul$celdas$3(clk,rst,clear,a_in,b_in,a_out,b_out,acc,overflow);
+    
+    // Module arguments
+    input wire  clk;
+    input wire  rst;
+    input wire  clear;
+    input wire signed [3:0] a_in;
+    input wire signed [3:0] b_in;
+    output reg signed [3:0] a_out;
+    output reg signed [3:0] b_out;
+    output reg signed [8:0] acc;
+    output reg  overflow;
+    
+    // Stub signals
+    reg signed [3:0] a$d;
+    wire signed [3:0] a$q;
+    reg  a$clk;
+    reg signed [3:0] b$d;
+    wire signed [3:0] b$q;
+    reg  b$clk;
+    reg signed [8:0] suma$d;
+    wire signed [8:0] suma$q;
+    reg  suma$clk;
+    reg  desborde$d;
+    wire  desborde$q;
+    reg  desborde$clk;
+    reg signed [3:0] producto$a;
+    reg signed [3:0] producto$b;
+    wire signed [8:0] producto$p;
+    reg  producto$clk;
+    reg  [8:0] sumador$a;
+    reg  [8:0] sumador$b;
+    wire  [8:0] sumador$sum;
+    wire  sumador$overflow;
+    
+    // Sub module instances
+    SystolicMatMul$celdas$3$a a(
+        .d(a$d),
+        .q(a$q),
+        .clk(a$clk)
+    );
+    SystolicMatMul$celdas$3$b b(
+        .d(b$d),
+        .q(b$q),
+        .clk(b$clk)
+    );
+    SystolicMatMul$celdas$3$suma suma(
+        .d(suma$d),
+        .q(suma$q),
+        .clk(suma$clk)
+    );
+    SystolicMatMul$celdas$3$desborde desborde(
+        .d(desborde$d),
+        .q(desborde$q),
+        .clk(desborde$clk)
+    );
+    SystolicMatMul$celdas$3$producto producto(
+        .a(producto$a),
+        .b(producto$b),
+        .p(producto$p),
+        .clk(producto$clk)
+    );
+    SystolicMatMul$celdas$3$sumador sumador(
+        .a(sumador$a),
+        .b(sumador$b),
+        .sum(sumador$sum),
+        .overflow(sumador$overflow)
+    );
+    
+    // Update code
+    always @(*) begin
+        a$clk = clk;
+        b$clk = clk;
+        suma$clk = clk;
+        desborde$clk = clk;
+        producto$clk = clk;
+        producto$a = a_in;
+        producto$b = b_in;
+        sumador$a = $unsigned(suma$q);
+        if (clear) begin
+            sumador$a = 32'h0;
+        end
+        sumador$b = $unsigned(producto$p);
+        if (rst) begin
+            a$d = 32'h0;
+            b$d = 32'h0;
+            suma$d = 32'h0;
+            desborde$d = 1'b0;
+        end
+        else begin
+            a$d = a_in;
+            b$d = b_in;
+            suma$d = $signed(sumador$sum);
+            if (clear) begin
+                desborde$d = sumador$overflow;
+            end
+            else begin
+                desborde$d = desborde$q | sumador$overflow;
+            end
+        end
+        a_out = a$q;
+        b_out = b$q;
+        acc = suma$q;
+        overflow = desborde$q;
+    end
+    
+endmodule // SystolicMatMul$celdas$3
+
+
+module SystolicMatMul$celdas$3$a(d,q,clk);
+    
+    // Module arguments
+    input wire signed [3:0] d;
+    output reg signed [3:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 4'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$3$a
+
+
+module SystolicMatMul$celdas$3$b(d,q,clk);
+    
+    // Module arguments
+    input wire signed [3:0] d;
+    output reg signed [3:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 4'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$3$b
+
+
+module SystolicMatMul$celdas$3$desborde(d,q,clk);
+    
+    // Module arguments
+    input wire  d;
+    output reg  q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 1'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$3$desborde
+
+
+module SystolicMatMul$celdas$3$producto(a,b,p,clk);
+    
+    // Module arguments
+    input wire signed [3:0] a;
+    input wire signed [3:0] b;
+    output reg signed [8:0] p;
+    input wire  clk;
+    
+    // Stub signals
+    reg  [3:0] nucleo$a;
+    reg  [3:0] nucleo$b;
+    wire  [8:0] nucleo$resul;
+    
+    // Sub module instances
+    SystolicMatMul$celdas$3$producto$nucleo nucleo(
+        .a(nucleo$a),
+        .b(nucleo$b),
+        .resul(nucleo$resul)
+    );
+    
+    // Update code (custom)
+    always @(*) begin
+        nucleo$a = a;
+        nucleo$b = b;
+        p = nucleo$resul;
+    end
+endmodule // SystolicMatMul$celdas$3$producto
+
+
+module SystolicMatMul$celdas$3$producto$nucleo(a,b,resul);
+    
+    // Module arguments
+    input wire  [3:0] a;
+    input wire  [3:0] b;
+    output reg  [8:0] resul;
+    
+    // Update code (custom)
+    always @(*) resul = $signed(a) * $signed(b);
+endmodule // SystolicMatMul$celdas$3$producto$nucleo
+
+
+module SystolicMatMul$celdas$3$suma(d,q,clk);
+    
+    // Module arguments
+    input wire signed [8:0] d;
+    output reg signed [8:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 9'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$celdas$3$suma
+
+
+module SystolicMatMul$celdas$3$sumador(a,b,sum,overflow);
+    
+    // Module arguments
+    input wire  [8:0] a;
+    input wire  [8:0] b;
+    output reg  [8:0] sum;
+    output reg  overflow;
+    
+    // Constant declarations
+    localparam  maximo = 9'b11111111;
+    localparam  minimo = 9'b100000000;
+    localparam  con_signo = 1'b1;
+    localparam  saturar = 1'b0;
+    
+    // Local signals
+    reg  [8:0] suma;
+    
+    // Update code
+    always @(*) begin
+        suma = a + b;
+        sum = suma;
+        overflow = 1'b0;
+        if (con_signo) begin
+            if ((a[8] == b[8]) & (suma[8] != a[8])) begin
+                overflow = 1'b1;
+                if (saturar) begin
+                    if (a[8]) begin
+                        sum = minimo;
+                    end
+                    else begin
+                        sum = maximo;
+                    end
+                end
+            end
+        end
+        else if (suma < a) begin
+            overflow = 1'b1;
+            if (saturar) begin
+                sum = maximo;
+            end
+        end
+    end
+    
+endmodule // SystolicMatMul$celdas$3$sumador
+
+
+module SystolicMatMul$paso(d,q,clk);
+    
+    // Module arguments
+    input wire  [15:0] d;
+    output reg  [15:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 16'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$paso
+
+
+module SystolicMatMul$retardo_a$0(d,q,clk);
+    
+    // Module arguments
+    input wire signed [3:0] d;
+    output reg signed [3:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 4'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$retardo_a$0
+
+
+module SystolicMatMul$retardo_b$0(d,q,clk);
+    
+    // Module arguments
+    input wire signed [3:0] d;
+    output reg signed [3:0] q;
+    input wire  clk;
+    
+    // Update code (custom)
+    initial begin
+       q = 4'h0;
+    end
+    
+    always @(posedge clk) q <= d;
+endmodule // SystolicMatMul$retardo_b$0
@@ -1,3 +1,5 @@
+// Módulo productoEscalar generado con RustHDL
+// Crate: producto_escalar v0.1.0
 
 
 module productoEscalar(clock,reset,start,a,b,valid,result,busy);
@@ -30,22 +32,22 @@ module productoEscalar(clock,reset,start,a,b,valid,result,busy);
     reg  [7:0] max_count;
     
     // Sub module instances
-    top$accumulator accumulator(
+    productoEscalar$accumulator accumulator(
         .d(accumulator$d),
         .q(accumulator$q),
         .clk(accumulator$clk)
     );
-    top$counter counter(
+    productoEscalar$counter counter(
         .d(counter$d),
         .q(counter$q),
         .clk(counter$clk)
     );
-    top$e_busy e_busy(
+    productoEscalar$e_busy e_busy(
         .d(e_busy$d),
         .q(e_busy$q),
         .clk(e_busy$clk)
     );
-    top$mul mul(
+    productoEscalar$mul mul(
         .a(mul$a),
         .b(mul$b),
         .resul(mul$resul)
@@ -90,10 +92,10 @@ module productoEscalar(clock,reset,start,a,b,valid,result,busy);
         end
     end
     
-endmodule // top
+endmodule // productoEscalar
 
 
-module top$accumulator(d,q,clk);
+module productoEscalar$accumulator(d,q,clk);
     
     // Module arguments
     input wire  [15:0] d;
@@ -106,10 +108,10 @@ module top$accumulator(d,q,clk);
     end
     
     always @(posedge clk) q <= d;
-endmodule // top$accumulator
+endmodule // productoEscalar$accumulator
 
 
-module top$counter(d,q,clk);
+module productoEscalar$counter(d,q,clk);
     
     // Module arguments
     input wire  [7:0] d;
@@ -122,10 +124,10 @@ module top$counter(d,q,clk);
     end
     
     always @(posedge clk) q <= d;
-endmodule // top$counter
+endmodule // productoEscalar$counter
 
 
-module top$e_busy(d,q,clk);
+module productoEscalar$e_busy(d,q,clk);
     
     // Module arguments
     input wire  d;
@@ -138,43 +140,67 @@ module top$e_busy(d,q,clk);
     end
     
     always @(posedge clk) q <= d;
-endmodule // top$e_busy
+endmodule // productoEscalar$e_busy
 
 
-module top$mul(a,b,resul);
+module productoEscalar$mul(a,b,resul);
     
     // Module arguments
     input wire  [7:0] a;
     input wire  [7:0] b;
     output reg  [15:0] resul;
     
+    // Constant declarations
+    localparam  posiciones$0 = 16'h0;
+    localparam  posiciones$1 = 16'h1;
+    localparam  posiciones$2 = 16'h2;
+    localparam  posiciones$3 = 16'h3;
+    localparam  posiciones$4 = 16'h4;
+    localparam  posiciones$5 = 16'h5;
+    localparam  posiciones$6 = 16'h6;
+    localparam  posiciones$7 = 16'h7;
+    localparam  extension = 16'hff00;
+    localparam  con_signo = 1'b0;
+    
+    // Local signals
+    reg  [15:0] extendido;
+    
     // Update code
     always @(*) begin
+        extendido = ((a) & 16'hffff);
+        if (con_signo & a[7]) begin
+            extendido = ((a) & 16'hffff) | extension;
+        end
         resul = 32'h0;
-        if (b[32'h0]) begin
-            resul = resul + ((a) & 16'hffff);
+        if (b[0]) begin
+            resul = resul + (extendido << posiciones$0);
         end
-        if (b[32'h1]) begin
-            resul = resul + (((a) & 16'hffff) << 32'h1);
+        if (b[1]) begin
+            resul = resul + (extendido << posiciones$1);
         end
-        if (b[32'h2]) begin
-            resul = resul + (((a) & 16'hffff) << 32'h2);
+        if (b[2]) begin
+            resul = resul + (extendido << posiciones$2);
         end
-        if (b[32'h3]) begin
-            resul = resul + (((a) & 16'hffff) << 32'h3);
+        if (b[3]) begin
+            resul = resul + (extendido << posiciones$3);
         end
-        if (b[32'h4]) begin
-            resul = resul + (((a) & 16'hffff) << 32'h4);
+        if (b[4]) begin
+            resul = resul + (extendido << posiciones$4);
         end
-        if (b[32'h5]) begin
-            resul = resul + (((a) & 16'hffff) << 32'h5);
+        if (b[5]) begin
+            resul = resul + (extendido << posiciones$5);
         end
-        if (b[32'h6]) begin
-            resul = resul + (((a) & 16'hffff) << 32'h6);
+        if (b[6]) begin
+            resul = resul + (extendido << posiciones$6);
         end
-        if (b[32'h7]) begin
-            resul = resul + (((a) & 16'hffff) << 32'h7);
+        if (b[7]) begin
+            if (con_signo) begin
+                resul = resul - (extendido << posiciones$7);
+            end
+            else begin
+                resul = resul + (extendido << posiciones$7);
+            end
         end
     end
     
-endmodule // top$mul
+endmodule // productoEscalar$mul
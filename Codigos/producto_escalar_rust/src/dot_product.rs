@@ -0,0 +1,609 @@
+use rust_hdl::prelude::*;
+
+// Flip-Flop común a todos los diseños
+use tfg_hdl_common::EdgeDFF;
+
+use crate::{MulAlgorithm, ShiftAdd, Signedness, Unsigned};
+use tfg_hdl_common::{Multiplier, OverflowAdder, OverflowMode, Wrap};
+
+// Producto escalar de vectores de hasta L elementos de W bits, sin signo o,
+// con S = TwosComplement, en complemento a 2 (a, b y result). El
+// multiplicador es el de sumas y desplazamientos de productoEscalar, salvo
+// que A elija otro algoritmo (Booth o Wallace).
+//
+// Funciona como productoEscalar: con start a 1 estando parado empieza un
+// cálculo, y en cada ciclo con valid a 1 se multiplican a y b y se suma el
+// producto al acumulador. La diferencia es la longitud, que no está fija: en
+// el flanco de start se guarda el puerto `length` (de 1 a L; con 0 se usa L,
+// así que dejándolo a 0 el bloque es de longitud fija, y con más de L también
+// se usa L, porque el vector no cabe en el acumulador). En el flanco que
+// acepta el último par, result toma la suma completa, busy baja y done se
+// pone a 1 durante un ciclo. result se mantiene hasta el siguiente cálculo.
+//
+// El acumulador tiene como mucho O = 2W + ceil(log2 L) bits, lo justo para
+// que no desborde con L productos máximos (con signo el peor producto es
+// (-2^(W-1))², que también cabe en 2W bits). Con menos (al menos 2W, para
+// que quepa un producto) la suma puede no caber: overflow se pone a 1 con la
+// primera suma que desborda y se mantiene hasta el siguiente start, y con
+// M = Saturate el acumulador se queda en el máximo o el mínimo en vez de dar
+// la vuelta. La anchura se comprueba al construir.
+//
+// Con PIPELINED = true (PipelinedDotProduct) el MAC va segmentado: el
+// producto se guarda en un registro y se suma al acumulador en el ciclo
+// siguiente, así que el camino crítico es el multiplicador o el sumador, no
+// los dos seguidos. Se sigue aceptando un par por ciclo, pero el último
+// tarda un ciclo más en llegar al acumulador: result, busy y done cambian en
+// el flanco siguiente al que acepta el último par, y en ese ciclo de vaciado
+// los datos de la entrada no cuentan.
+//
+// El multiplicador tiene que ser combinacional (A::LATENCY = 0), porque el
+// control cuenta con el producto en el mismo ciclo; para registrarlo está
+// PIPELINED. Se comprueba al construir.
+#[derive(LogicBlock, Clone)]
+pub struct DotProduct<
+    const L: usize,
+    const W: usize,
+    const O: usize,
+    S: Signedness = Unsigned,
+    A: MulAlgorithm = ShiftAdd,
+    const PIPELINED: bool = false,
+    M: OverflowMode = Wrap,
+> {
+    pub clock: Signal<In, Clock>,
+    pub reset: Signal<In, Bit>,
+    pub start: Signal<In, Bit>,
+    pub length: Signal<In, Bits<16>>,
+    pub a: Signal<In, Bits<W>>,
+    pub b: Signal<In, Bits<W>>,
+    pub valid: Signal<In, Bit>,
+    pub result: Signal<Out, Bits<O>>,
+    pub busy: Signal<Out, Bit>,
+    pub done: Signal<Out, Bit>,
+    pub overflow: Signal<Out, Bit>,
+    accumulator: EdgeDFF<Bits<O>>,
+    resultado: EdgeDFF<Bits<O>>,
+    counter: EdgeDFF<Bits<16>>,
+    // Índice del último par: length - 1, o L - 1 con length a 0 o mayor que L
+    max_count: EdgeDFF<Bits<16>>,
+    e_busy: EdgeDFF<Bit>,
+    e_done: EdgeDFF<Bit>,
+    e_overflow: EdgeDFF<Bit>,
+    // Se aceptan pares: de start al último par (busy sigue a 1 mientras se
+    // vacía la segmentación)
+    e_carga: EdgeDFF<Bit>,
+    // Etapa del producto de la segmentación, con el par del que viene
+    producto: EdgeDFF<Bits<O>>,
+    producto_valido: EdgeDFF<Bit>,
+    producto_ultimo: EdgeDFF<Bit>,
+    acepta: Signal<Local, Bit>,
+    ultimo: Signal<Local, Bit>,
+    // Lo que llega al acumulador en este ciclo
+    sumando: Signal<Local, Bits<O>>,
+    suma_valida: Signal<Local, Bit>,
+    suma_ultima: Signal<Local, Bit>,
+    suma: Signal<Local, Bits<O>>,
+    ultimo_por_defecto: Constant<Bits<16>>,
+    segmentado: Constant<Bit>,
+    mul: Multiplier<W, O, S, A>,
+    sumador: OverflowAdder<O, S, M>,
+}
+
+// DotProduct con el MAC segmentado
+pub type PipelinedDotProduct<const L: usize, const W: usize, const O: usize, S = Unsigned, A = ShiftAdd, M = Wrap> =
+    DotProduct<L, W, O, S, A, true, M>;
+
+// Anchura del acumulador para L productos de W x W bits, con o sin signo
+pub const fn dot_product_width(w: usize, l: usize) -> usize {
+    2 * w + l.next_power_of_two().trailing_zeros() as usize
+}
+
+impl<
+        const L: usize,
+        const W: usize,
+        const O: usize,
+        S: Signedness,
+        A: MulAlgorithm,
+        const PIPELINED: bool,
+        M: OverflowMode,
+    > DotProduct<L, W, O, S, A, PIPELINED, M>
+{
+    // Ciclos de más que tarda done en subir tras el flanco que acepta el
+    // último par
+    pub const DRAIN_CYCLES: usize = PIPELINED as usize;
+}
+
+impl<
+        const L: usize,
+        const W: usize,
+        const O: usize,
+        S: Signedness,
+        A: MulAlgorithm,
+        const PIPELINED: bool,
+        M: OverflowMode,
+    > Default for DotProduct<L, W, O, S, A, PIPELINED, M>
+{
+    fn default() -> Self {
+        const {
+            assert!(L >= 1 && L < 1 << 16, "L fuera de rango");
+            assert!(
+                O >= 2 * W && O <= dot_product_width(W, L),
+                "O tiene que estar entre 2W y 2W + ceil(log2 L)"
+            );
+            assert!(
+                A::LATENCY == 0,
+                "el multiplicador tiene que ser combinacional; para registrar el producto está PIPELINED"
+            );
+        }
+        Self {
+            clock: Default::default(),
+            reset: Default::default(),
+            start: Default::default(),
+            length: Default::default(),
+            a: Default::default(),
+            b: Default::default(),
+            valid: Default::default(),
+            result: Default::default(),
+            busy: Default::default(),
+            done: Default::default(),
+            overflow: Default::default(),
+            accumulator: EdgeDFF::new(0.into()),
+            resultado: EdgeDFF::new(0.into()),
+            counter: EdgeDFF::new(0.into()),
+            max_count: EdgeDFF::new(0.into()),
+            e_busy: EdgeDFF::new(false),
+            e_done: EdgeDFF::new(false),
+            e_overflow: EdgeDFF::new(false),
+            e_carga: EdgeDFF::new(false),
+            producto: EdgeDFF::new(0.into()),
+            producto_valido: EdgeDFF::new(false),
+            producto_ultimo: EdgeDFF::new(false),
+            acepta: Default::default(),
+            ultimo: Default::default(),
+            sumando: Default::default(),
+            suma_valida: Default::default(),
+            suma_ultima: Default::default(),
+            suma: Default::default(),
+            ultimo_por_defecto: Constant::new(((L - 1) as u64).into()),
+            segmentado: Constant::new(PIPELINED),
+            mul: Default::default(),
+            sumador: Default::default(),
+        }
+    }
+}
+
+impl<
+        const L: usize,
+        const W: usize,
+        const O: usize,
+        S: Signedness,
+        A: MulAlgorithm,
+        const PIPELINED: bool,
+        M: OverflowMode,
+    > Logic for DotProduct<L, W, O, S, A, PIPELINED, M>
+{
+    #[hdl_gen]
+    fn update(&mut self) {
+        // Enlazamos el reloj
+        self.accumulator.clk.next = self.clock.val();
+        self.resultado.clk.next = self.clock.val();
+        self.counter.clk.next = self.clock.val();
+        self.max_count.clk.next = self.clock.val();
+        self.e_busy.clk.next = self.clock.val();
+        self.e_done.clk.next = self.clock.val();
+        self.e_overflow.clk.next = self.clock.val();
+        self.e_carga.clk.next = self.clock.val();
+        self.producto.clk.next = self.clock.val();
+        self.producto_valido.clk.next = self.clock.val();
+        self.producto_ultimo.clk.next = self.clock.val();
+        self.mul.clk.next = self.clock.val();
+
+        // Por defecto los registros se mantienen
+        self.accumulator.d.next = self.accumulator.q.val();
+        self.resultado.d.next = self.resultado.q.val();
+        self.counter.d.next = self.counter.q.val();
+        self.max_count.d.next = self.max_count.q.val();
+        self.e_busy.d.next = self.e_busy.q.val();
+        self.e_done.d.next = false;
+        self.e_overflow.d.next = self.e_overflow.q.val();
+        self.e_carga.d.next = self.e_carga.q.val();
+
+        self.mul.a.next = self.a.val();
+        self.mul.b.next = self.b.val();
+
+        // Par aceptado en este ciclo, y si es el último del vector
+        self.acepta.next = self.e_carga.q.val() & self.valid.val();
+        self.ultimo.next = false;
+        if self.acepta.val() & (self.counter.q.val() == self.max_count.q.val()) {
+            self.ultimo.next = true;
+        }
+
+        // Etapa del producto: el del par de este ciclo queda registrado
+        self.producto.d.next = self.mul.resul.val();
+        self.producto_valido.d.next = self.acepta.val();
+        self.producto_ultimo.d.next = self.ultimo.val();
+
+        // Al acumulador llega el producto del par de este ciclo o, con la
+        // segmentación, el registrado en el ciclo anterior
+        if self.segmentado.val() {
+            self.sumando.next = self.producto.q.val();
+            self.suma_valida.next = self.producto_valido.q.val();
+            self.suma_ultima.next = self.producto_ultimo.q.val();
+        } else {
+            self.sumando.next = self.mul.resul.val();
+            self.suma_valida.next = self.acepta.val();
+            self.suma_ultima.next = self.ultimo.val();
+        }
+        self.sumador.a.next = self.accumulator.q.val();
+        self.sumador.b.next = self.sumando.val();
+        self.suma.next = self.sumador.sum.val();
+
+        if self.reset.val() {
+            self.accumulator.d.next = 0.into();
+            self.resultado.d.next = 0.into();
+            self.counter.d.next = 0.into();
+            self.max_count.d.next = 0.into();
+            self.e_busy.d.next = false;
+            self.e_overflow.d.next = false;
+            self.e_carga.d.next = false;
+            self.producto_valido.d.next = false;
+            self.producto_ultimo.d.next = false;
+        } else if !self.e_busy.q.val() {
+            if self.start.val() {
+                self.e_busy.d.next = true;
+                self.e_carga.d.next = true;
+                self.e_overflow.d.next = false;
+                self.counter.d.next = 0.into();
+                self.accumulator.d.next = 0.into();
+                if (self.length.val() == 0) | (self.length.val() > self.ultimo_por_defecto.val()) {
+                    self.max_count.d.next = self.ultimo_por_defecto.val();
+                } else {
+                    self.max_count.d.next = self.length.val() - 1;
+                }
+            }
+        } else {
+            if self.acepta.val() {
+                self.counter.d.next = self.counter.q.val() + 1;
+                if self.ultimo.val() {
+                    self.e_carga.d.next = false;
+                }
+            }
+            if self.suma_valida.val() {
+                self.accumulator.d.next = self.suma.val();
+                self.e_overflow.d.next = self.e_overflow.q.val() | self.sumador.overflow.val();
+                if self.suma_ultima.val() {
+                    self.resultado.d.next = self.suma.val();
+                    self.e_busy.d.next = false;
+                    self.e_done.d.next = true;
+                }
+            }
+        }
+
+        self.result.next = self.resultado.q.val();
+        self.busy.next = self.e_busy.q.val();
+        self.done.next = self.e_done.q.val();
+        self.overflow.next = self.e_overflow.q.val();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Booth, TwosComplement, Wallace};
+    use tfg_hdl_common::{overflow_add, Input, Output, Saturate, TestVector, TestVectors};
+
+    type Producto<const L: usize, const O: usize, S, A = ShiftAdd, const P: bool = false, M = Wrap> =
+        DotProduct<L, 8, O, S, A, P, M>;
+
+    struct Puertos {
+        reset: Input,
+        start: Input,
+        length: Input,
+        a: Input,
+        b: Input,
+        valid: Input,
+        result: Output,
+        busy: Output,
+        done: Output,
+        overflow: Output,
+    }
+
+    fn banco<const L: usize, const O: usize, S: Signedness, A: MulAlgorithm, const P: bool, M: OverflowMode>(
+        modulo: &str,
+    ) -> (TestVectors<Producto<L, O, S, A, P, M>>, Puertos) {
+        let mut tb = TestVectors::<Producto<L, O, S, A, P, M>>::new(modulo).clock("clock", |x| &mut x.clock);
+        let p = Puertos {
+            reset: tb.input("reset", |x| &mut x.reset),
+            start: tb.input("start", |x| &mut x.start),
+            length: tb.input("length", |x| &mut x.length),
+            a: tb.input("a", |x| &mut x.a),
+            b: tb.input("b", |x| &mut x.b),
+            valid: tb.input("valid", |x| &mut x.valid),
+            result: tb.output("result", |x| &x.result),
+            busy: tb.output("busy", |x| &x.busy),
+            done: tb.output("done", |x| &x.done),
+            overflow: tb.output("overflow", |x| &x.overflow),
+        };
+        tb.push(TestVector::new("reset").set(p.reset, 1).set(p.start, 0).set(p.valid, 0));
+        (tb, p)
+    }
+
+    // Un cálculo completo con `length` en el puerto (los pares a usar son
+    // los de `vector`). Cada `huecos` pares hay un ciclo con valid a 0, y
+    // después del último se comprueban result, busy y done. Los negativos
+    // van a los puertos en complemento a 2
+    fn calcular<const L: usize, const O: usize, S: Signedness, A: MulAlgorithm, const P: bool, M: OverflowMode>(
+        tb: &mut TestVectors<Producto<L, O, S, A, P, M>>,
+        p: &Puertos,
+        length: usize,
+        vector: &[(i64, i64)],
+        huecos: usize,
+    ) {
+        let nombre = format!("longitud {}", vector.len());
+        // La suma como la hace el acumulador, y si se ha desbordado
+        let (esperado, desborda) = vector.iter().fold((0, false), |(suma, desborda), &(x, y)| {
+            let (suma, ahora) = overflow_add::<S, M>(O, suma, x * y);
+            (suma, desborda | ahora)
+        });
+        tb.push(
+            TestVector::new(&nombre)
+                .set(p.reset, 0)
+                .set(p.start, 1)
+                .set(p.length, length as i64)
+                .expect(p.busy, 1),
+        );
+        for (i, &(va, vb)) in vector.iter().enumerate() {
+            if huecos != 0 && i % huecos == huecos - 1 {
+                tb.push(
+                    TestVector::new(&nombre)
+                        .set(p.start, 0)
+                        .set(p.valid, 0)
+                        .expect(p.busy, 1),
+                );
+            }
+            let mut v = TestVector::new(&format!("{nombre}, par {i}"))
+                .set(p.start, 0)
+                .set(p.valid, 1)
+                .set(p.a, va & 0xff)
+                .set(p.b, vb & 0xff);
+            if i + 1 == vector.len() && !P {
+                v = v
+                    .expect(p.result, esperado & ((1 << O) - 1))
+                    .expect(p.busy, 0)
+                    .expect(p.done, 1)
+                    .expect(p.overflow, desborda as i64);
+            } else {
+                v = v.expect(p.busy, 1).expect(p.done, 0);
+            }
+            tb.push(v);
+        }
+        // Con la segmentación el resultado sale un ciclo después, y el par
+        // que llegue mientras tanto no cuenta
+        if P {
+            tb.push(
+                TestVector::new(&format!("{nombre}, vaciado"))
+                    .set(p.valid, 1)
+                    .set(p.a, 0xff)
+                    .set(p.b, 0x7f)
+                    .expect(p.result, esperado & ((1 << O) - 1))
+                    .expect(p.busy, 0)
+                    .expect(p.done, 1)
+                    .expect(p.overflow, desborda as i64),
+            );
+        }
+        // Sin start los datos que sigan no cuentan
+        tb.push(
+            TestVector::new(&nombre)
+                .set(p.valid, 1)
+                .set(p.a, 1)
+                .set(p.b, 1)
+                .expect(p.busy, 0)
+                .expect(p.done, 0),
+        );
+        tb.push(TestVector::new(&nombre).set(p.valid, 0));
+    }
+
+    fn comprobar<const L: usize, const O: usize, S: Signedness, A: MulAlgorithm, const P: bool, M: OverflowMode>(
+        tb: TestVectors<Producto<L, O, S, A, P, M>>,
+    ) -> anyhow::Result<()> {
+        let mut uut = Producto::<L, O, S, A, P, M>::default();
+        uut.connect_all();
+
+        tb.verify(&uut, 1_000_000)?;
+        Ok(())
+    }
+
+    fn pares(n: usize, semilla: i64) -> Vec<(i64, i64)> {
+        (0..n as i64)
+            .map(|i| ((i * 37 + semilla) % 256, (i * 91 + 3 * semilla) % 256))
+            .collect()
+    }
+
+    #[test]
+    fn test_anchura_del_acumulador() {
+        assert_eq!(dot_product_width(8, 4), 18);
+        assert_eq!(dot_product_width(8, 16), 20);
+        assert_eq!(dot_product_width(8, 64), 22);
+        assert_eq!(dot_product_width(8, 256), 24);
+        assert_eq!(dot_product_width(8, 1), 16);
+    }
+
+    // Los 4 pares del test de productoEscalar y el peor caso con 16
+    #[test]
+    fn test_longitud_fija_16() -> anyhow::Result<()> {
+        let (mut tb, p) = banco::<16, 20, Unsigned, ShiftAdd, false, Wrap>("DotProduct16");
+        calcular(&mut tb, &p, 4, &[(3, 4), (5, 6), (7, 8), (9, 10)], 0);
+        calcular(&mut tb, &p, 0, &[(255, 255); 16], 0);
+        calcular(&mut tb, &p, 0, &pares(16, 1), 3);
+        calcular(&mut tb, &p, 1, &[(200, 100)], 0);
+        comprobar(tb)
+    }
+
+    // Las longitudes que usamos, con el mismo bloque de hasta 256 elementos
+    #[test]
+    fn test_longitud_en_ejecucion_hasta_256() -> anyhow::Result<()> {
+        let (mut tb, p) = banco::<256, 24, Unsigned, ShiftAdd, false, Wrap>("DotProduct256");
+        calcular(&mut tb, &p, 16, &pares(16, 2), 0);
+        calcular(&mut tb, &p, 64, &pares(64, 5), 7);
+        calcular(&mut tb, &p, 0, &[(255, 255); 256], 0);
+        calcular(&mut tb, &p, 256, &pares(256, 9), 0);
+        calcular(&mut tb, &p, 3, &pares(3, 4), 1);
+        comprobar(tb)
+    }
+
+    // Con length mayor que L se usan L pares, como con 0: los que lleguen
+    // después no cuentan
+    #[test]
+    fn test_longitud_mayor_que_l() -> anyhow::Result<()> {
+        let (mut tb, p) = banco::<16, 20, Unsigned, ShiftAdd, false, Wrap>("DotProduct16Clamp");
+        calcular(&mut tb, &p, 17, &pares(16, 4), 0);
+        calcular(&mut tb, &p, 0xffff, &[(255, 255); 16], 2);
+        calcular(&mut tb, &p, 16, &pares(16, 6), 0);
+        comprobar(tb)?;
+
+        let (mut tb, p) = banco::<4, 18, Unsigned, ShiftAdd, true, Wrap>("PipelinedDotProduct4Clamp");
+        calcular(&mut tb, &p, 5, &pares(4, 7), 0);
+        calcular(&mut tb, &p, 1000, &pares(4, 8), 1);
+        comprobar(tb)
+    }
+
+    // Vectores con negativos, los extremos (-128 · -128 y -128 · 127) y un
+    // resultado final negativo
+    #[test]
+    fn test_con_signo() -> anyhow::Result<()> {
+        let (mut tb, p) = banco::<16, 20, TwosComplement, ShiftAdd, false, Wrap>("DotProduct16Signed");
+        calcular(&mut tb, &p, 4, &[(-3, 4), (5, -6), (-7, -8), (9, 10)], 0);
+        calcular(&mut tb, &p, 0, &[(-128, -128); 16], 0);
+        calcular(&mut tb, &p, 0, &[(-128, 127); 16], 2);
+        let mezcla: Vec<_> = pares(16, 3).into_iter().map(|(x, y)| (x - 128, y - 128)).collect();
+        calcular(&mut tb, &p, 16, &mezcla, 0);
+        calcular(&mut tb, &p, 2, &[(1, -1), (0, 5)], 0);
+        comprobar(tb)
+    }
+
+    // Los mismos cálculos con los otros multiplicadores
+    fn con_algoritmo<A: MulAlgorithm>(modulo: &str) -> anyhow::Result<()> {
+        let (mut tb, p) = banco::<16, 20, Unsigned, A, false, Wrap>(modulo);
+        calcular(&mut tb, &p, 4, &[(3, 4), (5, 6), (7, 8), (9, 10)], 0);
+        calcular(&mut tb, &p, 0, &[(255, 255); 16], 0);
+        calcular(&mut tb, &p, 0, &pares(16, 1), 3);
+        comprobar(tb)?;
+
+        let (mut tb, p) = banco::<16, 20, TwosComplement, A, false, Wrap>(&format!("{}Signed", modulo));
+        calcular(&mut tb, &p, 4, &[(-3, 4), (5, -6), (-7, -8), (9, 10)], 0);
+        calcular(&mut tb, &p, 0, &[(-128, -128); 16], 0);
+        calcular(&mut tb, &p, 0, &[(-128, 127); 16], 2);
+        comprobar(tb)
+    }
+
+    #[test]
+    fn test_booth_y_wallace() -> anyhow::Result<()> {
+        con_algoritmo::<Booth>("DotProduct16Booth")?;
+        con_algoritmo::<Wallace>("DotProduct16Wallace")
+    }
+
+    // Los mismos casos con el MAC segmentado, sin y con signo y con los
+    // otros multiplicadores
+    #[test]
+    fn test_segmentado() -> anyhow::Result<()> {
+        assert_eq!(Producto::<16, 20, Unsigned>::DRAIN_CYCLES, 0);
+        assert_eq!(Producto::<16, 20, Unsigned, ShiftAdd, true>::DRAIN_CYCLES, 1);
+
+        let (mut tb, p) = banco::<16, 20, Unsigned, ShiftAdd, true, Wrap>("PipelinedDotProduct16");
+        calcular(&mut tb, &p, 4, &[(3, 4), (5, 6), (7, 8), (9, 10)], 0);
+        calcular(&mut tb, &p, 0, &[(255, 255); 16], 0);
+        calcular(&mut tb, &p, 0, &pares(16, 1), 3);
+        calcular(&mut tb, &p, 1, &[(200, 100)], 0);
+        calcular(&mut tb, &p, 2, &[(7, 9), (11, 13)], 1);
+        comprobar(tb)?;
+
+        let (mut tb, p) = banco::<16, 20, TwosComplement, Booth, true, Wrap>("PipelinedDotProduct16Booth");
+        calcular(&mut tb, &p, 4, &[(-3, 4), (5, -6), (-7, -8), (9, 10)], 0);
+        calcular(&mut tb, &p, 0, &[(-128, -128); 16], 0);
+        calcular(&mut tb, &p, 1, &[(-128, 127)], 0);
+        comprobar(tb)?;
+
+        let (mut tb, p) = banco::<256, 24, Unsigned, Wallace, true, Wrap>("PipelinedDotProduct256Wallace");
+        calcular(&mut tb, &p, 0, &[(255, 255); 256], 0);
+        calcular(&mut tb, &p, 64, &pares(64, 5), 7);
+        comprobar(tb)
+    }
+
+    // Acumuladores de 16 bits para vectores de hasta 16 elementos de 8 bits,
+    // que se desbordan a propósito, dando la vuelta y saturando. Después de
+    // cada desbordamiento hay un cálculo que cabe, para ver que start borra
+    // overflow
+    #[test]
+    fn test_desbordamiento() -> anyhow::Result<()> {
+        let (mut tb, p) = banco::<16, 16, Unsigned, ShiftAdd, false, Wrap>("DotProduct16Wrap");
+        calcular(&mut tb, &p, 0, &[(255, 255); 16], 0);
+        calcular(&mut tb, &p, 4, &[(3, 4), (5, 6), (7, 8), (9, 10)], 0);
+        calcular(&mut tb, &p, 2, &[(255, 255), (255, 255)], 0);
+        calcular(&mut tb, &p, 1, &[(255, 255)], 0);
+        comprobar(tb)?;
+
+        let (mut tb, p) = banco::<16, 16, Unsigned, ShiftAdd, false, Saturate>("DotProduct16Saturate");
+        calcular(&mut tb, &p, 0, &[(255, 255); 16], 1);
+        calcular(&mut tb, &p, 4, &[(3, 4), (5, 6), (7, 8), (9, 10)], 0);
+        calcular(&mut tb, &p, 3, &[(200, 200), (200, 200), (1, 1)], 0);
+        comprobar(tb)?;
+
+        // Con signo se satura por arriba y por abajo, y después de saturar
+        // la suma puede volver a bajar (overflow se queda a 1)
+        let (mut tb, p) = banco::<16, 16, TwosComplement, ShiftAdd, false, Saturate>("DotProduct16SignedSaturate");
+        calcular(&mut tb, &p, 2, &[(-128, -128), (-128, -128)], 0);
+        calcular(&mut tb, &p, 3, &[(-128, 127); 3], 0);
+        calcular(&mut tb, &p, 4, &[(127, 127), (127, 127), (127, 127), (-128, 127)], 0);
+        calcular(&mut tb, &p, 2, &[(-128, 127), (-128, -128)], 0);
+        comprobar(tb)?;
+
+        let (mut tb, p) = banco::<16, 16, TwosComplement, ShiftAdd, false, Wrap>("DotProduct16SignedWrap");
+        calcular(&mut tb, &p, 2, &[(-128, -128), (-128, -128)], 0);
+        calcular(&mut tb, &p, 3, &[(-128, 127); 3], 0);
+        comprobar(tb)?;
+
+        // Con el MAC segmentado y Booth
+        let (mut tb, p) = banco::<16, 17, TwosComplement, Booth, true, Saturate>("PipelinedDotProduct16Saturate");
+        calcular(&mut tb, &p, 0, &[(-128, -128); 16], 2);
+        calcular(&mut tb, &p, 5, &[(-128, 127); 5], 0);
+        calcular(&mut tb, &p, 3, &[(1, 2), (3, 4), (5, 6)], 0);
+        comprobar(tb)
+    }
+
+    // Con signo el Verilog es el mismo salvo la constante del multiplicador
+    #[test]
+    fn test_verilog_producto_escalar() {
+        let mut uut = DotProduct::<64, 8, 22>::default();
+        uut.connect_all();
+        let codigo = tfg_hdl_common::generate_named_verilog(&uut, "DotProduct64").unwrap();
+        assert!(
+            codigo.contains("localparam  ultimo_por_defecto = 16'h3f;"),
+            "{}",
+            codigo
+        );
+        assert!(codigo.contains("reg  [21:0] suma;"));
+        assert!(codigo.contains("localparam  con_signo = 1'b0;"));
+        assert!(codigo.contains("localparam  segmentado = 1'b0;"));
+
+        let mut uut = DotProduct::<64, 8, 22, TwosComplement>::default();
+        uut.connect_all();
+        let codigo = tfg_hdl_common::generate_named_verilog(&uut, "DotProduct64Signed").unwrap();
+        assert!(codigo.contains("localparam  con_signo = 1'b1;"));
+
+        let mut uut = DotProduct::<64, 8, 22, Unsigned, Booth>::default();
+        uut.connect_all();
+        let codigo = tfg_hdl_common::generate_named_verilog(&uut, "DotProduct64Booth").unwrap();
+        assert!(codigo.contains("parcial$4 << posiciones$8"));
+
+        let mut uut = PipelinedDotProduct::<64, 8, 22>::default();
+        uut.connect_all();
+        let codigo = tfg_hdl_common::generate_named_verilog(&uut, "PipelinedDotProduct64").unwrap();
+        assert!(codigo.contains("localparam  segmentado = 1'b1;"));
+        assert!(codigo.contains("producto$d = mul$resul;"));
+
+        let mut uut = DotProduct::<16, 8, 16, Unsigned, ShiftAdd, false, Saturate>::default();
+        uut.connect_all();
+        let codigo = tfg_hdl_common::generate_named_verilog(&uut, "DotProduct16Saturate").unwrap();
+        assert!(codigo.contains("output reg  overflow;"));
+        assert!(codigo.contains("localparam  saturar = 1'b1;"));
+    }
+}
@@ -0,0 +1,187 @@
+//Producto escalar en Rust
+use rust_hdl::prelude::*;
+
+// Flip-Flop común a todos los diseños
+use tfg_hdl_common::EdgeDFF;
+
+mod dot_product;
+mod mul;
+pub use dot_product::{dot_product_width, DotProduct, PipelinedDotProduct};
+pub use mul::Mul8x8;
+pub use tfg_hdl_common::{
+    Booth, MulAlgorithm, OverflowMode, Saturate, ShiftAdd, ShiftAddMul, Signedness, TwosComplement, Unsigned, Wallace, Wrap,
+};
+
+//Producto escalar
+#[derive(LogicBlock, Default, Clone)]
+#[allow(non_camel_case_types)]
+pub struct productoEscalar {
+    pub clock: Signal<In, Clock>,
+    pub reset: Signal<In, Bit>,
+    pub start: Signal<In, Bit>,        //señal para iniciar el cálculo
+    pub a: Signal<In, Bits<8>>,        
+    pub b: Signal<In, Bits<8>>,
+    pub valid: Signal<In, Bit>,        //indica si los datos de entrada son válidos
+    pub result: Signal<Out, Bits<16>>, //resultado del producto escalar
+    pub busy: Signal<Out, Bit>,
+    
+    //registros internos
+    accumulator: EdgeDFF<Bits<16>>,        //acumulador del producto escalar
+    counter: EdgeDFF<Bits<8>>,             //contador de ciclos/índice
+    e_busy: EdgeDFF<Bit>,                    //estado de ocupado
+    max_count: Signal<Local, Bits<8>>,
+    mul: Mul8x8,
+    
+}
+
+
+impl Logic for productoEscalar {
+    #[hdl_gen]
+    fn update(&mut self) { //solo cosas con self (señales internas)
+        //enlazamos el reloj
+        //dff_setup!(self, clock, accumulator, counter, busy);
+        self.accumulator.clk.next = self.clock.val();
+        self.counter.clk.next = self.clock.val();
+        self.e_busy.clk.next = self.clock.val();
+
+        //valor por defecto, no creo que haga falta pero por si acaso
+        self.accumulator.d.next = self.accumulator.q.val();
+        self.counter.d.next = self.counter.q.val();
+        self.e_busy.d.next = self.e_busy.q.val();
+
+        self.max_count.next = 4.into(); //debería ser N-1 pero no consigo generalizarlo
+        self.result.next = 0.into();
+
+        if self.reset.val() {
+            self.accumulator.d.next = 0.into();
+            self.counter.d.next = 0.into();
+            self.e_busy.d.next = false;
+            self.busy.next = false;
+        } 
+        else if !self.e_busy.q.val() { //si no está ocupado
+            if self.start.val() { //esperamos a que se inicie el cálculo
+                self.e_busy.d.next = true;
+                self.counter.d.next = 0.into();
+                self.accumulator.d.next = 0.into();
+                self.busy.next = true;
+            }
+        } 
+        else {
+            //procesamos los datos
+            if self.valid.val() { //si los datos son válidos(en la simulacion poner siempre a 1)
+                //conectamos entradas al multiplicador y esperamos el resultado
+                self.mul.a.next = self.a.val();
+                self.mul.b.next = self.b.val();
+                
+                self.accumulator.d.next = self.accumulator.q.val() + self.mul.resul.val(); //sumamos datos
+                self.counter.d.next = self.counter.q.val() + 1; //sumamos 1 al cont
+
+                if self.counter.q.val() == self.max_count.val() { //si ya hemos multiplicado todo
+                    self.result.next = self.accumulator.q.val();
+                    self.e_busy.d.next = false; //señalizamos que ya no está ocupado
+                    self.busy.next = false;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use tfg_hdl_common::{IcarusRunner, TestVector, TestVectors};
+
+    // Test con RustHDL y comparación con Verilog
+    #[test]
+    fn test_multiplicacion_matrices() -> anyhow::Result<()> {
+
+        let mut uut = productoEscalar::default();
+        uut.connect_all();
+
+        let mut tb = TestVectors::<productoEscalar>::new("productoEscalar").clock("clock", |x| &mut x.clock);
+        let reset = tb.input("reset", |x| &mut x.reset);
+        let start = tb.input("start", |x| &mut x.start);
+        let a = tb.input("a", |x| &mut x.a);
+        let b = tb.input("b", |x| &mut x.b);
+        let valid = tb.input("valid", |x| &mut x.valid);
+        let result = tb.output("result", |x| &x.result);
+        let busy = tb.output("busy", |x| &x.busy);
+
+        // 1. Reset (1 ciclo)
+        tb.push(TestVector::new("reset").set(reset, 1));
+        // 2. Start (1 ciclo)
+        tb.push(TestVector::new("start").set(reset, 0).set(start, 1));
+        // 3. Enviar valores válidos (4 ciclos)
+        let valores = [(3, 4), (5, 6), (7, 8), (9, 10)];
+        for (i, &(va, vb)) in valores.iter().enumerate() {
+            let mut vector = TestVector::new(&format!("a = {} b = {} a*b = {}", va, vb, va * vb))
+                .set(start, 0)
+                .set(valid, 1)
+                .set(a, va)
+                .set(b, vb);
+            // Resultado esperado: 3×4 + 5×6 + 7×8 + 9×10 = 12 + 30 + 56 + 90 = 188
+            if i == valores.len() - 1 {
+                vector = vector.expect(result, 188).expect(busy, 0);
+            }
+            tb.push(vector);
+        }
+
+        //Co-simulación: el mismo testbench en RustHDL y en Icarus, comparado ciclo a ciclo
+        if let Some(informe) = tb.cosimulate(&uut, &IcarusRunner::new())? {
+            println!("(iverilog) Salida de Verilog:\n{}", informe.stdout);
+            tb.check_verilog_output(&informe.stdout)?;
+        }
+
+        // Generamos la simulación y le añadimos el testbench
+        let mut sim = Simulation::<productoEscalar>::new();
+        tb.add_to(&mut sim);
+
+        /*
+
+        sim.add_testbench(move |mut ep| {
+            let mut x = ep.init()?;
+
+            // Definimos algunos valores de prueba para los vectores
+            let a_vals = vec![1, 2, 3];  // Ejemplos de valores para el vector A
+            let b_vals = vec![1, 2, 3];  // Ejemplos de valores para el vector B
+
+            let x_clone = x.clone();
+            x = ep.wait(1, x_clone)?;
+
+            // Iteramos sobre las combinaciones de valores de a y b
+            for &a in &a_vals {
+                for &b in &b_vals {
+                    // Cargamos los valores de entrada para el producto escalar
+                    x.a.next = Bits::<8>::from(a as u64);
+                    x.b.next = Bits::<8>::from(b as u64);
+
+                    x.valid.next = true;
+                    x.clock.next = Clock { clk: false };
+                    let x_clone = x.clone();
+                    x = ep.wait(1, x_clone)?;
+
+                    x.clock.next = Clock { clk: false };
+                    let x_clone = x.clone();
+                    x = ep.wait(1, x_clone)?;
+
+
+                    // Calculamos el resultado esperado en software (producto escalar)
+                    let expected_result = a * b;
+                    println!("Num {} Salida {:?}", expected_result, u64::from(x.result.val()));
+                    // Validamos el resultado en hardware con el esperado
+                    //sim_assert_eq!(ep, x.result.val(), Bits::<16>::from(expected_result as u64), x);
+                }
+            }
+
+            ep.done(x)?;
+
+            Ok(())
+        });*/
+
+        sim.run_to_file(Box::new(uut), 1000000000, "productoEscalarWave.vcd")
+            .map_err(|err| anyhow!("{:?}", err))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use tfg_hdl_common::{ShiftAddMul, Unsigned};
+
+// El multiplicador original de 8x8 bits, que ahora es el de sumas y
+// desplazamientos de tfg-hdl-common; Mul8x8<TwosComplement> es la versión con
+// signo
+pub type Mul8x8<S = Unsigned> = ShiftAddMul<8, 16, S>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_hdl::prelude::*;
+    use tfg_hdl_common::{TestVector, TestVectors, TwosComplement};
+
+    // Mul8x8 con signo en los extremos, incluido -128 · -128
+    #[test]
+    fn test_mul8x8_con_signo() -> anyhow::Result<()> {
+        let mut uut = Mul8x8::<TwosComplement>::default();
+        uut.connect_all();
+
+        let mut tb = TestVectors::<Mul8x8<TwosComplement>>::new("Mul8x8Signed");
+        let a = tb.input("a", |x| &mut x.a);
+        let b = tb.input("b", |x| &mut x.b);
+        let resul = tb.output("resul", |x| &x.resul);
+        for va in [-128, -127, -64, -1, 0, 1, 5, 127] {
+            for vb in [-128, -3, -1, 0, 1, 77, 127] {
+                tb.push(
+                    TestVector::new(&format!("{} * {}", va, vb))
+                        .set(a, va & 0xff)
+                        .set(b, vb & 0xff)
+                        .expect(resul, (va * vb) & 0xffff),
+                );
+            }
+        }
+
+        tb.verify(&uut, 10_000)?;
+        Ok(())
+    }
+}
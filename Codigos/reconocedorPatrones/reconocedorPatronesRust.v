@@ -1,3 +1,5 @@
+// Módulo reconocedorPatrones generado con RustHDL
+// Crate: reconocedorPatrones v0.1.0
 
 
 module reconocedorPatrones(clk,rst,entrada,salida);
@@ -21,7 +23,7 @@ module reconocedorPatrones(clk,rst,entrada,salida);
     reg  state$clk;
     
     // Sub module instances
-    top$state state(
+    reconocedorPatrones$state state(
         .d(state$d),
         .q(state$q),
         .clk(state$clk)
@@ -76,10 +78,10 @@ module reconocedorPatrones(clk,rst,entrada,salida);
         end
     end
     
-endmodule // top
+endmodule // reconocedorPatrones
 
 
-module top$state(d,q,clk);
+module reconocedorPatrones$state(d,q,clk);
     
     // Module arguments
     input wire  [2:0] d;
@@ -99,4 +101,4 @@ module top$state(d,q,clk);
     end
     
     always @(posedge clk) q <= d;
-endmodule // top$state
+endmodule // reconocedorPatrones$state
@@ -0,0 +1,791 @@
+// Generador de la máquina de estados de un reconocedor de patrones.
+//
+// ReconocedorPatrones tiene los estados de 1011 escritos a mano. Automaton
+// calcula la tabla de transiciones para cualquier patrón: con from_pattern
+// para una secuencia de bits (el autómata de KMP, un estado por cada prefijo
+// del patrón ya leído) y con from_regex para una expresión regular pequeña
+// sobre {0, 1}. En los dos casos se busca el patrón en cualquier punto de la
+// entrada y las apariciones se pueden solapar: el estado de aceptación se
+// alcanza cada vez que los últimos bits leídos forman el patrón.
+//
+// Con from_patterns se buscan varias secuencias a la vez (el autómata de
+// Aho-Corasick), y cada estado dice cuáles de ellas acaban de aparecer.
+//
+// Los estados son números, del 0 (siempre el inicial) a states() - 1, no un
+// enum con LogicState como el State de ReconocedorPatrones: su número es el
+// código que se guarda en el registro de estado, de state_bits() bits, y lo
+// que se sabe de cada uno (su nombre con label y si es de aceptación con
+// accepts) está en el Automaton. to_dot dibuja el autómata con esos
+// nombres. Tabla convierte la tabla en hardware, y PatternRecognizer y
+// MultiPatternRecognizer la usan con un registro de estado.
+use rust_hdl::prelude::*;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternError {
+    // El patrón no tiene ningún bit
+    Empty,
+    // Carácter que no es ni un bit ni un operador, con su posición
+    InvalidChar(char, usize),
+    // Expresión mal formada (paréntesis sin cerrar, operador sin operando...)
+    Syntax(usize),
+    // La expresión acepta la cadena vacía, y la salida estaría siempre a 1
+    MatchesEmpty,
+    // Los estados no caben en el registro de estado
+    TooManyStates { states: usize, bits: usize },
+    // El diseño tiene salidas para otro número de patrones
+    PatternCount { patterns: usize, outputs: usize },
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::Empty => write!(f, "el patrón está vacío"),
+            PatternError::InvalidChar(c, pos) => write!(f, "carácter '{}' no válido en la posición {}", c, pos),
+            PatternError::Syntax(pos) => write!(f, "expresión mal formada en la posición {}", pos),
+            PatternError::MatchesEmpty => write!(f, "la expresión acepta la cadena vacía"),
+            PatternError::TooManyStates { states, bits } => {
+                write!(f, "{} estados no caben en un registro de {} bits", states, bits)
+            }
+            PatternError::PatternCount { patterns, outputs } => {
+                write!(f, "hay {} patrones y {} salidas", patterns, outputs)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Automaton {
+    // siguiente[s][b]: estado al que se pasa desde s al leer el bit b
+    siguiente: Vec<[usize; 2]>,
+    // Patrones que acaban de aparecer en cada estado, de menor a mayor (con
+    // un solo patrón, [0] en los de aceptación)
+    coincide: Vec<Vec<usize>>,
+    patrones: usize,
+    // Nombre de cada estado (el prefijo leído con KMP y Aho-Corasick, Sn con
+    // from_regex)
+    etiquetas: Vec<String>,
+}
+
+impl Automaton {
+    // Autómata de KMP para una secuencia de bits como "1011". El estado j
+    // indica que los últimos j bits leídos son los j primeros del patrón; al
+    // fallar se pasa al estado del prefijo más largo que sigue encajando, así
+    // que no se pierden las apariciones solapadas
+    pub fn from_pattern(patron: &str) -> Result<Self, PatternError> {
+        let bits = leer_bits(patron)?;
+        let m = bits.len();
+        let mut siguiente = vec![[0; 2]; m + 1];
+        siguiente[0][bits[0] as usize] = 1;
+        // Estado al que se vuelve al fallar (el de KMP para el prefijo j)
+        let mut respaldo = 0;
+        for j in 1..=m {
+            siguiente[j] = siguiente[respaldo];
+            if j < m {
+                siguiente[j][bits[j] as usize] = j + 1;
+                respaldo = siguiente[respaldo][bits[j] as usize];
+            }
+        }
+        Ok(Self {
+            siguiente,
+            coincide: (0..=m).map(|j| if j == m { vec![0] } else { vec![] }).collect(),
+            patrones: 1,
+            etiquetas: (0..=m).map(|j| format!("\"{}\"", &patron[..j])).collect(),
+        })
+    }
+
+    // Autómata de Aho-Corasick para varias secuencias de bits (de cualquier
+    // longitud, y una puede estar dentro de otra). Los estados son los nodos
+    // del árbol de prefijos de todos los patrones; al fallar se pasa al nodo
+    // del sufijo más largo de lo leído que es prefijo de algún patrón, como
+    // en KMP. Un estado da como encontrados su patrón y los de los estados a
+    // los que lleva fallando (los patrones que son sufijo del suyo). Con un
+    // solo patrón es el autómata de from_pattern
+    pub fn from_patterns(patrones: &[&str]) -> Result<Self, PatternError> {
+        if patrones.is_empty() {
+            return Err(PatternError::Empty);
+        }
+        // Árbol de prefijos: hijos[n][b] es el nodo de prefijo(n) + b
+        let mut hijos: Vec<[Option<usize>; 2]> = vec![[None; 2]];
+        let mut prefijos = vec![String::new()];
+        let mut coincide = vec![vec![]];
+        for (p, patron) in patrones.iter().enumerate() {
+            let mut nodo = 0;
+            for (bit, c) in leer_bits(patron)?.into_iter().zip(patron.chars()) {
+                nodo = match hijos[nodo][bit as usize] {
+                    Some(hijo) => hijo,
+                    None => {
+                        hijos.push([None; 2]);
+                        prefijos.push(format!("{}{}", prefijos[nodo], c));
+                        coincide.push(vec![]);
+                        hijos[nodo][bit as usize] = Some(hijos.len() - 1);
+                        hijos.len() - 1
+                    }
+                };
+            }
+            coincide[nodo].push(p);
+        }
+
+        // Los nodos se recorren por niveles, así que el nodo al que se falla
+        // (más corto) ya está completo cuando se usa. Los estados se numeran
+        // en ese mismo orden
+        let mut orden = vec![0];
+        let mut numero = vec![0; hijos.len()];
+        let mut siguiente = vec![[0; 2]; hijos.len()];
+        let mut fallo = vec![0; hijos.len()];
+        let mut i = 0;
+        while i < orden.len() {
+            let nodo = orden[i];
+            let heredados = coincide[fallo[nodo]].clone();
+            if nodo != 0 {
+                coincide[nodo].extend(heredados);
+                coincide[nodo].sort_unstable();
+                coincide[nodo].dedup();
+            }
+            for b in 0..2 {
+                let respaldo = if nodo == 0 { 0 } else { siguiente[fallo[nodo]][b] };
+                siguiente[nodo][b] = match hijos[nodo][b] {
+                    Some(hijo) => {
+                        fallo[hijo] = respaldo;
+                        numero[hijo] = orden.len();
+                        orden.push(hijo);
+                        hijo
+                    }
+                    None => respaldo,
+                };
+            }
+            i += 1;
+        }
+        Ok(Self {
+            siguiente: orden.iter().map(|&n| siguiente[n].map(|t| numero[t])).collect(),
+            coincide: orden.iter().map(|&n| coincide[n].clone()).collect(),
+            patrones: patrones.len(),
+            etiquetas: orden.iter().map(|&n| format!("\"{}\"", prefijos[n])).collect(),
+        })
+    }
+
+    // Autómata para una expresión regular sobre {0, 1}: bits, '.' (cualquier
+    // bit), paréntesis, '|' y los cuantificadores '*', '+' y '?'. Se construye
+    // el autómata no determinista de Thompson, se pasa a determinista por
+    // subconjuntos (volviendo a meter el estado inicial en cada paso para
+    // buscar en cualquier punto) y se minimiza. Para una secuencia de bits
+    // sale el mismo autómata que con from_pattern
+    pub fn from_regex(expresion: &str) -> Result<Self, PatternError> {
+        let mut nfa = Thompson::default();
+        let mut lector = Lector {
+            caracteres: expresion.chars().collect(),
+            pos: 0,
+        };
+        if lector.caracteres.is_empty() {
+            return Err(PatternError::Empty);
+        }
+        let (inicio, fin) = lector.alternativa(&mut nfa)?;
+        if lector.pos < lector.caracteres.len() {
+            return Err(PatternError::Syntax(lector.pos));
+        }
+        let dfa = nfa.determinista(inicio, fin);
+        if dfa.accepts(0) {
+            return Err(PatternError::MatchesEmpty);
+        }
+        Ok(dfa.minimo())
+    }
+
+    pub fn states(&self) -> usize {
+        self.siguiente.len()
+    }
+
+    pub fn next(&self, estado: usize, bit: bool) -> usize {
+        self.siguiente[estado][bit as usize]
+    }
+
+    pub fn accepts(&self, estado: usize) -> bool {
+        !self.coincide[estado].is_empty()
+    }
+
+    // Número de patrones que se buscan
+    pub fn patterns(&self) -> usize {
+        self.patrones
+    }
+
+    // Patrones que acaban de aparecer al llegar a `estado`
+    pub fn matched(&self, estado: usize) -> &[usize] {
+        &self.coincide[estado]
+    }
+
+    pub fn label(&self, estado: usize) -> &str {
+        &self.etiquetas[estado]
+    }
+
+    // Diagrama en DOT, como StateMachine::to_dot: cada estado con su nombre
+    // y, si se buscan varios patrones, los que da; los de aceptación con
+    // doble círculo y una flecha que entra al inicial
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph automata {\n    rankdir=LR;\n    node [shape=circle];\n");
+        dot += "    inicio [shape=point];\n";
+        for s in 0..self.states() {
+            let mut etiqueta = self.label(s).to_string();
+            if self.patterns() > 1 && self.accepts(s) {
+                let patrones: Vec<String> = self.matched(s).iter().map(|p| p.to_string()).collect();
+                etiqueta += &format!("\\n{{{}}}", patrones.join(", "));
+            }
+            let forma = if self.accepts(s) { ", shape=doublecircle" } else { "" };
+            dot += &format!("    {} [label=\"{}\"{}];\n", s, etiqueta.replace('"', "\\\""), forma);
+        }
+        dot += "    inicio -> 0;\n";
+        for s in 0..self.states() {
+            let (con_cero, con_uno) = (self.next(s, false), self.next(s, true));
+            if con_cero == con_uno {
+                dot += &format!("    {} -> {} [label=\"0, 1\"];\n", s, con_cero);
+            } else {
+                dot += &format!("    {} -> {} [label=\"0\"];\n", s, con_cero);
+                dot += &format!("    {} -> {} [label=\"1\"];\n", s, con_uno);
+            }
+        }
+        dot + "}\n"
+    }
+
+    // Bits mínimos del registro de estado
+    pub fn state_bits(&self) -> usize {
+        (usize::BITS - (self.states() - 1).leading_zeros()).max(1) as usize
+    }
+
+    // Modelo de referencia: si el autómata está en un estado de aceptación
+    // después de leer cada bit
+    pub fn matches(&self, entrada: &[bool]) -> Vec<bool> {
+        let mut estado = 0;
+        entrada
+            .iter()
+            .map(|&bit| {
+                estado = self.next(estado, bit);
+                self.accepts(estado)
+            })
+            .collect()
+    }
+
+    // El mismo autómata sin solapamiento: después de una aparición se sigue
+    // como desde el estado inicial, así que los bits del patrón encontrado no
+    // cuentan para el siguiente (con 1011, 1011011 da una sola aparición y
+    // 10111011 da dos)
+    pub fn non_overlapping(&self) -> Self {
+        let mut automata = self.clone();
+        for s in 0..self.states() {
+            if self.accepts(s) {
+                automata.siguiente[s] = self.siguiente[0];
+            }
+        }
+        automata
+    }
+
+    // Minimización por refinamiento de particiones: se empieza separando los
+    // estados según los patrones que dan y se parte cada bloque según a qué
+    // bloques llevan sus transiciones, hasta que no cambia. Los bloques se
+    // numeran en el orden en que se alcanzan desde el inicial
+    fn minimo(&self) -> Self {
+        let mut salidas = HashMap::new();
+        let mut bloque: Vec<usize> = self
+            .coincide
+            .iter()
+            .map(|c| {
+                let n = salidas.len();
+                *salidas.entry(c).or_insert(n)
+            })
+            .collect();
+        loop {
+            let mut claves = HashMap::new();
+            let nuevo: Vec<usize> = (0..self.states())
+                .map(|s| {
+                    let clave = (bloque[s], bloque[self.siguiente[s][0]], bloque[self.siguiente[s][1]]);
+                    let n = claves.len();
+                    *claves.entry(clave).or_insert(n)
+                })
+                .collect();
+            let estables = claves.len() == bloque.iter().collect::<BTreeSet<_>>().len();
+            bloque = nuevo;
+            if estables {
+                break;
+            }
+        }
+
+        let mut orden = HashMap::new();
+        let mut representantes = vec![];
+        let mut pendientes = VecDeque::from([0]);
+        orden.insert(bloque[0], 0);
+        representantes.push(0);
+        while let Some(s) = pendientes.pop_front() {
+            for t in self.siguiente[s] {
+                if let Entry::Vacant(hueco) = orden.entry(bloque[t]) {
+                    hueco.insert(representantes.len());
+                    representantes.push(t);
+                    pendientes.push_back(t);
+                }
+            }
+        }
+        Self {
+            siguiente: representantes
+                .iter()
+                .map(|&s| {
+                    [
+                        orden[&bloque[self.siguiente[s][0]]],
+                        orden[&bloque[self.siguiente[s][1]]],
+                    ]
+                })
+                .collect(),
+            coincide: representantes.iter().map(|&s| self.coincide[s].clone()).collect(),
+            patrones: self.patrones,
+            etiquetas: (0..representantes.len()).map(|s| format!("S{}", s)).collect(),
+        }
+    }
+}
+
+// Reconocedores que se construyen a partir de un Automaton, con los atajos
+// para una secuencia de bits y para una expresión regular
+pub trait FromAutomaton: Sized {
+    fn new(automata: &Automaton) -> Result<Self, PatternError>;
+
+    // Atajo para una secuencia de bits como "1011"
+    fn from_pattern(patron: &str) -> Result<Self, PatternError> {
+        Self::new(&Automaton::from_pattern(patron)?)
+    }
+
+    // Atajo para una expresión regular (ver Automaton::from_regex)
+    fn from_regex(expresion: &str) -> Result<Self, PatternError> {
+        Self::new(&Automaton::from_regex(expresion)?)
+    }
+}
+
+// Tabla de transiciones de un Automaton en hardware, la misma para todos los
+// reconocedores. Es combinacional: para el estado `estado` de B bits y el
+// bit `entrada` da el estado `siguiente`, y los P bits de los patrones que
+// acaban en `estado` (`encontrados`, para la salida Moore) y en `siguiente`
+// (`encontrados_siguiente`, para la Mealy). Con un solo patrón, P = 1 y el
+// bit dice si el estado es de aceptación.
+//
+// Para cada estado s hay una constante con su código, otra con el estado
+// siguiente al leer un 0, otra al leer un 1 y las de los patrones. B tiene
+// que ser al menos Automaton::state_bits(); si sobran códigos, un estado que
+// no existe vuelve al inicial.
+#[derive(LogicBlock, Clone)]
+pub(crate) struct Tabla<const B: usize, const P: usize = 1> {
+    pub estado: Signal<In, Bits<B>>,
+    pub entrada: Signal<In, Bit>,
+    pub siguiente: Signal<Out, Bits<B>>,
+    pub encontrados: Signal<Out, Bits<P>>,
+    pub encontrados_siguiente: Signal<Out, Bits<P>>,
+    // Código de cada estado (su número)
+    codigos: Vec<Constant<Bits<B>>>,
+    con_cero: Vec<Constant<Bits<B>>>,
+    con_uno: Vec<Constant<Bits<B>>>,
+    // Patrones que acaban en cada estado y en el siguiente con un 0 o un 1
+    patrones: Vec<Constant<Bits<P>>>,
+    patrones_con_cero: Vec<Constant<Bits<P>>>,
+    patrones_con_uno: Vec<Constant<Bits<P>>>,
+}
+
+impl<const B: usize, const P: usize> Tabla<B, P> {
+    pub fn new(automata: &Automaton) -> Result<Self, PatternError> {
+        let estados = automata.states();
+        if automata.state_bits() > B {
+            return Err(PatternError::TooManyStates {
+                states: estados,
+                bits: B,
+            });
+        }
+        if automata.patterns() != P {
+            return Err(PatternError::PatternCount {
+                patterns: automata.patterns(),
+                outputs: P,
+            });
+        }
+        let codigo = |s: usize| Constant::new(Bits::<B>::from(s as u64));
+        let patrones = |s: usize| {
+            let bits = automata
+                .matched(s)
+                .iter()
+                .fold(Bits::<P>::default(), |b, &p| b.replace_bit(p, true));
+            Constant::new(bits)
+        };
+        Ok(Self {
+            estado: Default::default(),
+            entrada: Default::default(),
+            siguiente: Default::default(),
+            encontrados: Default::default(),
+            encontrados_siguiente: Default::default(),
+            codigos: (0..estados).map(codigo).collect(),
+            con_cero: (0..estados).map(|s| codigo(automata.next(s, false))).collect(),
+            con_uno: (0..estados).map(|s| codigo(automata.next(s, true))).collect(),
+            patrones: (0..estados).map(patrones).collect(),
+            patrones_con_cero: (0..estados).map(|s| patrones(automata.next(s, false))).collect(),
+            patrones_con_uno: (0..estados).map(|s| patrones(automata.next(s, true))).collect(),
+        })
+    }
+}
+
+impl<const B: usize, const P: usize> Logic for Tabla<B, P> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        // Un código que no es de ningún estado vuelve al inicial
+        self.siguiente.next = 0.into();
+        self.encontrados.next = 0.into();
+        self.encontrados_siguiente.next = 0.into();
+
+        for s in 0..self.codigos.len() {
+            if self.estado.val() == self.codigos[s].val() {
+                self.encontrados.next = self.patrones[s].val();
+                if self.entrada.val() {
+                    self.siguiente.next = self.con_uno[s].val();
+                    self.encontrados_siguiente.next = self.patrones_con_uno[s].val();
+                } else {
+                    self.siguiente.next = self.con_cero[s].val();
+                    self.encontrados_siguiente.next = self.patrones_con_cero[s].val();
+                }
+            }
+        }
+    }
+}
+
+fn leer_bits(patron: &str) -> Result<Vec<bool>, PatternError> {
+    if patron.is_empty() {
+        return Err(PatternError::Empty);
+    }
+    patron
+        .chars()
+        .enumerate()
+        .map(|(pos, c)| match c {
+            '0' => Ok(false),
+            '1' => Ok(true),
+            _ => Err(PatternError::InvalidChar(c, pos)),
+        })
+        .collect()
+}
+
+// Autómata no determinista de Thompson: cada nodo tiene transiciones vacías
+// y transiciones con cada bit
+#[derive(Default)]
+struct Thompson {
+    vacias: Vec<Vec<usize>>,
+    con_bit: Vec<[Vec<usize>; 2]>,
+}
+
+impl Thompson {
+    fn nodo(&mut self) -> usize {
+        self.vacias.push(vec![]);
+        self.con_bit.push([vec![], vec![]]);
+        self.vacias.len() - 1
+    }
+
+    // Nodos alcanzables desde `nodos` sin leer ningún bit
+    fn cierre(&self, nodos: impl IntoIterator<Item = usize>) -> BTreeSet<usize> {
+        let mut cierre = BTreeSet::new();
+        let mut pendientes: Vec<usize> = nodos.into_iter().collect();
+        while let Some(n) = pendientes.pop() {
+            if cierre.insert(n) {
+                pendientes.extend(&self.vacias[n]);
+            }
+        }
+        cierre
+    }
+
+    // Construcción por subconjuntos. Cada estado es un conjunto de nodos, y
+    // en todos está el cierre del inicio para que el patrón pueda empezar en
+    // cualquier bit
+    fn determinista(&self, inicio: usize, fin: usize) -> Automaton {
+        let arranque = self.cierre([inicio]);
+        let mut conjuntos = vec![arranque.clone()];
+        let mut indices = HashMap::from([(arranque.clone(), 0)]);
+        let mut siguiente = vec![];
+        let mut s = 0;
+        while s < conjuntos.len() {
+            let mut transiciones = [0; 2];
+            for (b, destino) in transiciones.iter_mut().enumerate() {
+                let alcanzados = conjuntos[s].iter().flat_map(|&n| self.con_bit[n][b].iter().copied());
+                let mut conjunto = self.cierre(alcanzados);
+                conjunto.extend(&arranque);
+                *destino = match indices.get(&conjunto) {
+                    Some(&t) => t,
+                    None => {
+                        indices.insert(conjunto.clone(), conjuntos.len());
+                        conjuntos.push(conjunto);
+                        conjuntos.len() - 1
+                    }
+                };
+            }
+            siguiente.push(transiciones);
+            s += 1;
+        }
+        Automaton {
+            coincide: conjuntos
+                .iter()
+                .map(|c| if c.contains(&fin) { vec![0] } else { vec![] })
+                .collect(),
+            patrones: 1,
+            etiquetas: (0..conjuntos.len()).map(|s| format!("S{}", s)).collect(),
+            siguiente,
+        }
+    }
+}
+
+// Analizador descendente de la expresión. Cada regla añade sus nodos al
+// autómata de Thompson y devuelve el nodo de entrada y el de salida
+//
+//     alternativa = secuencia ('|' secuencia)*
+//     secuencia   = repeticion*
+//     repeticion  = atomo ('*' | '+' | '?')*
+//     atomo       = '0' | '1' | '.' | '(' alternativa ')'
+struct Lector {
+    caracteres: Vec<char>,
+    pos: usize,
+}
+
+impl Lector {
+    fn actual(&self) -> Option<char> {
+        self.caracteres.get(self.pos).copied()
+    }
+
+    fn alternativa(&mut self, nfa: &mut Thompson) -> Result<(usize, usize), PatternError> {
+        let (mut inicio, mut fin) = self.secuencia(nfa)?;
+        while self.actual() == Some('|') {
+            self.pos += 1;
+            let (otro_inicio, otro_fin) = self.secuencia(nfa)?;
+            let (nuevo_inicio, nuevo_fin) = (nfa.nodo(), nfa.nodo());
+            nfa.vacias[nuevo_inicio].extend([inicio, otro_inicio]);
+            nfa.vacias[fin].push(nuevo_fin);
+            nfa.vacias[otro_fin].push(nuevo_fin);
+            (inicio, fin) = (nuevo_inicio, nuevo_fin);
+        }
+        Ok((inicio, fin))
+    }
+
+    fn secuencia(&mut self, nfa: &mut Thompson) -> Result<(usize, usize), PatternError> {
+        let inicio = nfa.nodo();
+        let mut fin = inicio;
+        while let Some(c) = self.actual() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let (parte_inicio, parte_fin) = self.repeticion(nfa)?;
+            nfa.vacias[fin].push(parte_inicio);
+            fin = parte_fin;
+        }
+        Ok((inicio, fin))
+    }
+
+    fn repeticion(&mut self, nfa: &mut Thompson) -> Result<(usize, usize), PatternError> {
+        let (mut inicio, mut fin) = self.atomo(nfa)?;
+        while let Some(c @ ('*' | '+' | '?')) = self.actual() {
+            self.pos += 1;
+            let (nuevo_inicio, nuevo_fin) = (nfa.nodo(), nfa.nodo());
+            nfa.vacias[nuevo_inicio].push(inicio);
+            nfa.vacias[fin].push(nuevo_fin);
+            if c != '+' {
+                // Cero veces
+                nfa.vacias[nuevo_inicio].push(nuevo_fin);
+            }
+            if c != '?' {
+                // Otra vez
+                nfa.vacias[fin].push(inicio);
+            }
+            (inicio, fin) = (nuevo_inicio, nuevo_fin);
+        }
+        Ok((inicio, fin))
+    }
+
+    fn atomo(&mut self, nfa: &mut Thompson) -> Result<(usize, usize), PatternError> {
+        let pos = self.pos;
+        let c = self.actual().ok_or(PatternError::Syntax(pos))?;
+        self.pos += 1;
+        let bits: &[usize] = match c {
+            '0' => &[0],
+            '1' => &[1],
+            '.' => &[0, 1],
+            '(' => {
+                let grupo = self.alternativa(nfa)?;
+                if self.actual() != Some(')') {
+                    return Err(PatternError::Syntax(self.pos));
+                }
+                self.pos += 1;
+                return Ok(grupo);
+            }
+            '|' | ')' | '*' | '+' | '?' => return Err(PatternError::Syntax(pos)),
+            _ => return Err(PatternError::InvalidChar(c, pos)),
+        };
+        let (inicio, fin) = (nfa.nodo(), nfa.nodo());
+        for &b in bits {
+            nfa.con_bit[inicio][b].push(fin);
+        }
+        Ok((inicio, fin))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Todas las secuencias de `largo` bits
+    fn secuencias(largo: usize) -> impl Iterator<Item = Vec<bool>> {
+        (0..1u32 << largo).map(move |n| (0..largo).map(|i| n >> i & 1 == 1).collect())
+    }
+
+    #[test]
+    fn test_kmp_1011() {
+        let automata = Automaton::from_pattern("1011").unwrap();
+        assert_eq!(automata.states(), 5);
+        assert_eq!(automata.state_bits(), 3);
+        // Después de 1011 con un 0 quedan leídos "10", y con un 1 "1"
+        assert_eq!(automata.next(4, false), 2);
+        assert_eq!(automata.next(4, true), 1);
+        assert_eq!(automata.label(3), "\"101\"");
+        // 1011011: dos apariciones solapadas
+        let entrada: Vec<bool> = "1011011".chars().map(|c| c == '1').collect();
+        assert_eq!(
+            automata.matches(&entrada),
+            [false, false, false, true, false, false, true]
+        );
+        // Sin solapamiento el 1 del medio ya no empieza otro 1011
+        assert_eq!(
+            automata.non_overlapping().matches(&entrada),
+            [false, false, false, true, false, false, false]
+        );
+        assert_eq!(automata.non_overlapping().next(4, false), 0);
+    }
+
+    // El modelo coincide con buscar el patrón al final de lo leído, para
+    // todas las entradas de hasta 10 bits
+    #[test]
+    fn test_kmp_contra_busqueda_directa() {
+        for patron in ["1", "0", "1011", "0000", "0101", "110110", "10010"] {
+            let automata = Automaton::from_pattern(patron).unwrap();
+            let bits: Vec<bool> = patron.chars().map(|c| c == '1').collect();
+            for entrada in secuencias(10) {
+                let esperado: Vec<bool> = (1..=entrada.len()).map(|i| entrada[..i].ends_with(&bits)).collect();
+                assert_eq!(automata.matches(&entrada), esperado, "{} en {:?}", patron, entrada);
+            }
+        }
+    }
+
+    // Una secuencia de bits como expresión regular da el mismo autómata
+    #[test]
+    fn test_regex_igual_que_kmp() {
+        for patron in ["1011", "0000", "0101", "110110", "10010"] {
+            let kmp = Automaton::from_pattern(patron).unwrap();
+            let regex = Automaton::from_regex(patron).unwrap();
+            assert_eq!(regex.siguiente, kmp.siguiente, "{}", patron);
+            assert_eq!(regex.coincide, kmp.coincide, "{}", patron);
+        }
+    }
+
+    #[test]
+    fn test_regex() {
+        // 1?01: el bit del medio da igual
+        let comodin = Automaton::from_regex("1.01").unwrap();
+        // 1, uno o más 0 y 1
+        let ceros = Automaton::from_regex("10+1").unwrap();
+        // 11 o 1001
+        let grupos = Automaton::from_regex("1(1|00)1").unwrap();
+        for entrada in secuencias(10) {
+            for i in 1..=entrada.len() {
+                let leido = &entrada[..i];
+                let texto: String = leido.iter().map(|&b| if b { '1' } else { '0' }).collect();
+                let sin_unos = texto.trim_end_matches('1');
+                let esperado_ceros = texto.ends_with('1')
+                    && texto.len() - sin_unos.len() == 1
+                    && sin_unos.ends_with('0')
+                    && sin_unos.trim_end_matches('0').ends_with('1');
+                assert_eq!(
+                    comodin.matches(leido)[i - 1],
+                    texto.ends_with("1001") || texto.ends_with("1101")
+                );
+                assert_eq!(ceros.matches(leido)[i - 1], esperado_ceros, "{}", texto);
+                assert_eq!(
+                    grupos.matches(leido)[i - 1],
+                    texto.ends_with("111") || texto.ends_with("1001")
+                );
+            }
+        }
+    }
+
+    // Con un solo patrón Aho-Corasick es KMP
+    #[test]
+    fn test_aho_corasick_igual_que_kmp() {
+        for patron in ["1", "1011", "0000", "110110"] {
+            assert_eq!(
+                Automaton::from_patterns(&[patron]).unwrap(),
+                Automaton::from_pattern(patron).unwrap()
+            );
+        }
+    }
+
+    // Cada estado da los patrones con los que acaba lo leído, para todas las
+    // entradas de hasta 10 bits
+    #[test]
+    fn test_aho_corasick_contra_busqueda_directa() {
+        let grupos: [&[&str]; 4] = [
+            &["1011", "0110"],
+            &["01111110", "111", "0"],
+            // Uno dentro de otro, y repetidos
+            &["10", "0100", "10", "100"],
+            &["000", "0001", "001", "01"],
+        ];
+        for patrones in grupos {
+            let automata = Automaton::from_patterns(patrones).unwrap();
+            assert_eq!(automata.patterns(), patrones.len());
+            for entrada in secuencias(10) {
+                let texto: String = entrada.iter().map(|&b| if b { '1' } else { '0' }).collect();
+                let mut estado = 0;
+                for i in 1..=texto.len() {
+                    estado = automata.next(estado, entrada[i - 1]);
+                    let esperado: Vec<usize> = (0..patrones.len())
+                        .filter(|&p| texto[..i].ends_with(patrones[p]))
+                        .collect();
+                    assert_eq!(automata.matched(estado), esperado, "{:?} en {}", patrones, &texto[..i]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_aho_corasick_estados() {
+        let automata = Automaton::from_patterns(&["10", "0100", "100"]).unwrap();
+        // "", "0", "1", "01", "10", "010", "100", "0100"
+        assert_eq!(automata.states(), 8);
+        assert_eq!(automata.label(6), "\"100\"");
+        // En "0100" acaban "0100" y "100"
+        assert_eq!(automata.matched(7), [1, 2]);
+        assert_eq!(automata.matched(5), [0]);
+    }
+
+    #[test]
+    fn test_dot() {
+        let dot = Automaton::from_pattern("10").unwrap().to_dot();
+        assert!(dot.starts_with("digraph automata {"), "{}", dot);
+        assert!(dot.contains("    1 [label=\"\\\"1\\\"\"];"), "{}", dot);
+        assert!(dot.contains("    2 [label=\"\\\"10\\\"\", shape=doublecircle];"));
+        assert!(dot.contains("    inicio -> 0;"));
+        assert!(dot.contains("    0 -> 1 [label=\"1\"];"));
+        assert!(dot.contains("    2 -> 1 [label=\"1\"];"));
+        assert!(dot.ends_with("}\n"));
+
+        // Con varios patrones se ven los que da cada estado, y con
+        // from_regex los estados se llaman Sn
+        let dot = Automaton::from_patterns(&["10", "0100", "100"]).unwrap().to_dot();
+        assert!(dot.contains("    7 [label=\"\\\"0100\\\"\\n{1, 2}\", shape=doublecircle];"), "{}", dot);
+        let dot = Automaton::from_regex("1.").unwrap().to_dot();
+        assert!(dot.contains("    0 [label=\"S0\"];"), "{}", dot);
+    }
+
+    #[test]
+    fn test_errores() {
+        assert_eq!(Automaton::from_pattern(""), Err(PatternError::Empty));
+        assert_eq!(Automaton::from_pattern("10x1"), Err(PatternError::InvalidChar('x', 2)));
+        assert_eq!(Automaton::from_patterns(&[]), Err(PatternError::Empty));
+        assert_eq!(Automaton::from_patterns(&["10", ""]), Err(PatternError::Empty));
+        assert_eq!(Automaton::from_regex("1(01"), Err(PatternError::Syntax(4)));
+        assert_eq!(Automaton::from_regex("10)"), Err(PatternError::Syntax(2)));
+        assert_eq!(Automaton::from_regex("*1"), Err(PatternError::Syntax(0)));
+        assert_eq!(Automaton::from_regex("1*"), Err(PatternError::MatchesEmpty));
+        assert_eq!(Automaton::from_regex("1|"), Err(PatternError::MatchesEmpty));
+    }
+}
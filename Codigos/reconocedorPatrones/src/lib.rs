@@ -0,0 +1,251 @@
+// Reconocedores de patrones de bits.
+//
+// ReconocedorPatrones es la máquina de estados de 1011 escrita a mano, con
+// un enum de estados, y se mantiene como referencia. Para cualquier otro
+// patrón, o para elegir salida y solapamiento, está PatternRecognizer, que
+// saca la máquina de un Automaton:
+//
+//     PatternRecognizer::<3, Moore, NonOverlapping>::from_pattern("1011")
+//
+// es el sustituto de ReconocedorPatrones. Los dos dan la salida un ciclo
+// después del último bit y no solapan apariciones, pero ReconocedorPatrones
+// pasa de S4 a S0 sin mirar la entrada, así que ese bit nunca empieza otra
+// aparición (con 10111011 da una y PatternRecognizer dos).
+// MultiPatternRecognizer busca varios patrones a la vez y
+// ParallelPatternRecognizer lee varios bits por ciclo.
+use rust_hdl::prelude::*;
+
+pub mod automaton;
+pub mod multi_pattern;
+pub mod parallel;
+pub mod pattern;
+
+pub use automaton::{Automaton, FromAutomaton, PatternError};
+pub use multi_pattern::MultiPatternRecognizer;
+pub use parallel::ParallelPatternRecognizer;
+pub use pattern::{MatchPolicy, Mealy, Moore, NonOverlapping, OutputMode, Overlapping, PatternRecognizer};
+
+// Flip-Flop común a todos los diseños
+use tfg_hdl_common::{EdgeDFF, FsmError, StateMachine};
+
+// Entradas de prueba que usan los tests de varios reconocedores
+#[cfg(test)]
+mod pruebas {
+    // Los bits de todos los bytes seguidos, el primero de cada byte el más
+    // significativo, que tienen de todo
+    pub fn todos_los_bytes() -> Vec<bool> {
+        (0..=255u8)
+            .flat_map(|n| (0..8).rev().map(move |i| n >> i & 1 == 1))
+            .collect()
+    }
+}
+
+//-------------------- Máquina de estados modificada --------------------
+
+// Da la salida en S4, un ciclo después del último bit, y de S4 vuelve a S0
+// sin mirar la entrada, así que se pierden las apariciones solapadas
+// (1011011 sólo da una). Su sustituto es PatternRecognizer con Moore y
+// NonOverlapping (ver arriba)
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, LogicState)]
+enum State {
+    S0,
+    S1,
+    S2,
+    S3,
+    S4,
+}
+
+#[derive(LogicBlock, Clone)]
+pub struct ReconocedorPatrones {
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    pub entrada: Signal<In, Bit>,
+    pub salida: Signal<Out, Bit>,
+    state: EdgeDFF<State>, // <--- Usamos tu EdgeDFF personalizado
+}
+
+impl Default for ReconocedorPatrones {
+    fn default() -> Self {
+        Self {
+            clk: Default::default(),
+            rst: Default::default(),
+            entrada: Default::default(),
+            salida: Default::default(),
+            state: EdgeDFF::new(State::S0),
+        }
+    }
+}
+
+impl ReconocedorPatrones {
+    // Estados y transiciones de `state`, sacados del update (State no es
+    // público, así que se extrae desde aquí)
+    pub fn state_machine(&self) -> Result<StateMachine, FsmError> {
+        StateMachine::extract::<State, _>(self, "state")
+    }
+}
+
+impl Logic for ReconocedorPatrones {
+    #[hdl_gen]
+    fn update(&mut self) {
+        // Enlazamos reloj
+        self.state.clk.next = self.clk.val();
+
+        // Valor por defecto
+        self.salida.next = false;
+        self.state.d.next = self.state.q.val(); // Mantiene el estado actual
+
+        if self.rst.val() {
+            self.state.d.next = State::S0;
+        } else {
+            match self.state.q.val() {
+                State::S0 => {
+                    if self.entrada.val() {
+                        self.state.d.next = State::S1;
+                    }
+                }
+                State::S1 => {
+                    if !self.entrada.val() {
+                        self.state.d.next = State::S2;
+                    }
+                }
+                State::S2 => {
+                    if self.entrada.val() {
+                        self.state.d.next = State::S3;
+                    } else {
+                        self.state.d.next = State::S0;
+                    }
+                }
+                State::S3 => {
+                    if self.entrada.val() {
+                        self.state.d.next = State::S4;
+                    } else {
+                        self.state.d.next = State::S2;
+                    }
+                }
+                State::S4 => {
+                    self.salida.next = true;
+                    self.state.d.next = State::S0;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use tfg_hdl_common::{IcarusRunner, Input, Output, TestVector, TestVectors};
+
+    // Secuencia de entrada y salida esperada después de cada flanco
+    // (buscando 1011)
+    const SECUENCIA: [(i64, i64, &str); 14] = [
+        // Primera prueba: 1011 (patrón correcto)
+        (1, 0, "S1"),
+        (0, 0, "S2"),
+        (1, 0, "S3"),
+        (1, 1, "S4 -> salida = 1"),
+        // Segunda prueba: patrón parcial (101)
+        (1, 0, "parcial"),
+        (0, 0, "parcial"),
+        (1, 0, "parcial"),
+        // Tercera prueba: ruido + patrón correcto + superposicion
+        (0, 0, "ruido"),
+        (1, 0, "patrón"),
+        (0, 0, "patrón"),
+        (1, 0, "superposicion"),
+        (0, 0, "patrón"),
+        (1, 0, "patrón"),
+        (1, 1, "salida -> 1"),
+    ];
+
+    // Reset y SECUENCIA, con los puertos que tienen los dos reconocedores
+    fn secuencia<T: Block + 'static>(tb: &mut TestVectors<T>, rst: Input, entrada: Input, salida: Output) {
+        tb.push(TestVector::new("reset").set(rst, 1));
+        tb.push(TestVector::new("fin del reset").set(rst, 0));
+        for (bit, esperado, nombre) in SECUENCIA {
+            tb.push(TestVector::new(nombre).set(entrada, bit).expect(salida, esperado));
+        }
+    }
+
+    fn casos() -> TestVectors<ReconocedorPatrones> {
+        let mut tb = TestVectors::<ReconocedorPatrones>::new("reconocedorPatrones").clock("clk", |x| &mut x.clk);
+        let rst = tb.input("rst", |x| &mut x.rst);
+        let entrada = tb.input("entrada", |x| &mut x.entrada);
+        let salida = tb.output("salida", |x| &x.salida);
+        secuencia(&mut tb, rst, entrada, salida);
+        tb
+    }
+
+    // Test con RustHDL y comparación con Verilog
+    #[test]
+    fn test_reconocedor_patrones() -> anyhow::Result<()> {
+
+        let mut uut = ReconocedorPatrones::default();
+        uut.connect_all();
+        let tb = casos();
+
+        //Co-simulación: el mismo testbench en RustHDL y en Icarus, comparado ciclo a ciclo
+        if let Some(informe) = tb.cosimulate(&uut, &IcarusRunner::new())? {
+            println!("(iverilog) Salida de Verilog:\n{}", informe.stdout);
+            tb.check_verilog_output(&informe.stdout)?;
+        }
+
+        // Simulación en RustHDL con VCD
+        let mut sim = Simulation::<ReconocedorPatrones>::new();
+        tb.add_to(&mut sim);
+        sim.run_to_file(Box::new(uut), 180_000, "reconocedorPatronesWave.vcd")
+            .map_err(|err| anyhow!("{:?}", err))?;
+
+        Ok(())
+    }
+
+    // El sustituto generado da la misma salida con la misma secuencia
+    #[test]
+    fn test_sustituto_generado() -> anyhow::Result<()> {
+        type Sustituto = PatternRecognizer<3, Moore, NonOverlapping>;
+        let mut uut = Sustituto::from_pattern("1011")?;
+        uut.connect_all();
+        let mut tb = TestVectors::<Sustituto>::new("reconocedorPatronesGenerado").clock("clk", |x| &mut x.clk);
+        let rst = tb.input("rst", |x| &mut x.rst);
+        let entrada = tb.input("entrada", |x| &mut x.entrada);
+        let salida = tb.output("salida", |x| &x.salida);
+        secuencia(&mut tb, rst, entrada, salida);
+
+        tb.verify(&uut, 180_000)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_maquina_de_estados() -> anyhow::Result<()> {
+        let fsm = ReconocedorPatrones::default().state_machine()?;
+        assert_eq!(fsm.states, ["S0", "S1", "S2", "S3", "S4"]);
+        assert_eq!(fsm.initial(), "S0");
+        let aristas: Vec<(Option<&str>, &str, &str)> = fsm
+            .transitions
+            .iter()
+            .map(|t| (t.from.as_deref(), t.to.as_str(), t.condition.as_str()))
+            .collect();
+        assert_eq!(
+            aristas,
+            [
+                (None, "S0", "rst"),
+                (Some("S0"), "S1", "!rst && entrada"),
+                (Some("S1"), "S2", "!rst && !entrada"),
+                (Some("S2"), "S3", "!rst && entrada"),
+                (Some("S2"), "S0", "!rst && !entrada"),
+                (Some("S3"), "S4", "!rst && entrada"),
+                (Some("S3"), "S2", "!rst && !entrada"),
+                (Some("S4"), "S0", "!rst"),
+            ]
+        );
+        assert!(fsm.unreachable().is_empty());
+        assert!(fsm.missing_transitions().is_empty());
+        assert_eq!(fsm.report(), "");
+        let dot = fsm.to_dot();
+        assert!(dot.contains("\"S0\" [shape=doublecircle];"), "{}", dot);
+        assert!(dot.contains("\"S3\" -> \"S2\" [label=\"!rst && !entrada\"];"));
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use rust_hdl::prelude::*;
+
+use crate::automaton::{Automaton, FromAutomaton, PatternError, Tabla};
+use tfg_hdl_common::EdgeDFF;
+
+// Reconocedor de varios patrones a la vez sobre la misma `entrada`.
+//
+// Es PatternRecognizer con un autómata de Automaton::from_patterns: el
+// estado es un número de B bits y las transiciones salen de la Tabla, que
+// da también los P bits de `salidas` (el bit p a 1 si el patrón p acaba de
+// aparecer). `id` es el número, en I bits, del primero de ellos, y `salida`
+// indica que ha aparecido alguno y que `id` es válido. Igual que en
+// PatternRecognizer, las salidas cambian en el ciclo siguiente al último
+// bit del patrón.
+#[derive(LogicBlock, Clone)]
+pub struct MultiPatternRecognizer<const B: usize, const P: usize, const I: usize> {
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    pub entrada: Signal<In, Bit>,
+    pub salidas: Signal<Out, Bits<P>>,
+    pub salida: Signal<Out, Bit>,
+    pub id: Signal<Out, Bits<I>>,
+    estado: EdgeDFF<Bits<B>>,
+    tabla: Tabla<B, P>,
+    // El número de cada patrón, para `id`
+    numeros: [Constant<Bits<I>>; P],
+}
+
+impl<const B: usize, const P: usize, const I: usize> FromAutomaton for MultiPatternRecognizer<B, P, I> {
+    fn new(automata: &Automaton) -> Result<Self, PatternError> {
+        const {
+            assert!(P <= 1 << I, "el id no tiene bits suficientes para P patrones");
+        }
+        Ok(Self {
+            clk: Default::default(),
+            rst: Default::default(),
+            entrada: Default::default(),
+            salidas: Default::default(),
+            salida: Default::default(),
+            id: Default::default(),
+            estado: EdgeDFF::new(0.into()),
+            tabla: Tabla::new(automata)?,
+            numeros: std::array::from_fn(|p| Constant::new((p as u64).into())),
+        })
+    }
+}
+
+impl<const B: usize, const P: usize, const I: usize> MultiPatternRecognizer<B, P, I> {
+    // Atajo para una lista de secuencias de bits
+    pub fn from_patterns(patrones: &[&str]) -> Result<Self, PatternError> {
+        Self::new(&Automaton::from_patterns(patrones)?)
+    }
+}
+
+impl<const B: usize, const P: usize, const I: usize> Logic for MultiPatternRecognizer<B, P, I> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.estado.clk.next = self.clk.val();
+
+        self.tabla.estado.next = self.estado.q.val();
+        self.tabla.entrada.next = self.entrada.val();
+        self.estado.d.next = self.tabla.siguiente.val();
+        self.salidas.next = self.tabla.encontrados.val();
+        self.salida.next = self.tabla.encontrados.val().any();
+
+        // El primero de los encontrados: se recorren de mayor a menor y se
+        // queda el último
+        // P entra como `q`: bucle de una vuelta, ver tfg_hdl_common
+        self.id.next = 0.into();
+        for q in P..(P + 1) {
+            for p in 1..(P + 1) {
+                if self.tabla.encontrados.val().get_bit(q - p) {
+                    self.id.next = self.numeros[q - p].val();
+                }
+            }
+        }
+
+        if self.rst.val() {
+            self.estado.d.next = 0.into();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tfg_hdl_common::{TestVector, TestVectors};
+
+    // Marcadores de trama: inicio, fin y escape (el de escape está dentro
+    // del de fin)
+    const MARCADORES: [&str; 3] = ["01111110", "0111111", "11111"];
+
+    type Reconocedor = MultiPatternRecognizer<4, 3, 2>;
+
+    // Reset y después cada bit de `entrada`, con las salidas calculadas
+    // buscando cada patrón al final de lo leído
+    fn casos(modulo: &str, patrones: &[&str], entrada: &str) -> TestVectors<Reconocedor> {
+        let mut tb = TestVectors::<Reconocedor>::new(modulo).clock("clk", |x| &mut x.clk);
+        let rst = tb.input("rst", |x| &mut x.rst);
+        let bit = tb.input("entrada", |x| &mut x.entrada);
+        let salidas = tb.output("salidas", |x| &x.salidas);
+        let salida = tb.output("salida", |x| &x.salida);
+        let id = tb.output("id", |x| &x.id);
+
+        tb.push(
+            TestVector::new("reset")
+                .set(rst, 1)
+                .expect(salidas, 0)
+                .expect(salida, 0),
+        );
+        tb.push(TestVector::new("fin del reset").set(rst, 0));
+        for i in 1..=entrada.len() {
+            let encontrados: Vec<usize> = (0..patrones.len())
+                .filter(|&p| entrada[..i].ends_with(patrones[p]))
+                .collect();
+            let mut vector = TestVector::new(&format!("bit {}", i - 1))
+                .set(bit, (&entrada[i - 1..i] == "1") as i64)
+                .expect(salidas, encontrados.iter().map(|p| 1 << p).sum())
+                .expect(salida, !encontrados.is_empty() as i64);
+            if let Some(&primero) = encontrados.first() {
+                vector = vector.expect(id, primero as i64);
+            }
+            tb.push(vector);
+        }
+        tb
+    }
+
+    fn comprobar(modulo: &str, patrones: &[&str], entrada: &str) -> anyhow::Result<()> {
+        let mut uut = Reconocedor::from_patterns(patrones)?;
+        uut.connect_all();
+        let tb = casos(modulo, patrones, entrada);
+
+        tb.verify(&uut, 100 * (entrada.len() as u64 + 10))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_marcadores() -> anyhow::Result<()> {
+        // Trama con inicio, datos, un escape, más datos y fin
+        comprobar(
+            "MultiPatternRecognizer",
+            &MARCADORES,
+            "0011111101011111010011011111101111111",
+        )?;
+        // Los bits de todos los bytes seguidos
+        let bytes: String = (0..=255u8).map(|n| format!("{:08b}", n)).collect();
+        comprobar("MultiPatternRecognizerBytes", &["1011", "0110", "111"], &bytes)
+    }
+
+    #[test]
+    fn test_numero_de_patrones() {
+        assert_eq!(
+            Reconocedor::from_patterns(&["10", "01"]).err(),
+            Some(PatternError::PatternCount {
+                patterns: 2,
+                outputs: 3
+            })
+        );
+        assert_eq!(
+            MultiPatternRecognizer::<2, 3, 2>::from_patterns(&MARCADORES).err(),
+            Some(PatternError::TooManyStates { states: 14, bits: 2 })
+        );
+    }
+
+    #[test]
+    fn test_verilog_multipatron() -> anyhow::Result<()> {
+        let mut uut = Reconocedor::from_patterns(&MARCADORES)?;
+        uut.connect_all();
+        let verilog = tfg_hdl_common::generate_named_verilog(&uut, "MultiPatternRecognizer")?;
+        // "0111111" es el estado 12, y en él acaban el fin y el escape
+        assert!(verilog.contains("localparam  patrones$12 = 3'b110;"), "{}", verilog);
+        // El id del primero que se encuentra
+        assert!(verilog.contains("if (tabla$encontrados[3 - 3]) begin"));
+        assert!(verilog.contains("id = numeros$0;"));
+        assert!(verilog.contains("output reg  [2:0] salidas;"));
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+use rust_hdl::prelude::*;
+
+use crate::automaton::{Automaton, FromAutomaton, PatternError, Tabla};
+use crate::pattern::{MatchPolicy, Overlapping};
+use tfg_hdl_common::EdgeDFF;
+
+// Reconocedor de patrones que lee K bits por ciclo.
+//
+// PatternRecognizer da un paso del autómata por ciclo, así que no pasa de un
+// bit por ciclo. Aquí `entrada` es una palabra de K bits y en cada ciclo se
+// dan K pasos seguidos: K copias de la Tabla del autómata, cada una con el
+// estado que deja la anterior. Los bits se leen del más significativo al
+// menos significativo (el bit K-1 es el primero que llegó, como en un
+// registro de desplazamiento que mete los bits por la derecha), y el estado
+// que queda después del bit 0 se guarda para la palabra siguiente, así que
+// el patrón puede empezar en una palabra y acabar en otra.
+//
+// `salidas` tiene un bit por cada bit de la palabra: el bit i a 1 si el
+// patrón acaba en el bit i de `entrada`. Está registrada, así que sale en el
+// ciclo siguiente a la palabra (como la salida Moore de PatternRecognizer), y
+// `salida` indica que ha aparecido alguna vez en la palabra.
+#[derive(LogicBlock, Clone)]
+pub struct ParallelPatternRecognizer<const K: usize, const B: usize, M: MatchPolicy = Overlapping> {
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    pub entrada: Signal<In, Bits<K>>,
+    pub salidas: Signal<Out, Bits<K>>,
+    pub salida: Signal<Out, Bit>,
+    estado: EdgeDFF<Bits<B>>,
+    encontrados: EdgeDFF<Bits<K>>,
+    nuevos: Signal<Local, Bits<K>>,
+    // Una Tabla por paso, cada una con el estado que deja la anterior
+    pasos: [Tabla<B>; K],
+    // Bit de `salidas` del paso j (el K-1-j)
+    posiciones: [Constant<Bits<K>>; K],
+    politica: M,
+}
+
+impl<const K: usize, const B: usize, M: MatchPolicy> FromAutomaton for ParallelPatternRecognizer<K, B, M> {
+    fn new(automata: &Automaton) -> Result<Self, PatternError> {
+        let tabla = Tabla::new(&M::automaton(automata))?;
+        Ok(Self {
+            clk: Default::default(),
+            rst: Default::default(),
+            entrada: Default::default(),
+            salidas: Default::default(),
+            salida: Default::default(),
+            estado: EdgeDFF::new(0.into()),
+            encontrados: EdgeDFF::new(0.into()),
+            nuevos: Default::default(),
+            pasos: std::array::from_fn(|_| tabla.clone()),
+            posiciones: std::array::from_fn(|j| Constant::new(Bits::<K>::default().replace_bit(K - 1 - j, true))),
+            politica: Default::default(),
+        })
+    }
+}
+
+impl<const K: usize, const B: usize, M: MatchPolicy> Logic for ParallelPatternRecognizer<K, B, M> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.estado.clk.next = self.clk.val();
+        self.encontrados.clk.next = self.clk.val();
+
+        // Los K pasos, uno detrás de otro; el paso j lee el bit K-1-j
+        // k = K - 1: bucle de una vuelta, ver tfg_hdl_common
+        self.nuevos.next = 0.into();
+        for k in (K - 1)..K {
+            for j in 0..K {
+                self.pasos[j].entrada.next = self.entrada.val().get_bit(k - j);
+                if self.pasos[j].encontrados_siguiente.val().any() {
+                    self.nuevos.next = self.nuevos.val() | self.posiciones[j].val();
+                }
+            }
+            self.estado.d.next = self.pasos[k].siguiente.val();
+        }
+        self.pasos[0].estado.next = self.estado.q.val();
+        for j in 1..K {
+            self.pasos[j].estado.next = self.pasos[j - 1].siguiente.val();
+        }
+
+        self.encontrados.d.next = self.nuevos.val();
+        if self.rst.val() {
+            self.estado.d.next = 0.into();
+            self.encontrados.d.next = 0.into();
+        }
+
+        self.salidas.next = self.encontrados.q.val();
+        self.salida.next = self.encontrados.q.val().any();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::NonOverlapping;
+    use crate::pruebas::todos_los_bytes;
+    use tfg_hdl_common::{TestVector, TestVectors};
+
+    // Reset y después `bits` en palabras de K, con las apariciones que da el
+    // modelo (el mismo autómata leyendo bit a bit)
+    fn comprobar<const K: usize, const B: usize, M: MatchPolicy>(
+        modulo: &str,
+        automata: &Automaton,
+        bits: &[bool],
+    ) -> anyhow::Result<()> {
+        let mut uut = ParallelPatternRecognizer::<K, B, M>::new(automata)?;
+        uut.connect_all();
+
+        let modelo = M::automaton(automata).matches(bits);
+        let mut tb = TestVectors::<ParallelPatternRecognizer<K, B, M>>::new(modulo).clock("clk", |x| &mut x.clk);
+        let rst = tb.input("rst", |x| &mut x.rst);
+        let entrada = tb.input("entrada", |x| &mut x.entrada);
+        let salidas = tb.output("salidas", |x| &x.salidas);
+        let salida = tb.output("salida", |x| &x.salida);
+        tb.push(TestVector::new("reset").set(rst, 1).expect(salidas, 0));
+        tb.push(TestVector::new("fin del reset").set(rst, 0));
+        for (n, (palabra, encontrados)) in bits.chunks(K).zip(modelo.chunks(K)).enumerate() {
+            // El primer bit de la palabra es el más significativo
+            let valor = |v: &[bool]| v.iter().fold(0, |a, &b| a << 1 | b as i64);
+            tb.push(
+                TestVector::new(&format!("palabra {}", n))
+                    .set(entrada, valor(palabra))
+                    .expect(salidas, valor(encontrados))
+                    .expect(salida, encontrados.contains(&true) as i64),
+            );
+        }
+
+        tb.verify(&uut, 100 * (bits.len() as u64 + 10))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_byte_por_ciclo() -> anyhow::Result<()> {
+        let bytes = todos_los_bytes();
+        let sincronismo = Automaton::from_pattern("01111110")?;
+        comprobar::<8, 4, Overlapping>("ParallelPatternRecognizerSync", &sincronismo, &bytes)?;
+        // Patrones cortos, que aparecen varias veces en la misma palabra
+        comprobar::<8, 3, Overlapping>(
+            "ParallelPatternRecognizer1011",
+            &Automaton::from_pattern("1011")?,
+            &bytes,
+        )?;
+        comprobar::<8, 2, Overlapping>("ParallelPatternRecognizer11", &Automaton::from_pattern("11")?, &bytes)?;
+        comprobar::<8, 2, NonOverlapping>(
+            "ParallelPatternRecognizer11NoSolape",
+            &Automaton::from_pattern("11")?,
+            &bytes,
+        )
+    }
+
+    // Otros anchos de palabra, incluido el de un bit (que es PatternRecognizer
+    // con la salida Moore)
+    #[test]
+    fn test_otros_anchos() -> anyhow::Result<()> {
+        let bytes = todos_los_bytes();
+        let automata = Automaton::from_regex("10+1")?;
+        comprobar::<1, 2, Overlapping>("ParallelPatternRecognizerK1", &automata, &bytes[..512])?;
+        comprobar::<3, 2, Overlapping>("ParallelPatternRecognizerK3", &automata, &bytes[..1020])?;
+        comprobar::<16, 3, NonOverlapping>(
+            "ParallelPatternRecognizerK16",
+            &Automaton::from_pattern("0110")?,
+            &bytes,
+        )
+    }
+
+    #[test]
+    fn test_verilog_paralelo() -> anyhow::Result<()> {
+        let mut uut = ParallelPatternRecognizer::<8, 3>::from_pattern("1011")?;
+        uut.connect_all();
+        let verilog = tfg_hdl_common::generate_named_verilog(&uut, "ParallelPatternRecognizer")?;
+        // El paso 0 lee el bit 7 y el 7 el bit 0
+        assert!(verilog.contains("pasos$0$entrada = entrada[7 - 0];"), "{}", verilog);
+        assert!(verilog.contains("pasos$7$entrada = entrada[7 - 7];"));
+        assert!(verilog.contains("localparam  posiciones$0 = 8'h80;"));
+        assert!(verilog.contains("nuevos = nuevos | posiciones$7;"));
+        // Cada paso sigue desde el estado del anterior, y el último es el
+        // que se guarda
+        assert!(verilog.contains("pasos$1$estado = pasos$0$siguiente;"));
+        assert!(verilog.contains("estado$d = pasos$7$siguiente;"));
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+use rust_hdl::prelude::*;
+
+use crate::automaton::{Automaton, FromAutomaton, PatternError, Tabla};
+use tfg_hdl_common::{modo_sin_hardware, EdgeDFF};
+
+// Cuándo se da la salida. Con Moore `salida` depende sólo del estado y vale
+// 1 en el ciclo siguiente al último bit del patrón; con Mealy depende también
+// de `entrada` y vale 1 en el mismo ciclo en el que llega ese bit
+pub trait OutputMode: Block + Clone + Default + Send + 'static {
+    const MEALY: bool;
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Moore;
+
+#[derive(Clone, Debug, Default)]
+pub struct Mealy;
+
+impl OutputMode for Moore {
+    const MEALY: bool = false;
+}
+
+impl OutputMode for Mealy {
+    const MEALY: bool = true;
+}
+
+// Si dos apariciones del patrón pueden compartir bits. Con NonOverlapping
+// se usa el autómata de Automaton::non_overlapping
+pub trait MatchPolicy: Block + Clone + Default + Send + 'static {
+    const OVERLAPPING: bool;
+
+    // El autómata que hay que convertir en hardware con esta política
+    fn automaton(automata: &Automaton) -> Automaton {
+        if Self::OVERLAPPING {
+            automata.clone()
+        } else {
+            automata.non_overlapping()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Overlapping;
+
+#[derive(Clone, Debug, Default)]
+pub struct NonOverlapping;
+
+impl MatchPolicy for Overlapping {
+    const OVERLAPPING: bool = true;
+}
+
+impl MatchPolicy for NonOverlapping {
+    const OVERLAPPING: bool = false;
+}
+
+modo_sin_hardware!(Moore, Mealy, Overlapping, NonOverlapping);
+
+// Reconocedor de patrones generado a partir de un Automaton.
+//
+// Tiene los mismos puertos que ReconocedorPatrones, pero en vez de un enum
+// con los estados escritos a mano el estado es un número de B bits y las
+// transiciones salen de la Tabla del autómata. Con Moore `salida` vale 1
+// mientras el estado es de aceptación; con Mealy, si lo es el estado
+// siguiente con el bit de `entrada`.
+#[derive(LogicBlock, Clone)]
+pub struct PatternRecognizer<const B: usize, O: OutputMode = Moore, M: MatchPolicy = Overlapping> {
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    pub entrada: Signal<In, Bit>,
+    pub salida: Signal<Out, Bit>,
+    estado: EdgeDFF<Bits<B>>,
+    tabla: Tabla<B>,
+    // 1 con Mealy, 0 con Moore
+    mealy: Constant<Bit>,
+    modo: O,
+    politica: M,
+}
+
+impl<const B: usize, O: OutputMode, M: MatchPolicy> FromAutomaton for PatternRecognizer<B, O, M> {
+    fn new(automata: &Automaton) -> Result<Self, PatternError> {
+        Ok(Self {
+            clk: Default::default(),
+            rst: Default::default(),
+            entrada: Default::default(),
+            salida: Default::default(),
+            estado: EdgeDFF::new(0.into()),
+            tabla: Tabla::new(&M::automaton(automata))?,
+            mealy: Constant::new(O::MEALY),
+            modo: Default::default(),
+            politica: Default::default(),
+        })
+    }
+}
+
+impl<const B: usize, O: OutputMode, M: MatchPolicy> Logic for PatternRecognizer<B, O, M> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.estado.clk.next = self.clk.val();
+
+        self.tabla.estado.next = self.estado.q.val();
+        self.tabla.entrada.next = self.entrada.val();
+        self.estado.d.next = self.tabla.siguiente.val();
+        if self.mealy.val() {
+            self.salida.next = self.tabla.encontrados_siguiente.val().any();
+        } else {
+            self.salida.next = self.tabla.encontrados.val().any();
+        }
+
+        if self.rst.val() {
+            self.estado.d.next = 0.into();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pruebas::todos_los_bytes;
+    use tfg_hdl_common::{TestVector, TestVectors};
+
+    // Reset y después cada bit de `entrada` con la salida del modelo
+    fn casos<const B: usize>(
+        modulo: &str,
+        automata: &Automaton,
+        entrada: &[bool],
+    ) -> TestVectors<PatternRecognizer<B>> {
+        let mut tb = TestVectors::<PatternRecognizer<B>>::new(modulo).clock("clk", |x| &mut x.clk);
+        let rst = tb.input("rst", |x| &mut x.rst);
+        let bit = tb.input("entrada", |x| &mut x.entrada);
+        let salida = tb.output("salida", |x| &x.salida);
+
+        tb.push(TestVector::new("reset").set(rst, 1).expect(salida, 0));
+        tb.push(TestVector::new("fin del reset").set(rst, 0));
+        for (i, (&b, esperado)) in entrada.iter().zip(automata.matches(entrada)).enumerate() {
+            tb.push(
+                TestVector::new(&format!("bit {}", i))
+                    .set(bit, b as i64)
+                    .expect(salida, esperado as i64),
+            );
+        }
+        tb
+    }
+
+    fn comprobar<const B: usize>(modulo: &str, automata: &Automaton, entrada: &[bool]) -> anyhow::Result<()> {
+        let mut uut = PatternRecognizer::<B>::new(automata)?;
+        uut.connect_all();
+        let tb = casos::<B>(modulo, automata, entrada);
+
+        tb.verify(&uut, 100 * (entrada.len() as u64 + 10))?;
+        Ok(())
+    }
+
+    // La misma secuencia que el test de ReconocedorPatrones
+    #[test]
+    fn test_1011_como_el_original() -> anyhow::Result<()> {
+        let entrada: Vec<bool> = "10111010101011".chars().map(|c| c == '1').collect();
+        let automata = Automaton::from_pattern("1011")?;
+        assert_eq!(
+            automata.matches(&entrada),
+            entrada
+                .iter()
+                .enumerate()
+                .map(|(i, _)| i == 3 || i == 13)
+                .collect::<Vec<_>>()
+        );
+        comprobar::<3>("PatternRecognizer1011", &automata, &entrada)
+    }
+
+    #[test]
+    fn test_patrones_y_expresiones() -> anyhow::Result<()> {
+        let entrada = todos_los_bytes();
+        // Palabra de sincronismo de 8 bits (9 estados, 4 bits de estado)
+        comprobar::<4>("PatternRecognizerSync", &Automaton::from_pattern("01111110")?, &entrada)?;
+        comprobar::<3>("PatternRecognizerWildcard", &Automaton::from_regex("1.01")?, &entrada)?;
+        comprobar::<3>("PatternRecognizerRegex", &Automaton::from_regex("10+1|0110")?, &entrada)
+    }
+
+    // Apariciones de `patron` que acaban en cada bit de `texto`, buscando
+    // directamente en el texto. Sin solapamiento una aparición sólo cuenta si
+    // empieza después de la última que se ha contado
+    fn buscar(patron: &str, texto: &str, solapando: bool) -> Vec<bool> {
+        let mut fin_anterior = 0;
+        (1..=texto.len())
+            .map(|i| {
+                let encontrada = texto[..i].ends_with(patron) && (solapando || i - patron.len() >= fin_anterior);
+                if encontrada {
+                    fin_anterior = i;
+                }
+                encontrada
+            })
+            .collect()
+    }
+
+    // Todas las entradas de `largo` bits, cada una después de un reset (con
+    // lo que también se prueban todas las más cortas). Con Mealy la salida
+    // se mira antes del flanco en el que entra el bit y con Moore después
+    fn exhaustivo<O: OutputMode, M: MatchPolicy>(patron: &str, largo: usize) {
+        let mut uut = PatternRecognizer::<3, O, M>::from_pattern(patron).unwrap();
+        uut.connect_all();
+
+        let patron = patron.to_string();
+        let mut sim = Simulation::<PatternRecognizer<3, O, M>>::new();
+        sim.add_testbench(move |mut ep: Sim<PatternRecognizer<3, O, M>>| {
+            let mut x = ep.init()?;
+            for n in 0..1u32 << largo {
+                let texto: String = (0..largo).map(|i| if n >> i & 1 == 1 { '1' } else { '0' }).collect();
+                let esperado = buscar(&patron, &texto, M::OVERLAPPING);
+                x.rst.next = true;
+                for (i, bit) in texto.chars().map(|c| c == '1').enumerate() {
+                    x.clk.next = Clock { clk: true };
+                    x = ep.wait(1, x)?;
+                    x.rst.next = false;
+                    x.entrada.next = bit;
+                    x.clk.next = Clock { clk: false };
+                    x = ep.wait(1, x)?;
+                    let moore = i > 0 && esperado[i - 1];
+                    sim_assert_eq!(ep, x.salida.val(), if O::MEALY { esperado[i] } else { moore }, x);
+                }
+                x.clk.next = Clock { clk: true };
+                x = ep.wait(1, x)?;
+                x.clk.next = Clock { clk: false };
+                x = ep.wait(1, x)?;
+                if !O::MEALY {
+                    sim_assert_eq!(ep, x.salida.val(), esperado[largo - 1], x);
+                }
+            }
+            ep.done(x)
+        });
+        sim.run(Box::new(uut), ((2 * largo as u64 + 2) << largo) + 10).unwrap();
+    }
+
+    #[test]
+    fn test_mealy_moore_y_solapamiento() {
+        // 1011011 da dos apariciones solapando y una sin solapar
+        assert_eq!(
+            buscar("1011", "1011011", true),
+            [false, false, false, true, false, false, true]
+        );
+        assert_eq!(
+            buscar("1011", "1011011", false),
+            [false, false, false, true, false, false, false]
+        );
+        for patron in ["1011", "0110", "111", "10"] {
+            exhaustivo::<Moore, Overlapping>(patron, 10);
+            exhaustivo::<Moore, NonOverlapping>(patron, 10);
+            exhaustivo::<Mealy, Overlapping>(patron, 10);
+            exhaustivo::<Mealy, NonOverlapping>(patron, 10);
+        }
+    }
+
+    #[test]
+    fn test_estados_que_no_caben() {
+        assert_eq!(
+            PatternRecognizer::<2>::from_pattern("1011").err(),
+            Some(PatternError::TooManyStates { states: 5, bits: 2 })
+        );
+        assert!(PatternRecognizer::<4>::from_regex("1(").is_err());
+    }
+
+    #[test]
+    fn test_verilog_reconocedor() -> anyhow::Result<()> {
+        let mut uut = PatternRecognizer::<3>::from_pattern("1011")?;
+        uut.connect_all();
+        let verilog = tfg_hdl_common::generate_named_verilog(&uut, "PatternRecognizer1011")?;
+        // Desde "1011" con un 0 se pasa a "10"
+        assert!(verilog.contains("localparam  con_cero$4 = 3'b10;"), "{}", verilog);
+        assert!(verilog.contains("localparam  patrones$4 = 1'b1;"));
+        assert!(verilog.contains("if (estado == codigos$4) begin"));
+        assert!(verilog.contains("tabla$estado = estado$q;"));
+
+        let mut uut = PatternRecognizer::<3, Mealy, NonOverlapping>::from_pattern("1011")?;
+        uut.connect_all();
+        let verilog = tfg_hdl_common::generate_named_verilog(&uut, "PatternRecognizerMealy")?;
+        // Sin solapamiento, desde "1011" con un 0 se vuelve al inicio
+        assert!(verilog.contains("localparam  con_cero$4 = 3'b0;"), "{}", verilog);
+        assert!(verilog.contains("localparam  patrones_con_uno$3 = 1'b1;"));
+        assert!(verilog.contains("localparam  mealy = 1'b1;"));
+        Ok(())
+    }
+}
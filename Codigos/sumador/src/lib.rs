@@ -0,0 +1,91 @@
+use rust_hdl::prelude::*;
+// signed_bit_cast de RustHDL desborda la pila con -128
+use tfg_hdl_common::signed_bit_cast;
+
+// Definición del módulo Sumador
+#[derive(LogicBlock, Clone, Default)]
+pub struct Sumador {
+    pub a: Signal<In, Signed<8>>,   // Entrada A (8 bits)
+    pub b: Signal<In, Signed<8>>,   // Entrada B (8 bits)
+    pub sum: Signal<Out, Signed<9>>, // Salida Sum (9 bits)
+}
+
+impl Logic for Sumador {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.sum.next = signed_bit_cast::<9, 8>(self.a.val()) + signed_bit_cast::<9, 8>(self.b.val());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use tfg_hdl_common::{TestVector, TestVectors};
+
+    // Casos que se prueban igual en la simulación de RustHDL y en Icarus
+    fn casos() -> TestVectors<Sumador> {
+        let mut tb = TestVectors::<Sumador>::new("Sumador");
+        let a = tb.input("a", |x| &mut x.a);
+        let b = tb.input("b", |x| &mut x.b);
+        let sum = tb.output("sum", |x| &x.sum);
+        for (va, vb) in [(-128, -128), (-50, 75), (-50, 50), (127, 127), (-128, 127), (0, -1)] {
+            tb.push(
+                TestVector::new(&format!("{} + {}", va, vb))
+                    .set(a, va)
+                    .set(b, vb)
+                    .expect(sum, va + vb),
+            );
+        }
+        tb
+    }
+
+    // Test con RustHDL y comparación con Verilog
+    #[test]
+    fn test_sumador_verilog() -> anyhow::Result<()> {
+        let mut uut = Sumador::default();
+        uut.connect_all();
+        let tb = casos();
+
+        //Co-simulación: el mismo testbench en RustHDL y en Icarus, comparado ciclo a ciclo
+        if let Some(informe) = tb.verify(&uut, 1_000)? {
+            println!("(iverilog) Salida de Verilog:\n{}", informe.stdout);
+        }
+
+        Ok(())
+    }
+
+    // Barrido de RustHDL sobre todos los pares de entradas (256 x 256,
+    // -128 incluido). Los casos se generan según avanza la simulación, así
+    // que no se guarda ningún vector de 65.536 elementos
+    #[test]
+    fn test_sumador() -> anyhow::Result<()> {
+        let mut uut = Sumador::default();
+        uut.connect_all();
+
+        let mut tb = TestVectors::<Sumador>::new("Sumador");
+        let a = tb.input("a", |x| &mut x.a);
+        let b = tb.input("b", |x| &mut x.b);
+        let sum = tb.output("sum", |x| &x.sum);
+        tb.stream(move || {
+            (-128..128).flat_map(move |va| {
+                (-128..128).map(move |vb| {
+                    TestVector::new(&format!("{} + {}", va, vb))
+                        .set(a, va)
+                        .set(b, vb)
+                        .expect(sum, va + vb)
+                })
+            })
+        });
+        assert_eq!(tb.len(), 65_536);
+
+        //Generamos la simulacion del sumador y le añadimos el testbench. No
+        //se escribe VCD: con 65.536 casos sería enorme y cambiaría
+        //sumadorWave.vcd cada vez que se pasan los test
+        let mut sim = Simulation::<Sumador>::new();
+        tb.add_to(&mut sim);
+        sim.run(Box::new(uut), 200_000).map_err(|err| anyhow!("{:?}", err))?;
+
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+// Módulo Sumador generado con RustHDL
+// Crate: sumador v0.1.0
 
 
 module Sumador(a,b,sum);
@@ -12,4 +14,4 @@ module Sumador(a,b,sum);
         sum = $signed(a) + $signed(b);
     end
     
-endmodule // top
+endmodule // Sumador
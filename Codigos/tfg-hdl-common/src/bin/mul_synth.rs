@@ -0,0 +1,60 @@
+// Sintetiza con Yosys los multiplicadores de 8x8 bits de tfg-hdl-common, con
+// cada algoritmo, con y sin signo y con y sin el producto registrado, y
+// muestra su área y su profundidad en LUTs para elegir cuál usar en los
+// diseños.
+//
+//     cargo run -p tfg-hdl-common --bin mul_synth
+use rust_hdl::prelude::*;
+use tfg_hdl_common::{
+    Booth, MulAlgorithm, Multiplier, Operator, Registered, ShiftAdd, Signedness, SynthStats, TwosComplement, Unsigned,
+    Wallace, YosysRunner,
+};
+
+fn sintetizar<S: Signedness, A: MulAlgorithm>(runner: &YosysRunner) -> (String, Option<SynthStats>) {
+    let signo = if S::SIGNED { "con signo" } else { "sin signo" };
+    let fila = format!("{} | {} | {}", A::name(), signo, A::LATENCY);
+    let mut uut = Multiplier::<8, 16, S, A>::default();
+    uut.connect_all();
+    match runner.synthesize_source("Multiplier", &generate_verilog(&uut)) {
+        Ok(estadisticas) => (fila, Some(estadisticas)),
+        Err(err) => {
+            eprintln!("  {} ({}): {}", A::name(), signo, err);
+            (fila, None)
+        }
+    }
+}
+
+fn main() {
+    let runner = YosysRunner::new();
+    if !runner.available() {
+        eprintln!("yosys no está instalado o no está en el PATH");
+        std::process::exit(1);
+    }
+
+    println!("Sintetizando los multiplicadores de 8x8 bits...");
+    let filas = [
+        sintetizar::<Unsigned, Operator>(&runner),
+        sintetizar::<Unsigned, ShiftAdd>(&runner),
+        sintetizar::<Unsigned, Booth>(&runner),
+        sintetizar::<Unsigned, Wallace>(&runner),
+        sintetizar::<TwosComplement, Operator>(&runner),
+        sintetizar::<TwosComplement, ShiftAdd>(&runner),
+        sintetizar::<TwosComplement, Booth>(&runner),
+        sintetizar::<TwosComplement, Wallace>(&runner),
+        sintetizar::<Unsigned, Registered<ShiftAdd>>(&runner),
+        sintetizar::<Unsigned, Registered<Booth>>(&runner),
+        sintetizar::<Unsigned, Registered<Wallace>>(&runner),
+        sintetizar::<TwosComplement, Registered<ShiftAdd>>(&runner),
+        sintetizar::<TwosComplement, Registered<Booth>>(&runner),
+        sintetizar::<TwosComplement, Registered<Wallace>>(&runner),
+    ];
+
+    println!("\n| Algoritmo | Operandos | Latencia | Celdas | LUT | Profundidad (LUT) |");
+    println!("|---|---|---:|---:|---:|---:|");
+    for (fila, estadisticas) in filas {
+        match estadisticas {
+            Some(e) => println!("| {} | {} | {} | {} |", fila, e.cells, e.luts, e.depth),
+            None => println!("| {} | - | - | - |", fila),
+        }
+    }
+}
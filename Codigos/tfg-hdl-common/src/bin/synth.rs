@@ -0,0 +1,56 @@
+// Sintetiza con Yosys todos los diseños del TFG (el <nombre>Rust.v generado
+// por RustHDL y el Verilog/*Verilog.v escrito a mano) y muestra la tabla de
+// área. Cada ejecución se añade a sintesis.csv para ver cómo evoluciona.
+//
+//     cargo run -p tfg-hdl-common --bin synth [directorio con los diseños]
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tfg_hdl_common::synth::{area_csv, area_table, find_designs, AreaRow, YosysRunner};
+
+fn main() {
+    let raiz = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(".."));
+    let runner = YosysRunner::new();
+    if !runner.available() {
+        eprintln!("yosys no está instalado o no está en el PATH");
+        std::process::exit(1);
+    }
+
+    let mut filas = vec![];
+    for (nombre, rust, verilog) in find_designs(&raiz).expect("No se pudo leer el directorio") {
+        println!("Sintetizando {}...", nombre);
+        let sintetizar = |fichero: &PathBuf| match runner.synthesize(fichero) {
+            Ok(estadisticas) => Some(estadisticas),
+            Err(err) => {
+                eprintln!("  {}: {}", fichero.display(), err);
+                None
+            }
+        };
+        filas.push(AreaRow {
+            design: nombre,
+            rust: sintetizar(&rust),
+            verilog: verilog.as_ref().and_then(sintetizar),
+        });
+    }
+
+    println!("\n{}", area_table(&filas));
+
+    let historico = raiz.join("sintesis.csv");
+    let nuevo = !historico.exists();
+    let mut fichero = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&historico)
+        .expect("No se pudo abrir sintesis.csv");
+    if nuevo {
+        writeln!(fichero, "fecha,diseño,origen,celdas,ff,luts").expect("Error al escribir sintesis.csv");
+    }
+    fichero
+        .write_all(area_csv(&filas, SystemTime::now()).as_bytes())
+        .expect("Error al escribir sintesis.csv");
+    println!("Histórico actualizado en {}", historico.display());
+}
@@ -0,0 +1,176 @@
+use crate::mul::{Signedness, Unsigned};
+use rust_hdl::prelude::*;
+
+// Multiplicador de Booth de base 4.
+//
+// b se recorre en grupos de tres bits que se solapan en uno (b[2j+1], b[2j]
+// y b[2j-1], con b[-1] = 0), y cada grupo elige un producto parcial entre 0,
+// ±a y ±2a que se suma desplazado 2j posiciones. Así hay (W + 2) / 2
+// productos parciales en vez de los W de las sumas y desplazamientos.
+//
+// Booth trabaja en complemento a 2: sin signo b se extiende con ceros y el
+// último grupo lee el 0 de por encima de su bit de más peso, así que b
+// nunca es negativo. a se extiende con su signo (o con ceros) hasta O bits.
+#[derive(LogicBlock, Clone)]
+pub struct BoothMul<const W: usize, const O: usize, S: Signedness = Unsigned> {
+    pub a: Signal<In, Bits<W>>,
+    pub b: Signal<In, Bits<W>>,
+    pub resul: Signal<Out, Bits<O>>,
+    extendido: Signal<Local, Bits<O>>,
+    b_extendido: Signal<Local, Bits<O>>,
+    // b desplazado 2j bits, para leer el grupo j en los bits 0 y 1
+    ventana: [Signal<Local, Bits<O>>; W],
+    // b[2j-1]
+    anterior: [Signal<Local, Bit>; W],
+    parcial: [Signal<Local, Bits<O>>; W],
+    // i, para desplazar (Bits sólo se desplaza con literales o con Bits)
+    posiciones: [Constant<Bits<16>>; O],
+    // Unos por encima de los W bits de los operandos
+    extension: Constant<Bits<O>>,
+    // 1 en complemento a 2, 0 sin signo
+    con_signo: Constant<Bit>,
+    modo: S,
+}
+
+impl<const W: usize, const O: usize, S: Signedness> BoothMul<W, O, S> {
+    // Productos parciales
+    pub const PARTIAL_PRODUCTS: usize = (W + 2) / 2;
+}
+
+impl<const W: usize, const O: usize, S: Signedness> Default for BoothMul<W, O, S> {
+    fn default() -> Self {
+        const {
+            assert!(W >= 1, "W tiene que ser al menos 1");
+            assert!(O >= 2 * W, "el producto necesita al menos 2W bits");
+        }
+        Self {
+            a: Default::default(),
+            b: Default::default(),
+            resul: Default::default(),
+            extendido: Default::default(),
+            b_extendido: Default::default(),
+            ventana: std::array::from_fn(|_| Default::default()),
+            anterior: std::array::from_fn(|_| Default::default()),
+            parcial: std::array::from_fn(|_| Default::default()),
+            posiciones: std::array::from_fn(|i| Constant::new((i as u64).into())),
+            extension: Constant::new(!bit_cast::<O, W>(Bits::<W>::mask())),
+            con_signo: Constant::new(S::SIGNED),
+            modo: Default::default(),
+        }
+    }
+}
+
+impl<const W: usize, const O: usize, S: Signedness> Logic for BoothMul<W, O, S> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        // Extensión de a y b a O bits
+        // i = W - 1, el bit de signo: bucle de una vuelta, ver tfg_hdl_common
+        self.extendido.next = bit_cast::<O, W>(self.a.val());
+        self.b_extendido.next = bit_cast::<O, W>(self.b.val());
+        for i in (W - 1)..W {
+            if self.con_signo.val() & self.a.val().get_bit(i) {
+                self.extendido.next = bit_cast::<O, W>(self.a.val()) | self.extension.val();
+            }
+            if self.con_signo.val() & self.b.val().get_bit(i) {
+                self.b_extendido.next = bit_cast::<O, W>(self.b.val()) | self.extension.val();
+            }
+        }
+
+        // Grupos de b
+        for j in 0..((W + 2) / 2) {
+            self.ventana[j].next = self.b_extendido.val() >> self.posiciones[2 * j].val();
+        }
+        self.anterior[0].next = false;
+        for j in 1..((W + 2) / 2) {
+            self.anterior[j].next = self.ventana[j - 1].val().get_bit(1);
+        }
+
+        // Producto parcial de cada grupo: ±a si b[2j] != b[2j-1], ±2a si son
+        // iguales pero distintos de b[2j+1], y 0 si no. El signo lo da
+        // b[2j+1]
+        for j in 0..((W + 2) / 2) {
+            self.parcial[j].next = 0.into();
+            if self.ventana[j].val().get_bit(0) ^ self.anterior[j].val() {
+                self.parcial[j].next = self.extendido.val();
+            } else if self.ventana[j].val().get_bit(1) ^ self.ventana[j].val().get_bit(0) {
+                self.parcial[j].next = self.extendido.val() << 1;
+            }
+            if self.ventana[j].val().get_bit(1) {
+                self.parcial[j].next = !self.parcial[j].val() + 1;
+            }
+        }
+        // Los que sobran del array no se usan
+        for j in ((W + 2) / 2)..W {
+            self.ventana[j].next = 0.into();
+            self.anterior[j].next = false;
+            self.parcial[j].next = 0.into();
+        }
+
+        self.resul.next = 0.into();
+        for j in 0..((W + 2) / 2) {
+            self.resul.next = self.resul.val() + (self.parcial[j].val() << self.posiciones[2 * j].val());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mul::TwosComplement;
+
+    // Todos los pares de operandos de W bits, con y sin signo (en la
+    // simulación de RustHDL; con Icarus lo prueba mul.rs)
+    fn todos_los_pares<const W: usize, const O: usize, S: Signedness>() {
+        let mut uut = BoothMul::<W, O, S>::default();
+        uut.connect_all();
+
+        let mut sim = Simulation::<BoothMul<W, O, S>>::new();
+        sim.add_testbench(move |mut ep: Sim<BoothMul<W, O, S>>| {
+            let mut x = ep.init()?;
+            let (desde, hasta) = if S::SIGNED {
+                (-(1 << (W - 1)), 1 << (W - 1))
+            } else {
+                (0, 1 << W)
+            };
+            for va in desde..hasta {
+                for vb in desde..hasta {
+                    x.a.next = Bits::from(va as u64 & ((1 << W) - 1));
+                    x.b.next = Bits::from(vb as u64 & ((1 << W) - 1));
+                    x = ep.wait(1, x)?;
+                    sim_assert_eq!(ep, x.resul.val(), Bits::<O>::from((va * vb) as u64 & ((1 << O) - 1)), x);
+                }
+            }
+            ep.done(x)
+        });
+        sim.run(Box::new(uut), 1_000_000).unwrap();
+    }
+
+    #[test]
+    fn test_booth_todas_las_anchuras_pequenas() {
+        todos_los_pares::<1, 2, Unsigned>();
+        todos_los_pares::<1, 2, TwosComplement>();
+        todos_los_pares::<2, 4, Unsigned>();
+        todos_los_pares::<2, 4, TwosComplement>();
+        todos_los_pares::<3, 6, Unsigned>();
+        todos_los_pares::<3, 6, TwosComplement>();
+        todos_los_pares::<5, 10, Unsigned>();
+        todos_los_pares::<5, 10, TwosComplement>();
+        todos_los_pares::<6, 13, TwosComplement>();
+    }
+
+    #[test]
+    fn test_verilog_booth() {
+        assert_eq!(BoothMul::<8, 16>::PARTIAL_PRODUCTS, 5);
+        let mut uut = BoothMul::<8, 16, TwosComplement>::default();
+        uut.connect_all();
+        let verilog = generate_verilog(&uut);
+        // Cinco productos parciales desplazados 0, 2, 4, 6 y 8 bits
+        assert!(
+            verilog.contains("resul = resul + (parcial$4 << posiciones$8);"),
+            "{}",
+            verilog
+        );
+        assert!(!verilog.contains("parcial$5 << "));
+        assert!(verilog.contains("anterior$1 = ventana$0[32'h1];"));
+    }
+}
@@ -0,0 +1,361 @@
+// Co-simulación: el mismo TestVectors en RustHDL y en Icarus, ciclo a ciclo.
+//
+// Los test imprimían la salida de vvp al lado de la de RustHDL pero nadie las
+// comparaba, así que si el Verilog generado no se comportaba como el modelo
+// de Rust no se enteraba nadie. Aquí las dos simulaciones vuelcan una traza
+// con el valor de todos los puertos después de cada flanco (en RustHDL desde
+// el testbench y en Icarus con $fwrite) y se comparan fila a fila. En la
+// primera diferencia se devuelve un informe con el ciclo, el caso y los
+// puertos que no coinciden.
+use crate::icarus::{IcarusError, IcarusRunner};
+use crate::testbench::{TestVectors, TestbenchError};
+use crate::verilog::{generate_named_verilog, VerilogError};
+use rust_hdl::prelude::*;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+// Fichero en el que el testbench Verilog escribe la traza
+const TRAZA: &str = "traza.txt";
+
+// Ciclos anteriores a la diferencia que se enseñan en el informe
+const CONTEXTO: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub cycle: usize,
+    pub case: Option<String>,
+    // Puertos que no coinciden: (nombre, valor en RustHDL, valor en Icarus)
+    pub ports: Vec<(String, String, String)>,
+    // Informe completo, con los ciclos anteriores a la diferencia
+    pub report: String,
+}
+
+#[derive(Debug)]
+pub enum CosimError {
+    Verilog(VerilogError),
+    Icarus(IcarusError),
+    Simulation(SimError),
+    // La traza de vvp no existe o no tiene el formato esperado
+    Trace(String),
+    Divergence(Box<Divergence>),
+    // El testbench Verilog ha encontrado salidas que no coinciden
+    Testbench(TestbenchError),
+}
+
+impl fmt::Display for CosimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CosimError::Verilog(err) => write!(f, "{}", err),
+            CosimError::Icarus(err) => write!(f, "{}", err),
+            CosimError::Simulation(err) => write!(f, "la simulación de RustHDL ha fallado: {:?}", err),
+            CosimError::Trace(err) => write!(f, "traza de Icarus no válida: {}", err),
+            CosimError::Divergence(div) => write!(f, "{}", div.report),
+            CosimError::Testbench(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for CosimError {}
+
+impl From<VerilogError> for CosimError {
+    fn from(err: VerilogError) -> Self {
+        CosimError::Verilog(err)
+    }
+}
+
+impl From<IcarusError> for CosimError {
+    fn from(err: IcarusError) -> Self {
+        CosimError::Icarus(err)
+    }
+}
+
+impl From<SimError> for CosimError {
+    fn from(err: SimError) -> Self {
+        CosimError::Simulation(err)
+    }
+}
+
+impl From<TestbenchError> for CosimError {
+    fn from(err: TestbenchError) -> Self {
+        CosimError::Testbench(err)
+    }
+}
+
+// Resultado de una co-simulación sin diferencias
+#[derive(Debug, Clone)]
+pub struct CosimReport {
+    pub cycles: usize,
+    // Salida de vvp, con las líneas ERROR/RESULTADO del testbench
+    pub stdout: String,
+}
+
+impl fmt::Display for CosimReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RustHDL e Icarus coinciden en los {} ciclos", self.cycles)
+    }
+}
+
+impl<T: Block + Clone + Send + 'static> TestVectors<T> {
+    // Simula los casos en RustHDL y en Icarus y compara las trazas. Si Icarus
+    // no está instalado devuelve Ok(None), como IcarusRunner::run_if_available
+    pub fn cosimulate(&self, uut: &T, runner: &IcarusRunner) -> Result<Option<CosimReport>, CosimError> {
+        let mut uut = uut.clone();
+        uut.connect_all();
+        let fuente = format!(
+            "{}{}",
+            self.testbench_verilog(Some(TRAZA)),
+            generate_named_verilog(&uut, self.nombre_modulo())?
+        );
+        let salida = match runner.run_if_available(&format!("cosim_{}", self.nombre_modulo()), &fuente)? {
+            Some(salida) => salida,
+            None => return Ok(None),
+        };
+        let traza_icarus = std::fs::read_to_string(salida.dir().join(TRAZA))
+            .map_err(|err| CosimError::Trace(format!("no se puede leer {}: {}", TRAZA, err)))?;
+
+        let traza_rust = self.traza_rust(uut)?;
+        let traza_icarus: Vec<String> = traza_icarus.lines().map(String::from).collect();
+        self.comparar_trazas(&traza_rust, &traza_icarus)?;
+        Ok(Some(CosimReport {
+            cycles: traza_rust.len(),
+            stdout: salida.stdout.clone(),
+        }))
+    }
+
+    // Lo que hacen los test de los diseños: co-simulación con Icarus (si está
+    // instalado) y comprobación de su testbench, y después la simulación de
+    // RustHDL con el testbench de add_to durante `max_time`
+    pub fn verify(&self, uut: &T, max_time: u64) -> Result<Option<CosimReport>, CosimError> {
+        let informe = self.cosimulate(uut, &IcarusRunner::new())?;
+        if let Some(informe) = &informe {
+            self.check_verilog_output(&informe.stdout)?;
+        }
+        let mut uut = uut.clone();
+        uut.connect_all();
+        let mut sim = Simulation::<T>::new();
+        self.add_to(&mut sim);
+        sim.run(Box::new(uut), max_time)?;
+        Ok(informe)
+    }
+
+    // Traza de la simulación de RustHDL
+    fn traza_rust(&self, uut: T) -> Result<Vec<String>, SimError> {
+        let traza = Arc::new(Mutex::new(vec![]));
+        let mut sim = Simulation::<T>::new();
+        let tb = self.clone();
+        let destino = traza.clone();
+        sim.add_testbench(move |ep: Sim<T>| tb.banco(ep, Some(destino.clone())));
+        sim.run(Box::new(uut), 2 * self.num_ciclos() as u64 + 10)?;
+        let filas = traza.lock().unwrap().clone();
+        Ok(filas)
+    }
+}
+
+impl<T: Block + 'static> TestVectors<T> {
+    // Compara las dos trazas y devuelve la primera diferencia
+    pub(crate) fn comparar_trazas(&self, rust: &[String], icarus: &[String]) -> Result<(), CosimError> {
+        let columnas = self.columnas_traza();
+        for ciclo in 0..rust.len().max(icarus.len()) {
+            let fila_rust = rust.get(ciclo).map(|f| valores(f)).transpose()?;
+            let fila_icarus = icarus.get(ciclo).map(|f| valores(f)).transpose()?;
+            let puertos: Vec<(String, String, String)> = match (&fila_rust, &fila_icarus) {
+                (Some((n_rust, v_rust)), Some((n_icarus, v_icarus))) => {
+                    if *n_rust != ciclo || *n_icarus != ciclo || v_icarus.len() != columnas.len() {
+                        return Err(CosimError::Trace(format!(
+                            "fila {} desalineada: RustHDL \"{}\", Icarus \"{}\"",
+                            ciclo, rust[ciclo], icarus[ciclo]
+                        )));
+                    }
+                    columnas
+                        .iter()
+                        .zip(v_rust.iter().zip(v_icarus))
+                        .filter(|(_, (r, i))| r != i)
+                        .map(|(nombre, (r, i))| (nombre.clone(), r.clone(), i.clone()))
+                        .collect()
+                }
+                // Una de las dos simulaciones ha terminado antes
+                (Some(_), None) => vec![("(fin)".into(), "-".into(), "sin datos".into())],
+                (None, Some(_)) => vec![("(fin)".into(), "sin datos".into(), "-".into())],
+                (None, None) => unreachable!(),
+            };
+            if !puertos.is_empty() {
+                let report = self.informe(ciclo, &puertos, rust, icarus);
+                return Err(CosimError::Divergence(Box::new(Divergence {
+                    cycle: ciclo,
+                    case: self.caso_del_ciclo(ciclo),
+                    ports: puertos,
+                    report,
+                })));
+            }
+        }
+        Ok(())
+    }
+
+    fn informe(&self, ciclo: usize, puertos: &[(String, String, String)], rust: &[String], icarus: &[String]) -> String {
+        let mut texto = format!(
+            "RustHDL e Icarus divergen en el ciclo {} (caso \"{}\")\n",
+            ciclo,
+            self.caso_del_ciclo(ciclo).as_deref().unwrap_or("-")
+        );
+        for (nombre, r, i) in puertos {
+            texto += &format!("  {}: RustHDL = {}, Icarus = {}\n", nombre, r, i);
+        }
+        texto += &format!("Ciclos anteriores (ciclo {}):\n", self.columnas_traza().join(" "));
+        for n in ciclo.saturating_sub(CONTEXTO)..=ciclo {
+            texto += &format!(
+                "  RustHDL: {}\n  Icarus:  {}\n",
+                rust.get(n).map(String::as_str).unwrap_or("-"),
+                icarus.get(n).map(String::as_str).unwrap_or("-")
+            );
+        }
+        texto
+    }
+}
+
+// Separa una fila de la traza en número de ciclo y valores
+fn valores(fila: &str) -> Result<(usize, Vec<String>), CosimError> {
+    let mut partes = fila.split_whitespace();
+    let ciclo = partes
+        .next()
+        .and_then(|n| n.parse().ok())
+        .ok_or_else(|| CosimError::Trace(format!("fila sin número de ciclo: \"{}\"", fila)))?;
+    Ok((ciclo, partes.map(String::from).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testbench::TestVector;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+
+    #[derive(LogicBlock, Default, Clone)]
+    struct Contador {
+        pub clk: Signal<In, Clock>,
+        pub en: Signal<In, Bit>,
+        pub cuenta: Signal<Out, Bits<4>>,
+        registro: DFF<Bits<4>>,
+    }
+
+    impl Logic for Contador {
+        #[hdl_gen]
+        fn update(&mut self) {
+            self.registro.clock.next = self.clk.val();
+            self.registro.d.next = self.registro.q.val();
+            if self.en.val() {
+                self.registro.d.next = self.registro.q.val() + 1;
+            }
+            self.cuenta.next = self.registro.q.val();
+        }
+    }
+
+    fn vectores() -> TestVectors<Contador> {
+        let mut tb = TestVectors::<Contador>::new("Contador").clock("clk", |x| &mut x.clk);
+        let en = tb.input("en", |x| &mut x.en);
+        let cuenta = tb.output("cuenta", |x| &x.cuenta);
+        for n in 1..=4 {
+            tb.push(TestVector::new(&format!("cuenta {}", n)).set(en, 1).expect(cuenta, n));
+        }
+        tb.push(TestVector::new("parado").set(en, 0).expect(cuenta, 4));
+        tb
+    }
+
+    // Traza de RustHDL para los vectores anteriores
+    fn traza_esperada() -> Vec<String> {
+        ["0 1 0001", "1 1 0010", "2 1 0011", "3 1 0100", "4 0 0100"]
+            .iter()
+            .map(|f| f.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_traza_rust() {
+        let tb = vectores();
+        assert_eq!(tb.traza_rust(Contador::default()).unwrap(), traza_esperada());
+    }
+
+    #[test]
+    fn test_trazas_iguales() {
+        let tb = vectores();
+        assert!(tb.comparar_trazas(&traza_esperada(), &traza_esperada()).is_ok());
+    }
+
+    #[test]
+    fn test_primera_divergencia() {
+        let tb = vectores();
+        let mut icarus = traza_esperada();
+        icarus[2] = "2 1 001x".into();
+        icarus[3] = "3 1 0000".into();
+        match tb.comparar_trazas(&traza_esperada(), &icarus) {
+            Err(CosimError::Divergence(div)) => {
+                assert_eq!(div.cycle, 2);
+                assert_eq!(div.case.as_deref(), Some("cuenta 3"));
+                assert_eq!(div.ports, vec![("cuenta".into(), "0011".into(), "001x".into())]);
+                assert!(div.report.contains("divergen en el ciclo 2"));
+                assert!(div.report.contains("Icarus:  1 1 0010"));
+            }
+            otro => panic!("se esperaba Divergence, llega {:?}", otro),
+        }
+    }
+
+    #[test]
+    fn test_traza_incompleta() {
+        let tb = vectores();
+        let icarus = traza_esperada()[..3].to_vec();
+        match tb.comparar_trazas(&traza_esperada(), &icarus) {
+            Err(CosimError::Divergence(div)) => assert_eq!(div.cycle, 3),
+            otro => panic!("se esperaba Divergence, llega {:?}", otro),
+        }
+    }
+
+    // Icarus de mentira: iverilog no hace nada y vvp escribe la traza que se
+    // le pasa, para probar la co-simulación completa sin tener Icarus
+    fn icarus_falso(dir: &Path, traza: &[String]) -> IcarusRunner {
+        std::fs::create_dir_all(dir).unwrap();
+        let iverilog = dir.join("iverilog");
+        let vvp = dir.join("vvp");
+        std::fs::write(&iverilog, "#!/bin/sh\nexit 0\n").unwrap();
+        std::fs::write(
+            &vvp,
+            format!("#!/bin/sh\nprintf '{}' > {}\necho 'RESULTADO: OK'\n", traza.join("\\n") + "\\n", TRAZA),
+        )
+        .unwrap();
+        for script in [&iverilog, &vvp] {
+            std::fs::set_permissions(script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        IcarusRunner::new().tools(iverilog.to_str().unwrap(), vvp.to_str().unwrap())
+    }
+
+    #[test]
+    fn test_cosimulacion_con_icarus_falso() {
+        let dir = std::env::temp_dir().join(format!("cosim-falso-{}", std::process::id()));
+        let tb = vectores();
+
+        let runner = icarus_falso(&dir.join("bien"), &traza_esperada());
+        let informe = tb.cosimulate(&Contador::default(), &runner).unwrap().unwrap();
+        assert_eq!(informe.cycles, 5);
+        assert!(informe.stdout.contains("RESULTADO: OK"));
+
+        let mut mala = traza_esperada();
+        mala[4] = "4 0 0101".into();
+        let runner = icarus_falso(&dir.join("mal"), &mala);
+        match tb.cosimulate(&Contador::default(), &runner) {
+            Err(CosimError::Divergence(div)) => assert_eq!(div.cycle, 4),
+            otro => panic!("se esperaba Divergence, llega {:?}", otro),
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_traza_en_el_testbench_verilog() {
+        let codigo = vectores().testbench_verilog(Some(TRAZA));
+        assert!(codigo.contains("traza = $fopen(\"traza.txt\", \"w\");"));
+        assert!(codigo.contains("$fwrite(traza, \"0 %b %b\\n\", en, cuenta);"));
+        assert!(codigo.contains("$fclose(traza);"));
+    }
+
+    #[test]
+    fn test_con_icarus() {
+        vectores().verify(&Contador::default(), 1_000).unwrap();
+    }
+}
@@ -0,0 +1,372 @@
+// Flip-flops activos por flanco de subida usados en todos los diseños.
+//
+// EdgeDFF es el flip-flop que antes estaba copiado en cada crate. Las
+// variantes añaden reset síncrono, reset asíncrono o habilitación de reloj,
+// cada una con su Verilog (`hdl()`) y su información de temporización
+// (`timing()`) para que el check de RustHDL las trate como registros.
+use rust_hdl::prelude::*;
+use rust_hdl_core::timing::TimingInfo;
+
+// Flip-flop D básico: q <= d en cada flanco de subida de clk
+#[derive(Clone, Debug, LogicBlock, Default)]
+pub struct EdgeDFF<T: Synth> {
+    pub d: Signal<In, T>,
+    pub q: Signal<Out, T>,
+    pub clk: Signal<In, Clock>,
+}
+
+impl<T: Synth> EdgeDFF<T> {
+    pub fn new(init: T) -> EdgeDFF<T> {
+        Self {
+            d: Signal::default(),
+            q: Signal::new_with_default(init),
+            clk: Signal::default(),
+        }
+    }
+}
+
+impl<T: Synth> Logic for EdgeDFF<T> {
+    fn update(&mut self) {
+        if self.clk.pos_edge() {
+            self.q.next = self.d.val()
+        }
+    }
+    fn connect(&mut self) {
+        self.q.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(format!(
+            "\
+initial begin
+   q = {:x};
+end
+
+always @(posedge clk) q <= d;",
+            self.q.verilog()
+        ))
+    }
+    fn timing(&self) -> Vec<TimingInfo> {
+        vec![TimingInfo {
+            name: "edge_ff".to_string(),
+            clock: "clk".to_string(),
+            inputs: vec!["d".into()],
+            outputs: vec!["q".into()],
+        }]
+    }
+}
+
+// Flip-flop D con reset síncrono activo a nivel alto: si rst está a 1 en el
+// flanco de subida, q vuelve al valor inicial
+#[derive(Clone, Debug, LogicBlock)]
+pub struct EdgeDFFSyncReset<T: Synth> {
+    pub d: Signal<In, T>,
+    pub q: Signal<Out, T>,
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    // Valor inicial y de reset, que en el Verilog es el localparam `init`
+    init: Constant<T>,
+}
+
+impl<T: Synth> Default for EdgeDFFSyncReset<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Synth> EdgeDFFSyncReset<T> {
+    pub fn new(init: T) -> EdgeDFFSyncReset<T> {
+        Self {
+            d: Signal::default(),
+            q: Signal::new_with_default(init),
+            clk: Signal::default(),
+            rst: Signal::default(),
+            init: Constant::new(init),
+        }
+    }
+}
+
+impl<T: Synth> Logic for EdgeDFFSyncReset<T> {
+    fn update(&mut self) {
+        if self.clk.pos_edge() {
+            if self.rst.val() {
+                self.q.next = self.init.val();
+            } else {
+                self.q.next = self.d.val();
+            }
+        }
+    }
+    fn connect(&mut self) {
+        self.q.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(
+            "\
+initial begin
+   q = init;
+end
+
+always @(posedge clk) begin
+   if (rst) q <= init;
+   else q <= d;
+end"
+            .into(),
+        )
+    }
+    fn timing(&self) -> Vec<TimingInfo> {
+        vec![TimingInfo {
+            name: "edge_ff_sync_reset".to_string(),
+            clock: "clk".to_string(),
+            inputs: vec!["d".into(), "rst".into()],
+            outputs: vec!["q".into()],
+        }]
+    }
+}
+
+// Flip-flop D con reset asíncrono activo a nivel alto: mientras rst está a 1,
+// q se mantiene en el valor inicial sin esperar al reloj
+#[derive(Clone, Debug, LogicBlock)]
+pub struct EdgeDFFAsyncReset<T: Synth> {
+    pub d: Signal<In, T>,
+    pub q: Signal<Out, T>,
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    // Valor inicial y de reset, que en el Verilog es el localparam `init`
+    init: Constant<T>,
+}
+
+impl<T: Synth> Default for EdgeDFFAsyncReset<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Synth> EdgeDFFAsyncReset<T> {
+    pub fn new(init: T) -> EdgeDFFAsyncReset<T> {
+        Self {
+            d: Signal::default(),
+            q: Signal::new_with_default(init),
+            clk: Signal::default(),
+            rst: Signal::default(),
+            init: Constant::new(init),
+        }
+    }
+}
+
+impl<T: Synth> Logic for EdgeDFFAsyncReset<T> {
+    fn update(&mut self) {
+        if self.rst.val() {
+            self.q.next = self.init.val();
+        } else if self.clk.pos_edge() {
+            self.q.next = self.d.val();
+        }
+    }
+    fn connect(&mut self) {
+        self.q.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(
+            "\
+initial begin
+   q = init;
+end
+
+always @(posedge clk or posedge rst) begin
+   if (rst) q <= init;
+   else q <= d;
+end"
+            .into(),
+        )
+    }
+    fn timing(&self) -> Vec<TimingInfo> {
+        vec![TimingInfo {
+            name: "edge_ff_async_reset".to_string(),
+            clock: "clk".to_string(),
+            inputs: vec!["d".into(), "rst".into()],
+            outputs: vec!["q".into()],
+        }]
+    }
+}
+
+// Flip-flop D con habilitación de reloj: q solo se actualiza en los flancos
+// en los que en está a 1
+#[derive(Clone, Debug, LogicBlock, Default)]
+pub struct EdgeDFFEnable<T: Synth> {
+    pub d: Signal<In, T>,
+    pub q: Signal<Out, T>,
+    pub clk: Signal<In, Clock>,
+    pub en: Signal<In, Bit>,
+}
+
+impl<T: Synth> EdgeDFFEnable<T> {
+    pub fn new(init: T) -> EdgeDFFEnable<T> {
+        Self {
+            d: Signal::default(),
+            q: Signal::new_with_default(init),
+            clk: Signal::default(),
+            en: Signal::default(),
+        }
+    }
+}
+
+impl<T: Synth> Logic for EdgeDFFEnable<T> {
+    fn update(&mut self) {
+        if self.clk.pos_edge() && self.en.val() {
+            self.q.next = self.d.val()
+        }
+    }
+    fn connect(&mut self) {
+        self.q.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(format!(
+            "\
+initial begin
+   q = {:x};
+end
+
+always @(posedge clk) if (en) q <= d;",
+            self.q.verilog()
+        ))
+    }
+    fn timing(&self) -> Vec<TimingInfo> {
+        vec![TimingInfo {
+            name: "edge_ff_enable".to_string(),
+            clock: "clk".to_string(),
+            inputs: vec!["d".into(), "en".into()],
+            outputs: vec!["q".into()],
+        }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Bloque de prueba con un registro de cada tipo colgando de las mismas
+    // entradas
+    #[derive(LogicBlock, Clone)]
+    struct Registros {
+        pub clk: Signal<In, Clock>,
+        pub rst: Signal<In, Bit>,
+        pub en: Signal<In, Bit>,
+        pub d: Signal<In, Bits<4>>,
+        pub q_simple: Signal<Out, Bits<4>>,
+        pub q_sync: Signal<Out, Bits<4>>,
+        pub q_async: Signal<Out, Bits<4>>,
+        pub q_en: Signal<Out, Bits<4>>,
+        simple: EdgeDFF<Bits<4>>,
+        sync: EdgeDFFSyncReset<Bits<4>>,
+        asyn: EdgeDFFAsyncReset<Bits<4>>,
+        enable: EdgeDFFEnable<Bits<4>>,
+    }
+
+    impl Default for Registros {
+        fn default() -> Self {
+            Self {
+                clk: Default::default(),
+                rst: Default::default(),
+                en: Default::default(),
+                d: Default::default(),
+                q_simple: Default::default(),
+                q_sync: Default::default(),
+                q_async: Default::default(),
+                q_en: Default::default(),
+                simple: EdgeDFF::new(5.into()),
+                sync: EdgeDFFSyncReset::new(5.into()),
+                asyn: EdgeDFFAsyncReset::new(5.into()),
+                enable: EdgeDFFEnable::new(5.into()),
+            }
+        }
+    }
+
+    impl Logic for Registros {
+        #[hdl_gen]
+        fn update(&mut self) {
+            self.simple.clk.next = self.clk.val();
+            self.sync.clk.next = self.clk.val();
+            self.asyn.clk.next = self.clk.val();
+            self.enable.clk.next = self.clk.val();
+
+            self.sync.rst.next = self.rst.val();
+            self.asyn.rst.next = self.rst.val();
+            self.enable.en.next = self.en.val();
+
+            self.simple.d.next = self.d.val();
+            self.sync.d.next = self.d.val();
+            self.asyn.d.next = self.d.val();
+            self.enable.d.next = self.d.val();
+
+            self.q_simple.next = self.simple.q.val();
+            self.q_sync.next = self.sync.q.val();
+            self.q_async.next = self.asyn.q.val();
+            self.q_en.next = self.enable.q.val();
+        }
+    }
+
+    #[test]
+    fn test_variantes_edge_dff() {
+        let mut uut = Registros::default();
+        uut.connect_all();
+
+        let verilog = generate_verilog(&uut);
+        assert!(verilog.contains("always @(posedge clk) q <= d;"));
+        assert!(verilog.contains("always @(posedge clk or posedge rst) begin"));
+        // El valor de reset es el localparam init de cada registro
+        assert!(verilog.contains("localparam  init = 4'h5;"), "{}", verilog);
+        assert!(verilog.contains("if (rst) q <= init;"));
+        assert!(verilog.contains("always @(posedge clk) if (en) q <= d;"));
+
+        let mut sim = Simulation::<Registros>::new();
+        sim.add_testbench(move |mut ep| {
+            let mut x = ep.init()?;
+
+            // Flanco con d = 3, sin reset y con habilitación
+            x.d.next = 3.into();
+            x.en.next = true;
+            x.clk.next = Clock { clk: true };
+            x = ep.wait(1, x)?;
+            sim_assert_eq!(ep, x.q_simple.val(), Bits::<4>::from(3), x);
+            sim_assert_eq!(ep, x.q_sync.val(), Bits::<4>::from(3), x);
+            sim_assert_eq!(ep, x.q_async.val(), Bits::<4>::from(3), x);
+            sim_assert_eq!(ep, x.q_en.val(), Bits::<4>::from(3), x);
+
+            // Sin habilitación el registro con enable mantiene el valor
+            x.d.next = 9.into();
+            x.en.next = false;
+            x.clk.next = Clock { clk: false };
+            x = ep.wait(1, x)?;
+            x.clk.next = Clock { clk: true };
+            x = ep.wait(1, x)?;
+            sim_assert_eq!(ep, x.q_simple.val(), Bits::<4>::from(9), x);
+            sim_assert_eq!(ep, x.q_en.val(), Bits::<4>::from(3), x);
+
+            // El reset asíncrono actúa sin flanco, el síncrono espera al reloj
+            x.rst.next = true;
+            x.clk.next = Clock { clk: false };
+            x = ep.wait(1, x)?;
+            sim_assert_eq!(ep, x.q_async.val(), Bits::<4>::from(5), x);
+            sim_assert_eq!(ep, x.q_sync.val(), Bits::<4>::from(9), x);
+            x.clk.next = Clock { clk: true };
+            x = ep.wait(1, x)?;
+            sim_assert_eq!(ep, x.q_sync.val(), Bits::<4>::from(5), x);
+            sim_assert_eq!(ep, x.q_simple.val(), Bits::<4>::from(9), x);
+
+            ep.done(x)
+        });
+
+        let mut uut = Registros::default();
+        uut.connect_all();
+        sim.run(Box::new(uut), 100).unwrap();
+    }
+
+    // Las entradas de control también cuentan como entradas del registro
+    #[test]
+    fn test_timing_edge_dff() {
+        let uut = Registros::default();
+        let entradas = |timing: Vec<TimingInfo>| timing[0].inputs.clone();
+        assert_eq!(entradas(uut.simple.timing()), ["d"]);
+        assert_eq!(entradas(uut.sync.timing()), ["d", "rst"]);
+        assert_eq!(entradas(uut.asyn.timing()), ["d", "rst"]);
+        assert_eq!(entradas(uut.enable.timing()), ["d", "en"]);
+    }
+}
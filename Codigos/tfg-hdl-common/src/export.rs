@@ -0,0 +1,183 @@
+// Exportación del Verilog desde el main de los diseños.
+//
+// Los main de los diseños con arrays de puertos hacían lo mismo: escribir
+// <nombre>Rust.v con el Verilog de RustHDL o, si se les pasa "packed" o
+// "flat", con el envoltorio de generate_verilog_ports y el mapa de puertos en
+// <nombre>Rust.ports. Aquí está una sola vez.
+//
+//     cargo run -p multiplicacionMatrices            # puertos a$0, a$1...
+//     cargo run -p multiplicacionMatrices -- packed  # a[15:0]
+//     cargo run -p multiplicacionMatrices -- flat    # a_0, a_1...
+use crate::ports::{generate_verilog_ports_with_header, PortStyle};
+use crate::verilog::{generate_named_verilog_with_header, VerilogError, VerilogHeader};
+use rust_hdl::prelude::*;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum ExportError {
+    // El argumento no es "packed" ni "flat"
+    UnknownStyle(String),
+    // No se ha podido generar el Verilog
+    Verilog(VerilogError),
+    // No se ha podido escribir un fichero
+    Io(io::Error),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::UnknownStyle(estilo) => {
+                write!(f, "estilo de puertos \"{}\" desconocido (tiene que ser packed o flat)", estilo)
+            }
+            ExportError::Verilog(err) => write!(f, "no se pudo generar el Verilog: {}", err),
+            ExportError::Io(err) => write!(f, "error al escribir el Verilog: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExportError::Verilog(err) => Some(err),
+            ExportError::Io(err) => Some(err),
+            ExportError::UnknownStyle(_) => None,
+        }
+    }
+}
+
+impl From<VerilogError> for ExportError {
+    fn from(err: VerilogError) -> Self {
+        ExportError::Verilog(err)
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(err: io::Error) -> Self {
+        ExportError::Io(err)
+    }
+}
+
+// Estilo de los puertos según el argumento del main: sin argumento, los de
+// RustHDL
+pub fn port_style_from_arg(arg: Option<&str>) -> Result<Option<PortStyle>, ExportError> {
+    match arg {
+        None => Ok(None),
+        Some("packed") => Ok(Some(PortStyle::Packed)),
+        Some("flat") => Ok(Some(PortStyle::Flat)),
+        Some(otro) => Err(ExportError::UnknownStyle(otro.into())),
+    }
+}
+
+// Escribe en `directorio` <nombre>Rust.v y, si hay estilo, <nombre>Rust.ports.
+// Devuelve los ficheros escritos
+pub fn export_verilog<T: Block>(
+    uut: &T,
+    nombre: &str,
+    cabecera: &VerilogHeader,
+    estilo: Option<PortStyle>,
+    directorio: &Path,
+) -> Result<Vec<PathBuf>, ExportError> {
+    export_verilog_as(uut, nombre, nombre, cabecera, estilo, directorio)
+}
+
+// Como export_verilog, pero con el módulo `nombre` en <fichero>Rust.v, para
+// los diseños cuyo fichero no se llama como el módulo
+pub fn export_verilog_as<T: Block>(
+    uut: &T,
+    nombre: &str,
+    fichero: &str,
+    cabecera: &VerilogHeader,
+    estilo: Option<PortStyle>,
+    directorio: &Path,
+) -> Result<Vec<PathBuf>, ExportError> {
+    let verilog = directorio.join(format!("{}Rust.v", fichero));
+    let mut ficheros = vec![verilog.clone()];
+    let codigo = match estilo {
+        Some(estilo) => {
+            let exportado = generate_verilog_ports_with_header(uut, nombre, estilo, cabecera)?;
+            let mapa = directorio.join(format!("{}Rust.ports", fichero));
+            std::fs::write(&mapa, exportado.port_map)?;
+            ficheros.push(mapa);
+            exportado.verilog
+        }
+        None => generate_named_verilog_with_header(uut, nombre, cabecera)?,
+    };
+    std::fs::write(&verilog, codigo)?;
+    Ok(ficheros)
+}
+
+// Lo que hace el main de un diseño: exporta `uut` en el directorio actual con
+// el estilo del primer argumento y dice qué ficheros ha escrito
+pub fn export_verilog_from_args<T: Block>(uut: &T, nombre: &str, cabecera: &VerilogHeader) -> Result<(), ExportError> {
+    export_verilog_from_args_as(uut, nombre, nombre, cabecera)
+}
+
+// export_verilog_from_args con el módulo `nombre` en <fichero>Rust.v
+pub fn export_verilog_from_args_as<T: Block>(
+    uut: &T,
+    nombre: &str,
+    fichero: &str,
+    cabecera: &VerilogHeader,
+) -> Result<(), ExportError> {
+    let arg = std::env::args().nth(1);
+    let estilo = port_style_from_arg(arg.as_deref())?;
+    for fichero in export_verilog_as(uut, nombre, fichero, cabecera, estilo, Path::new("."))? {
+        println!("Guardado {}", fichero.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(LogicBlock, Default)]
+    struct Doble {
+        pub a: [Signal<In, Bits<4>>; 2],
+        pub result: [Signal<Out, Bits<4>>; 2],
+    }
+
+    impl Logic for Doble {
+        #[hdl_gen]
+        fn update(&mut self) {
+            self.result[0].next = self.a[0].val();
+            self.result[1].next = self.a[1].val();
+        }
+    }
+
+    #[test]
+    fn test_estilo_del_argumento() {
+        assert_eq!(port_style_from_arg(None).unwrap(), None);
+        assert_eq!(port_style_from_arg(Some("packed")).unwrap(), Some(PortStyle::Packed));
+        assert_eq!(port_style_from_arg(Some("flat")).unwrap(), Some(PortStyle::Flat));
+        assert!(matches!(port_style_from_arg(Some("plano")), Err(ExportError::UnknownStyle(_))));
+    }
+
+    #[test]
+    fn test_ficheros_exportados() {
+        let dir = std::env::temp_dir().join(format!("export-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut uut = Doble::default();
+        uut.connect_all();
+        let cabecera = VerilogHeader::new("prueba", "0.1.0");
+
+        let ficheros = export_verilog(&uut, "Doble", &cabecera, None, &dir).unwrap();
+        assert_eq!(ficheros, vec![dir.join("DobleRust.v")]);
+        let codigo = std::fs::read_to_string(&ficheros[0]).unwrap();
+        assert!(codigo.contains("module Doble("), "{}", codigo);
+        assert!(codigo.contains("a$1"), "{}", codigo);
+
+        let ficheros = export_verilog(&uut, "Doble", &cabecera, Some(PortStyle::Packed), &dir).unwrap();
+        assert_eq!(ficheros, vec![dir.join("DobleRust.v"), dir.join("DobleRust.ports")]);
+        let codigo = std::fs::read_to_string(&ficheros[0]).unwrap();
+        assert!(codigo.contains("input wire [7:0] a"), "{}", codigo);
+        assert!(std::fs::read_to_string(&ficheros[1]).unwrap().contains("result"));
+
+        let ficheros = export_verilog_as(&uut, "Doble", "doble", &cabecera, None, &dir).unwrap();
+        assert_eq!(ficheros, vec![dir.join("dobleRust.v")]);
+        assert!(std::fs::read_to_string(&ficheros[0]).unwrap().contains("module Doble("));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,503 @@
+// Extracción de la máquina de estados de un diseño y diagrama en Graphviz.
+//
+// Los estados de los diseños (por ejemplo State en ReconocedorPatrones) se
+// documentaban dibujando el diagrama a mano. StateMachine::extract lo saca
+// del propio diseño: los estados, del enum con #[derive(LogicState)] que
+// guarda el registro; las transiciones, del Verilog que genera #[hdl_gen]
+// (antes de escribirlo como texto), buscando el `match` sobre la salida q del
+// registro y, dentro de cada rama, las asignaciones a su entrada d con las
+// condiciones de los `if` que llevan a ellas. Las asignaciones fuera del
+// match (el reset, normalmente) son transiciones desde cualquier estado.
+//
+// Es un análisis del código, no de la simulación: no se tiene en cuenta que
+// una asignación posterior pueda tapar otra, y los bucles se recorren una
+// vez con su variable sin sustituir. Con el resultado se puede escribir el
+// diagrama en DOT (to_dot), pasarlo a SVG si Graphviz está instalado
+// (to_svg) y ver qué estados no se alcanzan o no tienen transiciones.
+use crate::icarus::{directorio_temporal, ejecutar, IcarusError};
+use rust_hdl::prelude::*;
+use rust_hdl_core::type_descriptor::TypeKind;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::process::Command;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FsmError {
+    // El registro no guarda un enum con #[derive(LogicState)]
+    NotAnEnum(String),
+    // El Verilog del diseño no sale de #[hdl_gen] (está escrito a mano)
+    CustomVerilog,
+    // No hay un `match` sobre la salida del registro
+    NoStateMatch(String),
+    // No se encuentra dot (Graphviz) en el PATH
+    NotInstalled(String),
+    // dot ha fallado o no se ha podido lanzar
+    Graphviz(String),
+}
+
+impl fmt::Display for FsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsmError::NotAnEnum(tipo) => write!(f, "{} no es un enum con #[derive(LogicState)]", tipo),
+            FsmError::CustomVerilog => write!(f, "el Verilog del diseño no sale de #[hdl_gen]"),
+            FsmError::NoStateMatch(registro) => write!(f, "no hay un match sobre {}.q", registro),
+            FsmError::NotInstalled(tool) => write!(f, "{} no está instalado o no está en el PATH", tool),
+            FsmError::Graphviz(motivo) => write!(f, "error de Graphviz: {}", motivo),
+        }
+    }
+}
+
+impl std::error::Error for FsmError {}
+
+// dot se lanza con el mismo ejecutar() que Icarus
+impl From<IcarusError> for FsmError {
+    fn from(err: IcarusError) -> Self {
+        match err {
+            IcarusError::NotInstalled(tool) => FsmError::NotInstalled(tool),
+            otro => FsmError::Graphviz(otro.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    // None si la asignación está fuera del match (vale desde cualquier estado)
+    pub from: Option<String>,
+    pub to: String,
+    // Condiciones de los `if` que llevan a la asignación, unidas con &&
+    // (vacía si no hay ninguna)
+    pub condition: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateMachine {
+    // Nombre del enum
+    pub name: String,
+    // Variantes del enum, en orden
+    pub states: Vec<String>,
+    pub transitions: Vec<Transition>,
+}
+
+impl StateMachine {
+    // Máquina de estados del registro `registro` del diseño (el nombre del
+    // campo, por ejemplo "state" para self.state), que guarda un S
+    pub fn extract<S: Synth, T: Logic>(diseno: &T, registro: &str) -> Result<Self, FsmError> {
+        let descriptor = S::descriptor();
+        let TypeKind::Enum(variantes) = descriptor.kind else {
+            return Err(FsmError::NotAnEnum(descriptor.name));
+        };
+        let Verilog::Combinatorial(bloque) = diseno.hdl() else {
+            return Err(FsmError::CustomVerilog);
+        };
+        let mut extractor = Extractor {
+            registro: registro.into(),
+            estados: variantes.iter().map(|v| sin_prefijo(v).into()).collect(),
+            transiciones: vec![],
+            encontrado: false,
+        };
+        extractor.bloque(&bloque, None, &[]);
+        if !extractor.encontrado {
+            return Err(FsmError::NoStateMatch(registro.into()));
+        }
+        Ok(Self {
+            name: descriptor.name,
+            states: extractor.estados,
+            transitions: extractor.transiciones,
+        })
+    }
+
+    // El estado al que lleva el reset (la primera transición desde cualquier
+    // estado) o, si no hay, la primera variante del enum
+    pub fn initial(&self) -> &str {
+        self.transitions
+            .iter()
+            .find(|t| t.from.is_none())
+            .map_or(&self.states[0], |t| &t.to)
+    }
+
+    // Estados a los que no se llega desde el inicial
+    pub fn unreachable(&self) -> Vec<&str> {
+        let mut alcanzados = BTreeSet::from([self.initial()]);
+        alcanzados.extend(
+            self.transitions
+                .iter()
+                .filter(|t| t.from.is_none())
+                .map(|t| t.to.as_str()),
+        );
+        let mut pendientes: Vec<&str> = alcanzados.iter().copied().collect();
+        while let Some(estado) = pendientes.pop() {
+            for t in &self.transitions {
+                if t.from.as_deref() == Some(estado) && alcanzados.insert(&t.to) {
+                    pendientes.push(&t.to);
+                }
+            }
+        }
+        self.states
+            .iter()
+            .map(String::as_str)
+            .filter(|s| !alcanzados.contains(s))
+            .collect()
+    }
+
+    // Estados sin ninguna transición propia: no tienen rama en el match o
+    // su rama nunca cambia el estado, así que sólo se sale de ellos con el
+    // reset
+    pub fn missing_transitions(&self) -> Vec<&str> {
+        self.states
+            .iter()
+            .map(String::as_str)
+            .filter(|&s| {
+                !self
+                    .transitions
+                    .iter()
+                    .any(|t| t.from.as_deref() == Some(s) && t.to != s)
+            })
+            .collect()
+    }
+
+    // Resumen de los problemas encontrados, una línea por problema
+    pub fn report(&self) -> String {
+        let mut informe = String::new();
+        for estado in self.unreachable() {
+            informe += &format!("{}: no se llega desde {}\n", estado, self.initial());
+        }
+        for estado in self.missing_transitions() {
+            informe += &format!("{}: no tiene transiciones a otro estado\n", estado);
+        }
+        informe
+    }
+
+    // Diagrama en formato DOT. El estado inicial tiene doble círculo, los
+    // que no se alcanzan salen en gris y discontinuos, y las transiciones
+    // desde cualquier estado salen de un punto
+    pub fn to_dot(&self) -> String {
+        let inalcanzables = self.unreachable();
+        let mut dot = format!("digraph {} {{\n    rankdir=LR;\n    node [shape=circle];\n", self.name);
+        for estado in &self.states {
+            let estilo = if estado == self.initial() {
+                " [shape=doublecircle]"
+            } else if inalcanzables.contains(&estado.as_str()) {
+                " [style=dashed, color=gray]"
+            } else {
+                ""
+            };
+            dot += &format!("    \"{}\"{};\n", estado, estilo);
+        }
+        if self.transitions.iter().any(|t| t.from.is_none()) {
+            dot += "    \"*\" [shape=point];\n";
+        }
+        for t in &self.transitions {
+            let desde = t.from.as_deref().unwrap_or("*");
+            dot += &format!("    \"{}\" -> \"{}\"", desde, t.to);
+            if !t.condition.is_empty() {
+                dot += &format!(" [label=\"{}\"]", t.condition.replace('"', "\\\""));
+            }
+            dot += ";\n";
+        }
+        dot + "}\n"
+    }
+
+    // Diagrama en SVG, pasando to_dot por `dot -Tsvg`
+    pub fn to_svg(&self) -> Result<String, FsmError> {
+        let dir = directorio_temporal("fsm", &self.name).map_err(|err| FsmError::Graphviz(err.to_string()))?;
+        let fichero = dir.join(format!("{}.dot", self.name));
+        std::fs::write(&fichero, self.to_dot()).map_err(|err| FsmError::Graphviz(err.to_string()))?;
+        let mut comando = Command::new("dot");
+        comando.arg("-Tsvg").arg(&fichero);
+        let resultado = ejecutar(comando, "dot", Duration::from_secs(60));
+        let _ = std::fs::remove_dir_all(&dir);
+        let (status, stdout, stderr) = resultado?;
+        if !status.success() {
+            return Err(FsmError::Graphviz(stderr));
+        }
+        Ok(stdout)
+    }
+}
+
+// "State::Idle" o "State$Idle" -> "Idle"
+fn sin_prefijo(nombre: &str) -> &str {
+    nombre.rsplit([':', '$']).next().unwrap_or(nombre)
+}
+
+struct Extractor {
+    registro: String,
+    estados: Vec<String>,
+    transiciones: Vec<Transition>,
+    encontrado: bool,
+}
+
+impl Extractor {
+    // Recorre un bloque dentro de la rama del estado `desde` (None fuera del
+    // match) con las condiciones `condiciones`
+    fn bloque(&mut self, bloque: &ast::VerilogBlock, desde: Option<&str>, condiciones: &[String]) {
+        for sentencia in bloque {
+            self.sentencia(sentencia, desde, condiciones);
+        }
+    }
+
+    fn sentencia(&mut self, sentencia: &ast::VerilogStatement, desde: Option<&str>, condiciones: &[String]) {
+        match sentencia {
+            // Sólo cuentan los valores del enum; `d = q` no cambia nada
+            ast::VerilogStatement::Assignment(
+                ast::VerilogExpression::Signal(destino),
+                ast::VerilogExpression::Signal(valor),
+            ) if *destino == format!("{}$d$next", self.registro) => {
+                let estado = sin_prefijo(valor);
+                if self.estados.iter().any(|s| s == estado) && Some(estado) != desde {
+                    self.transiciones.push(Transition {
+                        from: desde.map(Into::into),
+                        to: estado.into(),
+                        condition: condiciones.join(" && "),
+                    });
+                }
+            }
+            ast::VerilogStatement::If(condicional) => self.condicional(condicional, desde, condiciones),
+            ast::VerilogStatement::Match(m) if expresion(&m.test) == format!("{}$q", self.registro) => {
+                self.encontrado = true;
+                let con_rama: Vec<&str> = m.cases.iter().map(|c| sin_prefijo(&c.condition)).collect();
+                for caso in &m.cases {
+                    let estados: Vec<String> = if caso.condition == "default" {
+                        let sin_rama = self.estados.iter().filter(|s| !con_rama.contains(&s.as_str()));
+                        sin_rama.cloned().collect()
+                    } else {
+                        vec![sin_prefijo(&caso.condition).into()]
+                    };
+                    for estado in estados {
+                        self.bloque(&caso.block, Some(&estado), condiciones);
+                    }
+                }
+            }
+            ast::VerilogStatement::Match(m) => {
+                let prueba = expresion(&m.test);
+                for caso in &m.cases {
+                    let condicion = if caso.condition == "default" {
+                        format!("{} en otro caso", prueba)
+                    } else {
+                        format!("{} == {}", prueba, caso.condition)
+                    };
+                    self.bloque(&caso.block, desde, &con(condiciones, condicion));
+                }
+            }
+            ast::VerilogStatement::Loop(bucle) => self.bloque(&bucle.block, desde, condiciones),
+            ast::VerilogStatement::Macro(bloque) => self.bloque(bloque, desde, condiciones),
+            _ => {}
+        }
+    }
+
+    fn condicional(&mut self, condicional: &ast::VerilogConditional, desde: Option<&str>, condiciones: &[String]) {
+        let prueba = expresion(&condicional.test);
+        self.bloque(&condicional.then, desde, &con(condiciones, prueba.clone()));
+        let negada = con(condiciones, negar(&prueba));
+        match &condicional.otherwise {
+            ast::VerilogBlockOrConditional::Block(bloque) => self.bloque(bloque, desde, &negada),
+            ast::VerilogBlockOrConditional::Conditional(otro) => {
+                if let ast::VerilogStatement::If(otro) = otro.as_ref() {
+                    self.condicional(otro, desde, &negada);
+                }
+            }
+            ast::VerilogBlockOrConditional::None => {}
+        }
+    }
+}
+
+fn con(condiciones: &[String], nueva: String) -> Vec<String> {
+    let mut todas = condiciones.to_vec();
+    todas.push(nueva);
+    todas
+}
+
+fn negar(condicion: &str) -> String {
+    if condicion.chars().all(|c| c.is_alphanumeric() || c == '$' || c == '_') {
+        format!("!{}", condicion)
+    } else {
+        format!("!({})", condicion)
+    }
+}
+
+// Texto de una expresión, con la misma sintaxis que el Verilog generado
+fn expresion(e: &ast::VerilogExpression) -> String {
+    use ast::VerilogExpression::*;
+    match e {
+        Signal(nombre) => nombre.clone(),
+        Literal(valor) => valor.to_string(),
+        Cast(e, bits) => format!("{}[{}:0]", expresion(e), bits - 1),
+        Signed(e) => format!("$signed({})", expresion(e)),
+        Unsigned(e) => format!("$unsigned({})", expresion(e)),
+        Paren(e) => format!("({})", expresion(e)),
+        Binary(a, op, b) => format!("{} {} {}", expresion(a), operador(op), expresion(b)),
+        Unary(op, e) => {
+            let simbolo = match op {
+                ast::VerilogOpUnary::Not => "!",
+                ast::VerilogOpUnary::Neg => "-",
+                ast::VerilogOpUnary::All => "&",
+                ast::VerilogOpUnary::Any => "|",
+                ast::VerilogOpUnary::Xor => "^",
+            };
+            format!("{}{}", simbolo, expresion(e))
+        }
+        Index(e, i) => format!("{}[{}]", expresion(e), expresion(i)),
+        Slice(e, bits, desde) => format!("{}[{} +: {}]", expresion(e), expresion(desde), bits),
+        IndexReplace(e, i, v) => format!("{} con [{}] = {}", expresion(e), expresion(i), expresion(v)),
+    }
+}
+
+fn operador(op: &ast::VerilogOp) -> &'static str {
+    use ast::VerilogOp::*;
+    match op {
+        Add => "+",
+        Sub => "-",
+        Mul => "*",
+        LogicalAnd => "&&",
+        LogicalOr => "||",
+        BitXor => "^",
+        BitAnd => "&",
+        BitOr => "|",
+        Shl => "<<",
+        Shr => ">>",
+        Eq => "==",
+        Lt => "<",
+        Le => "<=",
+        Ne => "!=",
+        Ge => ">=",
+        Gt => ">",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EdgeDFF;
+
+    // Apagado no se asigna nunca, a propósito
+    #[allow(dead_code)]
+    #[derive(Copy, Clone, PartialEq, Eq, Debug, LogicState)]
+    enum Semaforo {
+        Rojo,
+        Verde,
+        Ambar,
+        Averiado,
+        Apagado,
+    }
+
+    // Averiado no tiene salida y a Apagado no se llega nunca (sólo tiene la
+    // rama por defecto)
+    #[derive(LogicBlock)]
+    struct Cruce {
+        clk: Signal<In, Clock>,
+        rst: Signal<In, Bit>,
+        boton: Signal<In, Bit>,
+        fallo: Signal<In, Bit>,
+        luz: EdgeDFF<Semaforo>,
+        contador: EdgeDFF<Bits<3>>,
+    }
+
+    impl Default for Cruce {
+        fn default() -> Self {
+            Self {
+                clk: Default::default(),
+                rst: Default::default(),
+                boton: Default::default(),
+                fallo: Default::default(),
+                luz: EdgeDFF::new(Semaforo::Rojo),
+                contador: EdgeDFF::new(0.into()),
+            }
+        }
+    }
+
+    impl Logic for Cruce {
+        #[hdl_gen]
+        fn update(&mut self) {
+            self.luz.clk.next = self.clk.val();
+            self.contador.clk.next = self.clk.val();
+            self.luz.d.next = self.luz.q.val();
+            self.contador.d.next = self.contador.q.val() + 1;
+            match self.luz.q.val() {
+                Semaforo::Rojo => {
+                    if self.boton.val() & (self.contador.q.val() == 7) {
+                        self.luz.d.next = Semaforo::Verde;
+                    }
+                }
+                Semaforo::Verde => {
+                    self.luz.d.next = Semaforo::Ambar;
+                }
+                Semaforo::Ambar => {
+                    if self.fallo.val() {
+                        self.luz.d.next = Semaforo::Averiado;
+                    } else if !self.boton.val() {
+                        self.luz.d.next = Semaforo::Rojo;
+                    }
+                }
+                Semaforo::Averiado => {}
+                _ => {
+                    self.luz.d.next = Semaforo::Rojo;
+                }
+            }
+            if self.rst.val() {
+                self.luz.d.next = Semaforo::Rojo;
+            }
+        }
+    }
+
+    #[test]
+    fn test_extraer() -> Result<(), FsmError> {
+        let fsm = StateMachine::extract::<Semaforo, _>(&Cruce::default(), "luz")?;
+        assert_eq!(fsm.name, "Semaforo");
+        assert_eq!(fsm.states, ["Rojo", "Verde", "Ambar", "Averiado", "Apagado"]);
+        let aristas: Vec<(Option<&str>, &str, &str)> = fsm
+            .transitions
+            .iter()
+            .map(|t| (t.from.as_deref(), t.to.as_str(), t.condition.as_str()))
+            .collect();
+        assert_eq!(
+            aristas,
+            [
+                (Some("Rojo"), "Verde", "boton & (contador$q == 32'h7)"),
+                (Some("Verde"), "Ambar", ""),
+                (Some("Ambar"), "Averiado", "fallo"),
+                (Some("Ambar"), "Rojo", "!fallo && !boton"),
+                (Some("Apagado"), "Rojo", ""),
+                (None, "Rojo", "rst"),
+            ]
+        );
+        assert_eq!(fsm.initial(), "Rojo");
+        assert_eq!(fsm.unreachable(), ["Apagado"]);
+        assert_eq!(fsm.missing_transitions(), ["Averiado"]);
+        assert_eq!(
+            fsm.report(),
+            "Apagado: no se llega desde Rojo\nAveriado: no tiene transiciones a otro estado\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dot() -> Result<(), FsmError> {
+        let fsm = StateMachine::extract::<Semaforo, _>(&Cruce::default(), "luz")?;
+        let dot = fsm.to_dot();
+        assert!(dot.starts_with("digraph Semaforo {\n    rankdir=LR;\n"), "{}", dot);
+        assert!(dot.contains("    \"Rojo\" [shape=doublecircle];\n"));
+        assert!(dot.contains("    \"Apagado\" [style=dashed, color=gray];\n"));
+        assert!(dot.contains("    \"Verde\" -> \"Ambar\";\n"));
+        assert!(dot.contains("    \"*\" -> \"Rojo\" [label=\"rst\"];\n"));
+        assert!(dot.ends_with("}\n"));
+        match fsm.to_svg() {
+            Ok(svg) => assert!(svg.contains("<svg"), "{}", svg),
+            Err(FsmError::NotInstalled(tool)) => println!("{} no está instalado, se omite el SVG", tool),
+            Err(err) => return Err(err),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_errores() {
+        let cruce = Cruce::default();
+        assert_eq!(
+            StateMachine::extract::<Bits<3>, _>(&cruce, "contador").err(),
+            Some(FsmError::NotAnEnum("Bits::<3>".into()))
+        );
+        assert_eq!(
+            StateMachine::extract::<Semaforo, _>(&cruce, "contador").err(),
+            Some(FsmError::NoStateMatch("contador".into()))
+        );
+    }
+}
@@ -0,0 +1,357 @@
+// Ejecución de testbenches con Icarus Verilog.
+//
+// Sustituye a las copias de get_icarus_verilog_output que había en cada
+// crate: cada ejecución compila en su propio directorio temporal (así los
+// test de distintos diseños pueden correr en paralelo sin pisarse el
+// test_tb.vvp), los fallos de iverilog se devuelven como error con el stderr
+// del compilador y vvp se mata si supera el tiempo máximo.
+use std::fmt;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Contador para que dos ejecuciones del mismo proceso no compartan directorio
+static EJECUCIONES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+pub enum IcarusError {
+    // No se encuentra el ejecutable (iverilog o vvp) en el PATH
+    NotInstalled(String),
+    // Error de entrada/salida preparando o lanzando la simulación
+    Io(io::Error),
+    // iverilog no ha podido compilar el testbench
+    Compile { status: ExitStatus, stderr: String },
+    // vvp ha terminado con error
+    Simulation { status: ExitStatus, stdout: String, stderr: String },
+    // La herramienta no ha terminado dentro del tiempo máximo
+    Timeout { tool: String, limit: Duration },
+}
+
+impl fmt::Display for IcarusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IcarusError::NotInstalled(tool) => write!(f, "{} no está instalado o no está en el PATH", tool),
+            IcarusError::Io(err) => write!(f, "error de E/S ejecutando Icarus: {}", err),
+            IcarusError::Compile { status, stderr } => {
+                write!(f, "iverilog falló ({}):\n{}", status, stderr)
+            }
+            IcarusError::Simulation { status, stderr, .. } => {
+                write!(f, "vvp falló ({}):\n{}", status, stderr)
+            }
+            IcarusError::Timeout { tool, limit } => {
+                write!(f, "{} no terminó en {:?}", tool, limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for IcarusError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IcarusError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for IcarusError {
+    fn from(err: io::Error) -> Self {
+        IcarusError::Io(err)
+    }
+}
+
+// Resultado de una simulación correcta. El directorio temporal se borra al
+// soltar la salida, salvo que se llame a keep()
+#[derive(Debug)]
+pub struct IcarusOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub vcd: Option<PathBuf>,
+    dir: PathBuf,
+    keep: bool,
+}
+
+impl IcarusOutput {
+    // Directorio en el que se ha compilado y simulado
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // Conserva el directorio temporal (y el VCD) al soltar la salida
+    pub fn keep(mut self) -> PathBuf {
+        self.keep = true;
+        self.dir.clone()
+    }
+}
+
+impl Drop for IcarusOutput {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IcarusRunner {
+    iverilog: String,
+    vvp: String,
+    timeout: Duration,
+    vcd: Option<String>,
+}
+
+impl Default for IcarusRunner {
+    fn default() -> Self {
+        Self {
+            iverilog: "iverilog".into(),
+            vvp: "vvp".into(),
+            timeout: Duration::from_secs(60),
+            vcd: None,
+        }
+    }
+}
+
+impl IcarusRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Tiempo máximo para compilar y para simular (por separado)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    // Nombre del fichero que el testbench vuelca con $dumpfile; si vvp lo
+    // genera, su ruta se devuelve en IcarusOutput::vcd
+    pub fn vcd(mut self, fichero: &str) -> Self {
+        self.vcd = Some(fichero.into());
+        self
+    }
+
+    // Rutas alternativas a los ejecutables de Icarus
+    pub fn tools(mut self, iverilog: &str, vvp: &str) -> Self {
+        self.iverilog = iverilog.into();
+        self.vvp = vvp.into();
+        self
+    }
+
+    // Comprueba si iverilog se puede lanzar
+    pub fn available(&self) -> bool {
+        Command::new(&self.iverilog)
+            .arg("-V")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok()
+    }
+
+    // Compila y simula `fuente` (testbench + diseño) con el nombre `nombre`
+    pub fn run(&self, nombre: &str, fuente: &str) -> Result<IcarusOutput, IcarusError> {
+        let dir = directorio_temporal("icarus", nombre)?;
+        // A partir de aquí la salida es dueña del directorio y lo borra si
+        // algo falla
+        let mut salida = IcarusOutput {
+            stdout: String::new(),
+            stderr: String::new(),
+            vcd: None,
+            dir,
+            keep: false,
+        };
+
+        let fichero = format!("{}.v", nombre);
+        std::fs::write(salida.dir.join(&fichero), fuente)?;
+
+        let mut compilar = Command::new(&self.iverilog);
+        compilar
+            .args(["-tvvp", "-o", "test_tb.vvp", &fichero])
+            .current_dir(&salida.dir);
+        let (status, _stdout, stderr) = ejecutar(compilar, &self.iverilog, self.timeout)?;
+        if !status.success() {
+            return Err(IcarusError::Compile { status, stderr });
+        }
+
+        let mut simular = Command::new(&self.vvp);
+        simular.arg("test_tb.vvp").current_dir(&salida.dir);
+        let (status, stdout, stderr) = ejecutar(simular, &self.vvp, self.timeout)?;
+        if !status.success() {
+            return Err(IcarusError::Simulation { status, stdout, stderr });
+        }
+
+        salida.stdout = stdout;
+        salida.stderr = stderr;
+        if let Some(vcd) = &self.vcd {
+            let ruta = salida.dir.join(vcd);
+            if ruta.exists() {
+                salida.vcd = Some(ruta);
+            }
+        }
+        Ok(salida)
+    }
+
+    // Igual que run, pero si Icarus no está instalado avisa por pantalla y
+    // devuelve None para que el test siga solo con la simulación de RustHDL
+    pub fn run_if_available(
+        &self,
+        nombre: &str,
+        fuente: &str,
+    ) -> Result<Option<IcarusOutput>, IcarusError> {
+        match self.run(nombre, fuente) {
+            Ok(salida) => Ok(Some(salida)),
+            Err(IcarusError::NotInstalled(tool)) => {
+                println!("{} no está instalado, se omite la simulación con Icarus", tool);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+// Directorio temporal propio para una ejecución de `prefijo` (icarus, yosys)
+pub(crate) fn directorio_temporal(prefijo: &str, nombre: &str) -> io::Result<PathBuf> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let dir = std::env::temp_dir().join(format!(
+        "{}-{}-{}-{}-{}",
+        prefijo,
+        nombre,
+        std::process::id(),
+        EJECUCIONES.fetch_add(1, Ordering::SeqCst),
+        nanos
+    ));
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// Lanza el comando recogiendo stdout y stderr, y lo mata si tarda más de
+// `limite`
+pub(crate) fn ejecutar(
+    mut comando: Command,
+    tool: &str,
+    limite: Duration,
+) -> Result<(ExitStatus, String, String), IcarusError> {
+    let mut hijo = match comando
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(hijo) => hijo,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Err(IcarusError::NotInstalled(tool.into()))
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    // Las tuberías se leen en hilos aparte para que un proceso que escribe
+    // mucho no se bloquee mientras esperamos a que termine
+    let lector_stdout = leer_en_hilo(hijo.stdout.take());
+    let lector_stderr = leer_en_hilo(hijo.stderr.take());
+
+    let inicio = Instant::now();
+    let status = loop {
+        if let Some(status) = hijo.try_wait()? {
+            break status;
+        }
+        if inicio.elapsed() > limite {
+            let _ = hijo.kill();
+            let _ = hijo.wait();
+            return Err(IcarusError::Timeout {
+                tool: tool.into(),
+                limit: limite,
+            });
+        }
+        thread::sleep(Duration::from_millis(5));
+    };
+
+    let stdout = lector_stdout.join().unwrap_or_default();
+    let stderr = lector_stderr.join().unwrap_or_default();
+    Ok((status, stdout, stderr))
+}
+
+fn leer_en_hilo<R: Read + Send + 'static>(tuberia: Option<R>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut texto = Vec::new();
+        if let Some(mut tuberia) = tuberia {
+            let _ = tuberia.read_to_end(&mut texto);
+        }
+        String::from_utf8_lossy(&texto).into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_herramienta_no_instalada() {
+        let runner = IcarusRunner::new().tools("iverilog-que-no-existe", "vvp-que-no-existe");
+        assert!(!runner.available());
+        match runner.run("no_instalado", "module test; endmodule") {
+            Err(IcarusError::NotInstalled(tool)) => assert_eq!(tool, "iverilog-que-no-existe"),
+            otro => panic!("se esperaba NotInstalled, llega {:?}", otro),
+        }
+        assert!(runner
+            .run_if_available("no_instalado", "module test; endmodule")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_timeout_mata_el_proceso() {
+        let mut comando = Command::new("sleep");
+        comando.arg("10");
+        let inicio = Instant::now();
+        match ejecutar(comando, "sleep", Duration::from_millis(100)) {
+            Err(IcarusError::Timeout { tool, .. }) => assert_eq!(tool, "sleep"),
+            otro => panic!("se esperaba Timeout, llega {:?}", otro),
+        }
+        assert!(inicio.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_testbench_roto_da_error_de_compilacion() {
+        let runner = IcarusRunner::new();
+        if !runner.available() {
+            println!("iverilog no está instalado, se omite el test");
+            return;
+        }
+        match runner.run("roto", "module test; esto no es verilog endmodule") {
+            Err(IcarusError::Compile { stderr, .. }) => assert!(!stderr.is_empty()),
+            otro => panic!("se esperaba Compile, llega {:?}", otro),
+        }
+    }
+
+    #[test]
+    fn test_salida_y_vcd() {
+        let runner = IcarusRunner::new().vcd("onda.vcd");
+        if !runner.available() {
+            println!("iverilog no está instalado, se omite el test");
+            return;
+        }
+        let fuente = r#"
+module test;
+  reg a;
+  initial begin
+    $dumpfile("onda.vcd");
+    $dumpvars(0, test);
+    a = 0; #1 a = 1; #1;
+    $display("hola desde vvp");
+    $finish;
+  end
+endmodule
+"#;
+        let salida = runner.run("salida_y_vcd", fuente).unwrap();
+        assert!(salida.stdout.contains("hola desde vvp"));
+        let vcd = salida.vcd.clone().expect("vvp debería haber generado el VCD");
+        assert!(vcd.exists());
+        let dir = salida.dir().to_path_buf();
+        drop(salida);
+        assert!(!dir.exists());
+    }
+}
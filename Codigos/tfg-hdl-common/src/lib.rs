@@ -0,0 +1,53 @@
+// Biblioteca común a todos los diseños del TFG.
+//
+// Aquí vive todo lo que antes se copiaba a mano en cada crate (por ejemplo
+// el flip-flop EdgeDFF o la función que lanzaba Icarus Verilog), de forma que
+// un cambio se hace una sola vez.
+//
+// Bucles de una vuelta: #[hdl_gen] calcula los índices de los arrays y de
+// get_bit al generar el Verilog, y para entonces sólo conoce las variables de
+// bucle, no los parámetros const. Por eso un índice que depende de uno, como
+// el bit de signo W - 1 o la hoja 2·k + 2 - N, se escribe dentro de un bucle
+// que da una sola vuelta, `for i in (W - 1)..W` o `for n in N..(N + 1)`, con
+// la variable de bucle: en Rust es el mismo valor y en el Verilog sale como un
+// número. Los diseños que lo hacen remiten aquí con una línea.
+
+pub mod booth;
+pub mod cosim;
+pub mod dff;
+pub mod export;
+pub mod fsm;
+pub mod icarus;
+pub mod mul;
+pub mod overflow;
+pub mod ports;
+pub mod shift_add;
+pub mod signed;
+pub mod skid;
+pub mod synth;
+pub mod testbench;
+pub mod verilog;
+pub mod wallace;
+
+pub use cosim::{CosimError, CosimReport, Divergence};
+pub use dff::{EdgeDFF, EdgeDFFAsyncReset, EdgeDFFEnable, EdgeDFFSyncReset};
+pub use export::{
+    export_verilog, export_verilog_as, export_verilog_from_args, export_verilog_from_args_as, port_style_from_arg,
+    ExportError,
+};
+pub use fsm::{FsmError, StateMachine, Transition};
+pub use icarus::{IcarusError, IcarusOutput, IcarusRunner};
+pub use booth::BoothMul;
+pub use mul::{
+    Booth, MulAlgorithm, MulCore, Multiplier, Operator, OperatorMul, Registered, RegisteredMul, ShiftAdd, SignedMul,
+    Signedness, TwosComplement, Unsigned, Wallace,
+};
+pub use overflow::{overflow_add, OverflowAdder, OverflowMode, Saturate, Wrap};
+pub use shift_add::ShiftAddMul;
+pub use ports::{generate_verilog_ports, generate_verilog_ports_with_header, PortStyle, VerilogExport};
+pub use signed::signed_bit_cast;
+pub use skid::{PipelineHandshake, SkidBuffer, StreamModel};
+pub use synth::{SynthStats, YosysError, YosysRunner};
+pub use testbench::{Input, Output, PortValue, TestVector, TestVectors, TestbenchError};
+pub use verilog::{generate_named_verilog, generate_named_verilog_with_header, VerilogError, VerilogHeader};
+pub use wallace::WallaceMul;
@@ -0,0 +1,467 @@
+// Multiplicadores de anchura genérica.
+//
+// En rust-hdl 0.46 el operador * sólo existe para Signed<16> y Bits<16>, así
+// que con anchuras genéricas no se puede multiplicar dentro de #[hdl_gen].
+// Los diseños usan uno de estos bloques, y el algoritmo con el que se
+// construye el multiplicador se elige con un parámetro de tipo:
+//
+// - Operator: el operador * de Verilog (la herramienta de síntesis elige
+//   cómo implementarlo); en la simulación se multiplica con i64.
+// - ShiftAdd: sumas y desplazamientos, como Mul8x8.
+// - Booth: Booth de base 4, con la mitad de productos parciales.
+// - Wallace: los productos parciales se reducen con un árbol de sumadores
+//   carry-save y sólo la última suma propaga el acarreo.
+// - Registered<A>: el multiplicador de A con el producto en un registro, que
+//   sale un ciclo más tarde (LATENCY) pero corta el camino crítico entre el
+//   multiplicador y lo que venga detrás.
+//
+// Cada algoritmo es un bloque `núcleo` con puertos a, b y resul en Bits, que
+// interpreta sin signo o en complemento a 2 según Signedness. SignedMul
+// (puertos Signed, para los diseños de matrices) y Multiplier (puertos Bits,
+// para el producto escalar) sólo conectan sus puertos a los del núcleo, así
+// que los diseños pueden cambiar de algoritmo sin tocar su código. Los dos
+// tienen un puerto clk que sólo llega al núcleo si éste tiene registros.
+use crate::booth::BoothMul;
+use crate::shift_add::ShiftAddMul;
+use crate::wallace::WallaceMul;
+use crate::EdgeDFF;
+use crate::PortValue;
+use rust_hdl::prelude::*;
+
+// Cómo se interpretan los operandos. Unsigned y TwosComplement no generan
+// hardware: solo eligen el valor de la constante `con_signo` de los núcleos
+pub trait Signedness: Block + Clone + Default + Send + 'static {
+    const SIGNED: bool;
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Unsigned;
+
+#[derive(Clone, Debug, Default)]
+pub struct TwosComplement;
+
+impl Signedness for Unsigned {
+    const SIGNED: bool = false;
+}
+
+impl Signedness for TwosComplement {
+    const SIGNED: bool = true;
+}
+
+// Bloque vacío para que el derive de LogicBlock acepte el campo `modo`. Se
+// exporta para los modos que definen los diseños
+#[macro_export]
+macro_rules! modo_sin_hardware {
+    ($($modo:ty),*) => {$(
+        impl ::rust_hdl::prelude::Logic for $modo {
+            fn update(&mut self) {}
+        }
+
+        impl ::rust_hdl::prelude::Block for $modo {
+            fn connect_all(&mut self) {}
+            fn update_all(&mut self) {}
+            fn has_changed(&self) -> bool {
+                false
+            }
+            fn accept(&self, _name: &str, _probe: &mut dyn ::rust_hdl::prelude::Probe) {}
+        }
+    )*};
+}
+
+modo_sin_hardware!(Unsigned, TwosComplement);
+
+// Núcleo de un multiplicador: resul = a·b con a y b de W bits y resul de O.
+// Los puertos se llaman igual en todos (a, b, resul y, si tiene registros,
+// clk), porque SignedMul y Multiplier los nombran en su Verilog
+pub trait MulCore<const W: usize, const O: usize>: Block + Clone + Default + Send + 'static {
+    fn operands(&mut self) -> (&mut Signal<In, Bits<W>>, &mut Signal<In, Bits<W>>);
+    fn product(&self) -> &Signal<Out, Bits<O>>;
+    // Reloj de los núcleos con registros
+    fn clock(&mut self) -> Option<&mut Signal<In, Clock>> {
+        None
+    }
+}
+
+// Algoritmo de multiplicación: dice qué núcleo usar y cuántos ciclos tarda
+// el producto en salir
+pub trait MulAlgorithm: Clone + Default + Send + 'static {
+    type Core<const W: usize, const O: usize, S: Signedness>: MulCore<W, O>;
+    // 0 en los combinacionales
+    const LATENCY: usize = 0;
+    fn name() -> String;
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Operator;
+
+#[derive(Clone, Debug, Default)]
+pub struct ShiftAdd;
+
+#[derive(Clone, Debug, Default)]
+pub struct Booth;
+
+#[derive(Clone, Debug, Default)]
+pub struct Wallace;
+
+#[derive(Clone, Debug, Default)]
+pub struct Registered<A: MulAlgorithm>(A);
+
+impl MulAlgorithm for Operator {
+    type Core<const W: usize, const O: usize, S: Signedness> = OperatorMul<W, O, S>;
+    fn name() -> String {
+        "operador *".into()
+    }
+}
+
+impl MulAlgorithm for ShiftAdd {
+    type Core<const W: usize, const O: usize, S: Signedness> = ShiftAddMul<W, O, S>;
+    fn name() -> String {
+        "sumas y desplazamientos".into()
+    }
+}
+
+impl MulAlgorithm for Booth {
+    type Core<const W: usize, const O: usize, S: Signedness> = BoothMul<W, O, S>;
+    fn name() -> String {
+        "Booth base 4".into()
+    }
+}
+
+impl MulAlgorithm for Wallace {
+    type Core<const W: usize, const O: usize, S: Signedness> = WallaceMul<W, O, S>;
+    fn name() -> String {
+        "árbol de Wallace".into()
+    }
+}
+
+impl<A: MulAlgorithm> MulAlgorithm for Registered<A> {
+    type Core<const W: usize, const O: usize, S: Signedness> = RegisteredMul<W, O, S, A>;
+    const LATENCY: usize = A::LATENCY + 1;
+    fn name() -> String {
+        format!("{} registrado", A::name())
+    }
+}
+
+// Los núcleos escritos con #[hdl_gen] sólo tienen que dar sus puertos
+macro_rules! nucleo {
+    ($($nucleo:ident),*) => {$(
+        impl<const W: usize, const O: usize, S: Signedness> MulCore<W, O> for $nucleo<W, O, S> {
+            fn operands(&mut self) -> (&mut Signal<In, Bits<W>>, &mut Signal<In, Bits<W>>) {
+                (&mut self.a, &mut self.b)
+            }
+            fn product(&self) -> &Signal<Out, Bits<O>> {
+                &self.resul
+            }
+        }
+    )*};
+}
+
+nucleo!(OperatorMul, ShiftAddMul, BoothMul, WallaceMul);
+
+// El operador * de Verilog. En la simulación se multiplica con i64, así que
+// O no puede pasar de 64 bits
+#[derive(LogicBlock, Clone)]
+pub struct OperatorMul<const W: usize, const O: usize, S: Signedness> {
+    pub a: Signal<In, Bits<W>>,
+    pub b: Signal<In, Bits<W>>,
+    pub resul: Signal<Out, Bits<O>>,
+    modo: S,
+}
+
+impl<const W: usize, const O: usize, S: Signedness> Default for OperatorMul<W, O, S> {
+    fn default() -> Self {
+        const {
+            assert!(O >= 2 * W, "el producto necesita al menos 2W bits");
+            assert!(O <= 64, "la simulación multiplica en i64");
+        }
+        Self {
+            a: Default::default(),
+            b: Default::default(),
+            resul: Default::default(),
+            modo: Default::default(),
+        }
+    }
+}
+
+impl<const W: usize, const O: usize, S: Signedness> OperatorMul<W, O, S> {
+    fn valor(x: Bits<W>) -> i64 {
+        if S::SIGNED {
+            signed_cast(x).to_i64()
+        } else {
+            x.to_i64()
+        }
+    }
+}
+
+impl<const W: usize, const O: usize, S: Signedness> Logic for OperatorMul<W, O, S> {
+    fn update(&mut self) {
+        // El producto en O bits, que con signo es el complemento a 2
+        let producto = Self::valor(self.a.val()) * Self::valor(self.b.val());
+        self.resul.next = if S::SIGNED {
+            Signed::<O>::from(producto).inner()
+        } else {
+            Bits::<O>::from(producto as u64)
+        };
+    }
+    fn connect(&mut self) {
+        self.resul.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        // Con $signed Verilog extiende el signo a O bits antes de multiplicar
+        if S::SIGNED {
+            Verilog::Custom("always @(*) resul = $signed(a) * $signed(b);".into())
+        } else {
+            Verilog::Custom("always @(*) resul = a * b;".into())
+        }
+    }
+}
+
+// Núcleo de Registered<A>: el de A seguido de un registro con el producto
+#[derive(LogicBlock, Clone, Default)]
+pub struct RegisteredMul<const W: usize, const O: usize, S: Signedness, A: MulAlgorithm> {
+    pub a: Signal<In, Bits<W>>,
+    pub b: Signal<In, Bits<W>>,
+    pub resul: Signal<Out, Bits<O>>,
+    pub clk: Signal<In, Clock>,
+    nucleo: A::Core<W, O, S>,
+    registro: EdgeDFF<Bits<O>>,
+}
+
+impl<const W: usize, const O: usize, S: Signedness, A: MulAlgorithm> MulCore<W, O> for RegisteredMul<W, O, S, A> {
+    fn operands(&mut self) -> (&mut Signal<In, Bits<W>>, &mut Signal<In, Bits<W>>) {
+        (&mut self.a, &mut self.b)
+    }
+    fn product(&self) -> &Signal<Out, Bits<O>> {
+        &self.resul
+    }
+    fn clock(&mut self) -> Option<&mut Signal<In, Clock>> {
+        Some(&mut self.clk)
+    }
+}
+
+impl<const W: usize, const O: usize, S: Signedness, A: MulAlgorithm> Logic for RegisteredMul<W, O, S, A> {
+    fn update(&mut self) {
+        let (a, b) = self.nucleo.operands();
+        a.next = self.a.val();
+        b.next = self.b.val();
+        if let Some(clk) = self.nucleo.clock() {
+            clk.next = self.clk.val();
+        }
+        self.registro.clk.next = self.clk.val();
+        self.registro.d.next = self.nucleo.product().val();
+        self.resul.next = self.registro.q.val();
+    }
+    fn connect(&mut self) {
+        let (a, b) = self.nucleo.operands();
+        a.connect();
+        b.connect();
+        if let Some(clk) = self.nucleo.clock() {
+            clk.connect();
+        }
+        self.registro.clk.connect();
+        self.registro.d.connect();
+        self.resul.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(format!(
+            "always @(*) begin\n{}    registro$clk = clk;\n    registro$d = nucleo$resul;\n    resul = registro$q;\nend",
+            entradas_del_nucleo::<A>()
+        ))
+    }
+}
+
+// Asignaciones que pasan a, b y, si el núcleo de A tiene registros, clk al
+// núcleo
+fn entradas_del_nucleo<A: MulAlgorithm>() -> String {
+    let reloj = if A::LATENCY > 0 { "    nucleo$clk = clk;\n" } else { "" };
+    format!("    nucleo$a = a;\n    nucleo$b = b;\n{}", reloj)
+}
+
+// Verilog de los envoltorios: pasan a y b al núcleo y su resultado a `salida`
+fn conectar_nucleo<A: MulAlgorithm>(salida: &str) -> Verilog {
+    Verilog::Custom(format!(
+        "always @(*) begin\n{}    {} = nucleo$resul;\nend",
+        entradas_del_nucleo::<A>(),
+        salida
+    ))
+}
+
+// p = a·b con signo, con a y b de W bits y p de O >= 2W bits, para que el
+// producto quepa siempre. Es el multiplicador de los diseños de matrices. p
+// sale A::LATENCY flancos de clk después de a y b
+#[derive(LogicBlock, Clone, Default)]
+pub struct SignedMul<const W: usize, const O: usize, A: MulAlgorithm = Operator> {
+    pub a: Signal<In, Signed<W>>,
+    pub b: Signal<In, Signed<W>>,
+    pub p: Signal<Out, Signed<O>>,
+    pub clk: Signal<In, Clock>,
+    nucleo: A::Core<W, O, TwosComplement>,
+}
+
+impl<const W: usize, const O: usize, A: MulAlgorithm> Logic for SignedMul<W, O, A> {
+    fn update(&mut self) {
+        let (a, b) = self.nucleo.operands();
+        a.next = self.a.val().inner();
+        b.next = self.b.val().inner();
+        if let Some(clk) = self.nucleo.clock() {
+            clk.next = self.clk.val();
+        }
+        self.p.next = signed_cast(self.nucleo.product().val());
+    }
+    fn connect(&mut self) {
+        let (a, b) = self.nucleo.operands();
+        a.connect();
+        b.connect();
+        if let Some(clk) = self.nucleo.clock() {
+            clk.connect();
+        }
+        self.p.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        conectar_nucleo::<A>("p")
+    }
+}
+
+// resul = a·b con puertos Bits, sin signo o en complemento a 2 según S. Es
+// el multiplicador del producto escalar
+#[derive(LogicBlock, Clone, Default)]
+pub struct Multiplier<const W: usize, const O: usize, S: Signedness = Unsigned, A: MulAlgorithm = ShiftAdd> {
+    pub a: Signal<In, Bits<W>>,
+    pub b: Signal<In, Bits<W>>,
+    pub resul: Signal<Out, Bits<O>>,
+    pub clk: Signal<In, Clock>,
+    nucleo: A::Core<W, O, S>,
+}
+
+impl<const W: usize, const O: usize, S: Signedness, A: MulAlgorithm> Logic for Multiplier<W, O, S, A> {
+    fn update(&mut self) {
+        let (a, b) = self.nucleo.operands();
+        a.next = self.a.val();
+        b.next = self.b.val();
+        if let Some(clk) = self.nucleo.clock() {
+            clk.next = self.clk.val();
+        }
+        self.resul.next = self.nucleo.product().val();
+    }
+    fn connect(&mut self) {
+        let (a, b) = self.nucleo.operands();
+        a.connect();
+        b.connect();
+        if let Some(clk) = self.nucleo.clock() {
+            clk.connect();
+        }
+        self.resul.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        conectar_nucleo::<A>("resul")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TestVector, TestVectors};
+
+    // Todos los pares de operandos de 4 bits con signo. Si el producto va
+    // registrado sale A::LATENCY flancos después
+    fn todos_los_pares<A: MulAlgorithm>(modulo: &str) {
+        let mut uut = SignedMul::<4, 8, A>::default();
+        uut.connect_all();
+
+        let mut tb = TestVectors::<SignedMul<4, 8, A>>::new(modulo);
+        if A::LATENCY > 0 {
+            tb = tb.clock("clk", |x| &mut x.clk).latency(A::LATENCY - 1);
+        }
+        let a = tb.input("a", |x| &mut x.a);
+        let b = tb.input("b", |x| &mut x.b);
+        let p = tb.output("p", |x| &x.p);
+        for va in -8..8 {
+            for vb in -8..8 {
+                tb.push(
+                    TestVector::new(&format!("{} * {}", va, vb))
+                        .set(a, va)
+                        .set(b, vb)
+                        .expect(p, va * vb),
+                );
+            }
+        }
+
+        tb.verify(&uut, 10_000).unwrap();
+    }
+
+    #[test]
+    fn test_producto_con_signo() {
+        todos_los_pares::<Operator>("SignedMul");
+        todos_los_pares::<ShiftAdd>("SignedMulShiftAdd");
+        todos_los_pares::<Booth>("SignedMulBooth");
+        todos_los_pares::<Wallace>("SignedMulWallace");
+        todos_los_pares::<Registered<Booth>>("SignedMulBoothRegistrado");
+        todos_los_pares::<Registered<Registered<Wallace>>>("SignedMulWallaceRegistrado2");
+    }
+
+    // Los N bits bajos de v (en complemento a 2 si es negativo)
+    fn bits<const N: usize>(v: i64) -> Bits<N> {
+        Bits::from(v as u64 & ((1 << N) - 1))
+    }
+
+    // Los mismos extremos de 8 bits con todos los algoritmos, con y sin signo
+    fn extremos<S: Signedness, A: MulAlgorithm>(valores: &[i64]) {
+        let mut uut = Multiplier::<8, 16, S, A>::default();
+        uut.connect_all();
+
+        let mut sim = Simulation::<Multiplier<8, 16, S, A>>::new();
+        let valores = valores.to_vec();
+        sim.add_testbench(move |mut ep: Sim<Multiplier<8, 16, S, A>>| {
+            let mut x = ep.init()?;
+            for &va in &valores {
+                for &vb in &valores {
+                    x.a.next = bits(va);
+                    x.b.next = bits(vb);
+                    x = ep.wait(1, x)?;
+                    sim_assert_eq!(ep, x.resul.val(), bits::<16>(va * vb), x);
+                }
+            }
+            ep.done(x)
+        });
+        sim.run(Box::new(uut), 1_000_000).unwrap();
+    }
+
+    #[test]
+    fn test_todos_los_algoritmos_de_8_bits() {
+        let con_signo = [-128, -127, -86, -2, -1, 0, 1, 3, 85, 126, 127];
+        let sin_signo = [0, 1, 2, 3, 85, 127, 128, 170, 254, 255];
+        extremos::<TwosComplement, Operator>(&con_signo);
+        extremos::<TwosComplement, ShiftAdd>(&con_signo);
+        extremos::<TwosComplement, Booth>(&con_signo);
+        extremos::<TwosComplement, Wallace>(&con_signo);
+        extremos::<Unsigned, Operator>(&sin_signo);
+        extremos::<Unsigned, ShiftAdd>(&sin_signo);
+        extremos::<Unsigned, Booth>(&sin_signo);
+        extremos::<Unsigned, Wallace>(&sin_signo);
+    }
+
+    #[test]
+    fn test_verilog_del_envoltorio() {
+        let mut uut = SignedMul::<4, 8>::default();
+        uut.connect_all();
+        let verilog = generate_verilog(&uut);
+        assert!(verilog.contains("output reg signed [7:0] p;"), "{}", verilog);
+        assert!(verilog.contains("nucleo$b = b;\n        p = nucleo$resul;"));
+        assert!(verilog.contains("always @(*) resul = $signed(a) * $signed(b);"));
+
+        let mut uut = Multiplier::<4, 8, Unsigned, Operator>::default();
+        uut.connect_all();
+        assert!(generate_verilog(&uut).contains("always @(*) resul = a * b;"));
+
+        // Con Registered el reloj llega al núcleo y el producto sale del
+        // registro
+        let mut uut = SignedMul::<4, 8, Registered<Booth>>::default();
+        uut.connect_all();
+        let verilog = generate_verilog(&uut);
+        assert!(verilog.contains("nucleo$clk = clk;"), "{}", verilog);
+        assert!(verilog.contains("registro$d = nucleo$resul;"));
+        assert!(verilog.contains("resul = registro$q;"));
+        assert_eq!(<Registered<Registered<Booth>>>::LATENCY, 2);
+        assert_eq!(<Registered<Booth>>::name(), "Booth base 4 registrado");
+    }
+}
@@ -0,0 +1,199 @@
+// Desbordamiento de los acumuladores.
+//
+// Los acumuladores de los diseños suman en O bits, y si la suma no cabe el
+// resultado da la vuelta sin avisar. OverflowAdder es el sumador de esos
+// acumuladores: además de la suma da `overflow` cuando no cabe (acarreo de
+// salida sin signo; en complemento a 2, dos sumandos del mismo signo con una
+// suma del signo contrario), y con M = Saturate la suma se queda en el valor
+// máximo o mínimo en vez de dar la vuelta. Con Wrap el resultado es el de
+// siempre, sólo se añade el aviso. Cada diseño guarda su propio `overflow`
+// acumulado desde el inicio del cálculo.
+use crate::modo_sin_hardware;
+use crate::mul::{Signedness, Unsigned};
+use rust_hdl::prelude::*;
+
+// Qué hace la suma cuando no cabe. Wrap y Saturate no generan hardware:
+// solo eligen el valor de la constante `saturar`
+pub trait OverflowMode: Block + Clone + Default + Send + 'static {
+    const SATURATE: bool;
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Wrap;
+
+#[derive(Clone, Debug, Default)]
+pub struct Saturate;
+
+impl OverflowMode for Wrap {
+    const SATURATE: bool = false;
+}
+
+impl OverflowMode for Saturate {
+    const SATURATE: bool = true;
+}
+
+modo_sin_hardware!(Wrap, Saturate);
+
+// Modelo de OverflowAdder para los test: la suma de a y b en `ancho` bits
+// (con signo en [-2^(ancho-1), 2^(ancho-1)) y sin signo en [0, 2^ancho)) y si
+// se ha desbordado
+pub fn overflow_add<S: Signedness, M: OverflowMode>(ancho: usize, a: i64, b: i64) -> (i64, bool) {
+    let (minimo, maximo) = if S::SIGNED {
+        (-(1 << (ancho - 1)), (1 << (ancho - 1)) - 1)
+    } else {
+        (0, (1 << ancho) - 1)
+    };
+    let suma = a + b;
+    if (minimo..=maximo).contains(&suma) {
+        (suma, false)
+    } else if M::SATURATE {
+        (suma.clamp(minimo, maximo), true)
+    } else {
+        // Da la vuelta: los `ancho` bits bajos, leídos con o sin signo
+        let bajos = suma.rem_euclid(1 << ancho);
+        (if bajos > maximo { bajos - (1 << ancho) } else { bajos }, true)
+    }
+}
+
+#[derive(LogicBlock, Clone)]
+pub struct OverflowAdder<const O: usize, S: Signedness = Unsigned, M: OverflowMode = Wrap> {
+    pub a: Signal<In, Bits<O>>,
+    pub b: Signal<In, Bits<O>>,
+    pub sum: Signal<Out, Bits<O>>,
+    pub overflow: Signal<Out, Bit>,
+    // La suma en O bits, dando la vuelta
+    suma: Signal<Local, Bits<O>>,
+    // Valores a los que se satura
+    maximo: Constant<Bits<O>>,
+    minimo: Constant<Bits<O>>,
+    // 1 en complemento a 2, 0 sin signo
+    con_signo: Constant<Bit>,
+    // 1 con Saturate, 0 con Wrap
+    saturar: Constant<Bit>,
+    signo: S,
+    modo: M,
+}
+
+impl<const O: usize, S: Signedness, M: OverflowMode> Default for OverflowAdder<O, S, M> {
+    fn default() -> Self {
+        const {
+            assert!(O >= 2, "O tiene que ser al menos 2");
+        }
+        let (maximo, minimo) = if S::SIGNED {
+            (
+                Bits::<O>::mask().replace_bit(O - 1, false),
+                Bits::<O>::default().replace_bit(O - 1, true),
+            )
+        } else {
+            (Bits::<O>::mask(), Bits::<O>::default())
+        };
+        Self {
+            a: Default::default(),
+            b: Default::default(),
+            sum: Default::default(),
+            overflow: Default::default(),
+            suma: Default::default(),
+            maximo: Constant::new(maximo),
+            minimo: Constant::new(minimo),
+            con_signo: Constant::new(S::SIGNED),
+            saturar: Constant::new(M::SATURATE),
+            signo: Default::default(),
+            modo: Default::default(),
+        }
+    }
+}
+
+impl<const O: usize, S: Signedness, M: OverflowMode> Logic for OverflowAdder<O, S, M> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.suma.next = self.a.val() + self.b.val();
+        self.sum.next = self.suma.val();
+        self.overflow.next = false;
+        if self.con_signo.val() {
+            // i = O - 1, el bit de signo: bucle de una vuelta, ver tfg_hdl_common
+            for i in (O - 1)..O {
+                if (self.a.val().get_bit(i) == self.b.val().get_bit(i))
+                    & (self.suma.val().get_bit(i) != self.a.val().get_bit(i))
+                {
+                    self.overflow.next = true;
+                    if self.saturar.val() {
+                        if self.a.val().get_bit(i) {
+                            self.sum.next = self.minimo.val();
+                        } else {
+                            self.sum.next = self.maximo.val();
+                        }
+                    }
+                }
+            }
+        } else if self.suma.val() < self.a.val() {
+            self.overflow.next = true;
+            if self.saturar.val() {
+                self.sum.next = self.maximo.val();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TestVector, TestVectors, TwosComplement};
+
+    #[test]
+    fn test_modelo() {
+        assert_eq!(overflow_add::<Unsigned, Wrap>(8, 200, 100), (44, true));
+        assert_eq!(overflow_add::<Unsigned, Saturate>(8, 200, 100), (255, true));
+        assert_eq!(overflow_add::<Unsigned, Saturate>(8, 200, 55), (255, false));
+        assert_eq!(overflow_add::<TwosComplement, Wrap>(8, 100, 100), (-56, true));
+        assert_eq!(overflow_add::<TwosComplement, Wrap>(8, -100, -100), (56, true));
+        assert_eq!(overflow_add::<TwosComplement, Saturate>(8, 100, 100), (127, true));
+        assert_eq!(overflow_add::<TwosComplement, Saturate>(8, -100, -100), (-128, true));
+        assert_eq!(overflow_add::<TwosComplement, Saturate>(8, -100, 100), (0, false));
+    }
+
+    // Todos los pares de 4 bits, igual en RustHDL y en Icarus
+    fn todos_los_pares<S: Signedness, M: OverflowMode>(modulo: &str) {
+        let mut uut = OverflowAdder::<4, S, M>::default();
+        uut.connect_all();
+
+        let mut tb = TestVectors::<OverflowAdder<4, S, M>>::new(modulo);
+        let a = tb.input("a", |x| &mut x.a);
+        let b = tb.input("b", |x| &mut x.b);
+        let sum = tb.output("sum", |x| &x.sum);
+        let overflow = tb.output("overflow", |x| &x.overflow);
+        let valores = if S::SIGNED { -8..8 } else { 0..16 };
+        for va in valores.clone() {
+            for vb in valores.clone() {
+                let (esperado, desborda) = overflow_add::<S, M>(4, va, vb);
+                tb.push(
+                    TestVector::new(&format!("{} + {}", va, vb))
+                        .set(a, va & 0xf)
+                        .set(b, vb & 0xf)
+                        .expect(sum, esperado & 0xf)
+                        .expect(overflow, desborda as i64),
+                );
+            }
+        }
+
+        tb.verify(&uut, 10_000).unwrap();
+    }
+
+    #[test]
+    fn test_sumador_con_desbordamiento() {
+        todos_los_pares::<Unsigned, Wrap>("OverflowAdder");
+        todos_los_pares::<Unsigned, Saturate>("OverflowAdderSaturate");
+        todos_los_pares::<TwosComplement, Wrap>("OverflowAdderSigned");
+        todos_los_pares::<TwosComplement, Saturate>("OverflowAdderSignedSaturate");
+    }
+
+    #[test]
+    fn test_verilog_sumador() {
+        let mut uut = OverflowAdder::<8, TwosComplement, Saturate>::default();
+        uut.connect_all();
+        let verilog = generate_verilog(&uut);
+        assert!(verilog.contains("localparam  maximo = 8'h7f;"), "{}", verilog);
+        assert!(verilog.contains("localparam  minimo = 8'h80;"));
+        assert!(verilog.contains("localparam  saturar = 1'b1;"));
+        assert!(verilog.contains("suma[7] != a[7]"));
+    }
+}
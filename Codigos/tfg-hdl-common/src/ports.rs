@@ -0,0 +1,401 @@
+// Puertos de Verilog con nombres legales para los arrays de señales.
+//
+// RustHDL saca cada elemento de un array de puertos como un puerto aparte
+// (inputs$0, inputs$1...), y muchas herramientas no aceptan el $ en los
+// puertos del módulo principal. Aquí el diseño se exporta igual que con
+// generate_named_verilog pero con el nombre <nombre>_rusthdl, y se añade un
+// módulo envoltorio <nombre> con los puertos empaquetados en un vector
+// (inputs[63:0]) o renombrados (inputs_0). Junto al Verilog sale un mapa de
+// puertos que dice qué señal de RustHDL hay detrás de cada trozo.
+use crate::verilog::{generate_named_verilog, is_verilog_identifier, VerilogError, VerilogHeader};
+use rust_hdl::prelude::*;
+use rust_hdl_core::atom::{is_atom_signed, Atom, AtomKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortStyle {
+    // Un vector por array: inputs$0..inputs$7 pasan a ser inputs[63:0], con
+    // el elemento 0 en los bits bajos. Los demás puertos con $ (los de una
+    // interfaz, por ejemplo) se renombran como en Flat
+    Packed,
+    // Un puerto por elemento con _ en lugar de $: inputs_0..inputs_7
+    Flat,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerilogExport {
+    // Módulo envoltorio seguido del diseño generado por RustHDL
+    pub verilog: String,
+    // Una línea por señal de RustHDL: puerto Verilog, dirección, bits y
+    // nombre original
+    pub port_map: String,
+}
+
+// Puerto del módulo principal tal y como lo genera RustHDL
+#[derive(Debug, Clone)]
+struct PuertoRustHdl {
+    nombre: String,
+    direccion: &'static str,
+    bits: usize,
+    signed: bool,
+}
+
+// Recoge los puertos del módulo principal, en el mismo orden que RustHDL
+#[derive(Default)]
+struct Puertos {
+    profundidad: usize,
+    espacios: Vec<String>,
+    puertos: Vec<PuertoRustHdl>,
+}
+
+impl Probe for Puertos {
+    fn visit_start_scope(&mut self, _name: &str, _node: &dyn Block) {
+        self.profundidad += 1;
+    }
+
+    fn visit_start_namespace(&mut self, name: &str, _node: &dyn Block) {
+        if self.profundidad == 1 {
+            self.espacios.push(name.into());
+        }
+    }
+
+    fn visit_atom(&mut self, name: &str, signal: &dyn Atom) {
+        if self.profundidad != 1 {
+            return;
+        }
+        let direccion = match signal.kind() {
+            AtomKind::InputParameter => "input",
+            AtomKind::OutputParameter | AtomKind::OutputPassthrough => "output",
+            AtomKind::InOutParameter => "inout",
+            _ => return,
+        };
+        let mut nombre = self.espacios.join("$");
+        if !nombre.is_empty() {
+            nombre.push('$');
+        }
+        nombre.push_str(name);
+        self.puertos.push(PuertoRustHdl {
+            nombre,
+            direccion,
+            bits: signal.bits(),
+            signed: is_atom_signed(signal),
+        });
+    }
+
+    fn visit_end_namespace(&mut self, _name: &str, _node: &dyn Block) {
+        if self.profundidad == 1 {
+            self.espacios.pop();
+        }
+    }
+
+    fn visit_end_scope(&mut self, _name: &str, _node: &dyn Block) {
+        self.profundidad -= 1;
+    }
+}
+
+// Puerto del envoltorio y los puertos de RustHDL que lo forman, de los bits
+// bajos a los altos
+struct PuertoLegal<'a> {
+    nombre: String,
+    // Si junta los elementos de un array
+    array: bool,
+    partes: Vec<&'a PuertoRustHdl>,
+}
+
+impl PuertoLegal<'_> {
+    fn bits(&self) -> usize {
+        self.partes.iter().map(|p| p.bits).sum()
+    }
+
+    fn declaracion(&self) -> String {
+        let parte = self.partes[0];
+        let signo = if self.partes.iter().all(|p| p.signed) { "signed " } else { "" };
+        let rango = if self.bits() > 1 {
+            format!("[{}:0] ", self.bits() - 1)
+        } else {
+            String::new()
+        };
+        format!("{} wire {}{}{};", parte.direccion, signo, rango, self.nombre)
+    }
+
+    // Trozo del puerto del envoltorio que corresponde a cada parte
+    fn trozos(&self) -> Vec<(&PuertoRustHdl, String)> {
+        if self.partes.len() == 1 && self.partes[0].nombre == self.nombre {
+            return vec![(self.partes[0], self.nombre.clone())];
+        }
+        let mut bajo = 0;
+        let mut trozos = vec![];
+        for parte in &self.partes {
+            let trozo = if self.partes.len() == 1 {
+                self.nombre.clone()
+            } else if parte.bits == 1 {
+                format!("{}[{}]", self.nombre, bajo)
+            } else {
+                format!("{}[{}:{}]", self.nombre, bajo + parte.bits - 1, bajo)
+            };
+            trozos.push((*parte, trozo));
+            bajo += parte.bits;
+        }
+        trozos
+    }
+}
+
+// Nombre del array si el puerto es uno de sus elementos, es decir, si todo lo
+// que va detrás del primer $ son índices (valores$2, matriz$1$0)
+fn nombre_de_array(nombre: &str) -> Option<&str> {
+    let (array, indices) = nombre.split_once('$')?;
+    let es_indice = |i: &str| !i.is_empty() && i.bytes().all(|c| c.is_ascii_digit());
+    indices.split('$').all(es_indice).then_some(array)
+}
+
+fn agrupar(puertos: &[PuertoRustHdl], estilo: PortStyle) -> Result<Vec<PuertoLegal<'_>>, VerilogError> {
+    let mut legales: Vec<PuertoLegal> = vec![];
+    for puerto in puertos {
+        let (nombre, array) = match (estilo, nombre_de_array(&puerto.nombre)) {
+            (PortStyle::Packed, Some(array)) => (array.to_string(), true),
+            _ => (puerto.nombre.replace('$', "_"), false),
+        };
+        match legales.iter_mut().find(|l| l.nombre == nombre) {
+            // Sólo se juntan los elementos de un mismo array y dirección
+            Some(legal) if array && legal.array && legal.partes[0].direccion == puerto.direccion => {
+                legal.partes.push(puerto)
+            }
+            Some(_) => return Err(VerilogError::PortName(nombre)),
+            None => legales.push(PuertoLegal {
+                nombre,
+                array,
+                partes: vec![puerto],
+            }),
+        }
+    }
+    match legales.iter().find(|l| !is_verilog_identifier(&l.nombre)) {
+        Some(legal) => Err(VerilogError::PortName(legal.nombre.clone())),
+        None => Ok(legales),
+    }
+}
+
+// Genera el diseño con un envoltorio `nombre` cuyos puertos siguen `estilo`
+pub fn generate_verilog_ports<T: Block>(
+    uut: &T,
+    nombre: &str,
+    estilo: PortStyle,
+) -> Result<VerilogExport, VerilogError> {
+    let nucleo = format!("{}_rusthdl", nombre);
+    let codigo = generate_named_verilog(uut, &nucleo)?;
+    let mut recorrido = Puertos::default();
+    uut.accept(nombre, &mut recorrido);
+    let legales = agrupar(&recorrido.puertos, estilo)?;
+
+    let nombres: Vec<&str> = legales.iter().map(|l| l.nombre.as_str()).collect();
+    let mut envoltorio = format!("\nmodule {}({});\n", nombre, nombres.join(","));
+    for legal in &legales {
+        envoltorio.push_str(&format!("    {}\n", legal.declaracion()));
+    }
+    envoltorio.push_str(&format!("\n    {} nucleo(\n", nucleo));
+    let mut conexiones = vec![];
+    let mut port_map = format!("# Puertos de {} ({:?})\n# puerto dirección bits señal_rusthdl\n", nombre, estilo);
+    for legal in &legales {
+        for (parte, trozo) in legal.trozos() {
+            conexiones.push(format!("        .{}({})", parte.nombre, trozo));
+            port_map.push_str(&format!("{} {} {} {}\n", trozo, parte.direccion, parte.bits, parte.nombre));
+        }
+    }
+    envoltorio.push_str(&conexiones.join(",\n"));
+    envoltorio.push_str("\n    );\nendmodule\n");
+
+    Ok(VerilogExport {
+        verilog: format!("{}{}", envoltorio, codigo),
+        port_map,
+    })
+}
+
+// Igual que generate_verilog_ports, con la cabecera de comentario de
+// generate_named_verilog_with_header
+pub fn generate_verilog_ports_with_header<T: Block>(
+    uut: &T,
+    nombre: &str,
+    estilo: PortStyle,
+    cabecera: &VerilogHeader,
+) -> Result<VerilogExport, VerilogError> {
+    let mut exportado = generate_verilog_ports(uut, nombre, estilo)?;
+    exportado.verilog = format!("{}{}", cabecera.render(nombre), exportado.verilog);
+    Ok(exportado)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IcarusRunner;
+
+    #[derive(LogicBlock, Clone, Default)]
+    struct Selector {
+        pub valores: [Signal<In, Signed<4>>; 3],
+        pub activo: [Signal<In, Bit>; 2],
+        pub salida: Signal<Out, Signed<4>>,
+    }
+
+    impl Logic for Selector {
+        #[hdl_gen]
+        fn update(&mut self) {
+            if self.activo[1].val() {
+                self.salida.next = self.valores[2].val();
+            } else if self.activo[0].val() {
+                self.salida.next = self.valores[1].val();
+            } else {
+                self.salida.next = self.valores[0].val();
+            }
+        }
+    }
+
+    fn selector() -> Selector {
+        let mut uut = Selector::default();
+        uut.connect_all();
+        uut
+    }
+
+    #[test]
+    fn test_puertos_empaquetados() {
+        let exportado = generate_verilog_ports(&selector(), "Selector", PortStyle::Packed).unwrap();
+        let verilog = &exportado.verilog;
+        assert!(verilog.contains("module Selector(valores,activo,salida);"));
+        assert!(verilog.contains("    input wire signed [11:0] valores;"));
+        assert!(verilog.contains("    input wire [1:0] activo;"));
+        assert!(verilog.contains("    output wire signed [3:0] salida;"));
+        assert!(verilog.contains("    Selector_rusthdl nucleo(\n        .valores$0(valores[3:0]),"));
+        assert!(verilog.contains("        .valores$2(valores[11:8]),"));
+        assert!(verilog.contains("        .activo$1(activo[1]),"));
+        assert!(verilog.contains("        .salida(salida)\n    );"));
+        assert!(verilog.contains("module Selector_rusthdl(valores$0,"));
+        assert_eq!(
+            exportado.port_map,
+            "# Puertos de Selector (Packed)\n# puerto dirección bits señal_rusthdl\n\
+             valores[3:0] input 4 valores$0\nvalores[7:4] input 4 valores$1\n\
+             valores[11:8] input 4 valores$2\nactivo[0] input 1 activo$0\n\
+             activo[1] input 1 activo$1\nsalida output 4 salida\n"
+        );
+    }
+
+    #[test]
+    fn test_puertos_renombrados() {
+        let exportado = generate_verilog_ports(&selector(), "Selector", PortStyle::Flat).unwrap();
+        let verilog = &exportado.verilog;
+        assert!(verilog.contains("module Selector(valores_0,valores_1,valores_2,activo_0,activo_1,salida);"));
+        assert!(verilog.contains("    input wire signed [3:0] valores_1;"));
+        assert!(verilog.contains("    input wire activo_0;"));
+        assert!(verilog.contains("        .valores$1(valores_1),"));
+        assert!(exportado.port_map.contains("\nactivo_1 input 1 activo$1\n"));
+        // El envoltorio ya no tiene ningún $ en sus puertos
+        let cabecera = verilog.lines().find(|l| l.starts_with("module Selector(")).unwrap();
+        assert!(!cabecera.contains('$'));
+    }
+
+    #[test]
+    fn test_nombre_repetido() {
+        #[derive(LogicBlock, Clone, Default)]
+        struct Choque {
+            pub a: [Signal<In, Bit>; 2],
+            pub a_0: Signal<In, Bit>,
+            pub y: Signal<Out, Bit>,
+        }
+
+        impl Logic for Choque {
+            #[hdl_gen]
+            fn update(&mut self) {
+                self.y.next = self.a[0].val() ^ self.a[1].val() ^ self.a_0.val();
+            }
+        }
+
+        let mut uut = Choque::default();
+        uut.connect_all();
+        assert_eq!(
+            generate_verilog_ports(&uut, "Choque", PortStyle::Flat),
+            Err(VerilogError::PortName("a_0".into()))
+        );
+        assert!(generate_verilog_ports(&uut, "Choque", PortStyle::Packed).is_ok());
+    }
+
+    #[derive(LogicInterface, Clone, Default)]
+    struct Par {
+        pub a: Signal<In, Bit>,
+        pub b: Signal<In, Bit>,
+    }
+
+    // Los puertos de una interfaz no son elementos de un array aunque
+    // compartan el prefijo: x$a y x$b no se juntan en un vector x
+    #[test]
+    fn test_interfaz_no_es_array() {
+        #[derive(LogicBlock, Clone, Default)]
+        struct ConInterfaz {
+            pub x: Par,
+            pub v: [Signal<In, Bit>; 2],
+            pub y: Signal<Out, Bit>,
+        }
+
+        impl Logic for ConInterfaz {
+            #[hdl_gen]
+            fn update(&mut self) {
+                self.y.next = self.x.a.val() ^ self.x.b.val() ^ self.v[0].val() ^ self.v[1].val();
+            }
+        }
+
+        let mut uut = ConInterfaz::default();
+        uut.connect_all();
+        let exportado = generate_verilog_ports(&uut, "ConInterfaz", PortStyle::Packed).unwrap();
+        assert!(exportado.verilog.contains("module ConInterfaz(x_a,x_b,v,y);"), "{}", exportado.verilog);
+        assert!(exportado.verilog.contains("        .x$b(x_b),"));
+        assert!(exportado.verilog.contains("        .v$1(v[1]),"));
+    }
+
+    #[test]
+    fn test_interfaz_repetida() {
+        #[derive(LogicBlock, Clone, Default)]
+        struct Choque {
+            pub x: Par,
+            pub x_a: Signal<In, Bit>,
+            pub y: Signal<Out, Bit>,
+        }
+
+        impl Logic for Choque {
+            #[hdl_gen]
+            fn update(&mut self) {
+                self.y.next = self.x.a.val() ^ self.x.b.val() ^ self.x_a.val();
+            }
+        }
+
+        let mut uut = Choque::default();
+        uut.connect_all();
+        for estilo in [PortStyle::Packed, PortStyle::Flat] {
+            assert_eq!(
+                generate_verilog_ports(&uut, "Choque", estilo),
+                Err(VerilogError::PortName("x_a".into()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_con_icarus() {
+        let exportado = generate_verilog_ports(&selector(), "Selector", PortStyle::Packed).unwrap();
+        let testbench = r#"
+module test;
+    reg [11:0] valores;
+    reg [1:0] activo;
+    wire signed [3:0] salida;
+    Selector uut(.valores(valores), .activo(activo), .salida(salida));
+    initial begin
+        valores = {4'd7, 4'd2, 4'd1};
+        activo = 2'b11;
+        #1;
+        if (salida == 4'd7) $display("OK"); else $display("FALLO");
+        activo = 2'b01;
+        #1;
+        if (salida == 4'd2) $display("OK"); else $display("FALLO");
+        $finish;
+    end
+endmodule
+"#;
+        let codigo = format!("{}{}", testbench, exportado.verilog);
+        let salida = IcarusRunner::new().run_if_available("puertos_empaquetados", &codigo).unwrap();
+        if let Some(salida) = salida {
+            assert_eq!(salida.stdout.matches("OK").count(), 2, "{}", salida.stdout);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::mul::{Signedness, Unsigned};
+use rust_hdl::prelude::*;
+
+// Multiplicador de anchura genérica por sumas y desplazamientos, como Mul8x8:
+// por cada bit de b a 1 se suma a desplazado a su posición. La salida tiene
+// O >= 2W bits para que el producto quepa siempre.
+//
+// Con S = TwosComplement a, b y resul van en complemento a 2: a se extiende
+// con su signo hasta O bits y el bit de más peso de b, que vale -2^(W-1),
+// resta en vez de sumar. Como todo se hace módulo 2^O, el resultado es el
+// producto con signo en O bits.
+#[derive(LogicBlock, Clone)]
+pub struct ShiftAddMul<const W: usize, const O: usize, S: Signedness = Unsigned> {
+    pub a: Signal<In, Bits<W>>,
+    pub b: Signal<In, Bits<W>>,
+    pub resul: Signal<Out, Bits<O>>,
+    // a con la extensión de signo (o de ceros) a O bits
+    extendido: Signal<Local, Bits<O>>,
+    // i, para desplazar (Bits sólo se desplaza con literales o con Bits)
+    posiciones: [Constant<Bits<16>>; W],
+    // Unos por encima de los W bits de a
+    extension: Constant<Bits<O>>,
+    // 1 en complemento a 2, 0 sin signo
+    con_signo: Constant<Bit>,
+    modo: S,
+}
+
+impl<const W: usize, const O: usize, S: Signedness> Default for ShiftAddMul<W, O, S> {
+    fn default() -> Self {
+        const {
+            assert!(W >= 1, "W tiene que ser al menos 1");
+            assert!(O >= 2 * W, "el producto necesita al menos 2W bits");
+        }
+        Self {
+            a: Default::default(),
+            b: Default::default(),
+            resul: Default::default(),
+            extendido: Default::default(),
+            posiciones: std::array::from_fn(|i| Constant::new((i as u64).into())),
+            extension: Constant::new(!bit_cast::<O, W>(Bits::<W>::mask())),
+            con_signo: Constant::new(S::SIGNED),
+            modo: Default::default(),
+        }
+    }
+}
+
+impl<const W: usize, const O: usize, S: Signedness> Logic for ShiftAddMul<W, O, S> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        // i = W - 1, el bit de signo: bucles de una vuelta, ver tfg_hdl_common
+        self.extendido.next = bit_cast::<O, W>(self.a.val());
+        for i in (W - 1)..W {
+            if self.con_signo.val() & self.a.val().get_bit(i) {
+                self.extendido.next = bit_cast::<O, W>(self.a.val()) | self.extension.val();
+            }
+        }
+
+        self.resul.next = 0.into();
+        for i in 0..(W - 1) {
+            if self.b.val().get_bit(i) {
+                self.resul.next = self.resul.val() + (self.extendido.val() << self.posiciones[i].val());
+            }
+        }
+        for i in (W - 1)..W {
+            if self.b.val().get_bit(i) {
+                if self.con_signo.val() {
+                    self.resul.next = self.resul.val() - (self.extendido.val() << self.posiciones[i].val());
+                } else {
+                    self.resul.next = self.resul.val() + (self.extendido.val() << self.posiciones[i].val());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mul::TwosComplement;
+    use crate::{TestVector, TestVectors};
+
+    // Todos los pares de operandos de 4 bits. Los valores van en los puertos
+    // como bits, así que los negativos se pasan y se esperan en complemento
+    // a 2
+    fn todos_los_pares<S: Signedness>(modulo: &str, valores: std::ops::Range<i64>) {
+        let mut uut = ShiftAddMul::<4, 8, S>::default();
+        uut.connect_all();
+
+        let mut tb = TestVectors::<ShiftAddMul<4, 8, S>>::new(modulo);
+        let a = tb.input("a", |x| &mut x.a);
+        let b = tb.input("b", |x| &mut x.b);
+        let resul = tb.output("resul", |x| &x.resul);
+        for va in valores.clone() {
+            for vb in valores.clone() {
+                tb.push(
+                    TestVector::new(&format!("{} * {}", va, vb))
+                        .set(a, va & 0xf)
+                        .set(b, vb & 0xf)
+                        .expect(resul, (va * vb) & 0xff),
+                );
+            }
+        }
+
+        tb.verify(&uut, 10_000).unwrap();
+    }
+
+    #[test]
+    fn test_producto_sin_signo() {
+        todos_los_pares::<Unsigned>("ShiftAddMul", 0..16);
+
+        let mut uut = ShiftAddMul::<4, 8>::default();
+        uut.connect_all();
+        let verilog = generate_verilog(&uut);
+        assert!(verilog.contains("if (b[3]) begin"), "{}", verilog);
+        assert!(verilog.contains("localparam  con_signo = 1'b0;"));
+    }
+
+    #[test]
+    fn test_producto_con_signo() {
+        todos_los_pares::<TwosComplement>("ShiftAddMulSigned", -8..8);
+    }
+}
@@ -0,0 +1,38 @@
+// signed_bit_cast sin el desbordamiento de pila de RustHDL.
+//
+// En rust-hdl 0.46 signed_bit_cast extiende el signo de los negativos como
+// -signed_bit_cast(-x), pero para el valor mínimo (-128 en 8 bits) -x vuelve
+// a ser -128 y la función se llama a sí misma hasta desbordar la pila. Por eso
+// los test evitaban -128. Esta versión tiene el mismo nombre para que
+// #[hdl_gen] la siga traduciendo a $signed(...), así que basta con importarla
+// por delante del prelude:
+//
+//     use tfg_hdl_common::signed_bit_cast;
+use rust_hdl::prelude::{bit_cast, signed_cast, unsigned_cast, Bits, Signed};
+
+pub fn signed_bit_cast<const M: usize, const N: usize>(x: Signed<N>) -> Signed<M> {
+    let extendido = bit_cast::<M, N>(unsigned_cast(x));
+    if x.sign_bit() && M > N {
+        // Se rellenan con unos los bits por encima de N
+        signed_cast(extendido | !bit_cast::<M, N>(Bits::<N>::mask()))
+    } else {
+        signed_cast(extendido)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extension_de_signo() {
+        for valor in -128..128_i64 {
+            let x = Signed::<8>::from(valor);
+            assert_eq!(signed_bit_cast::<9, 8>(x), Signed::<9>::from(valor));
+            assert_eq!(signed_bit_cast::<16, 8>(x), Signed::<16>::from(valor));
+        }
+        // Al reducir la anchura se quedan los bits bajos, como en Verilog
+        assert_eq!(signed_bit_cast::<4, 8>(Signed::<8>::from(-1)), Signed::<4>::from(-1));
+        assert_eq!(signed_bit_cast::<4, 8>(Signed::<8>::from(17)), Signed::<4>::from(1));
+    }
+}
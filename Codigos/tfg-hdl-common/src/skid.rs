@@ -0,0 +1,316 @@
+// Skid buffer para interfaces valid/ready (como AXI-Stream).
+//
+// Un dato pasa de un lado a otro en el flanco en el que valid y ready están
+// los dos a 1. El productor mantiene el dato y valid hasta que se lo aceptan,
+// y el consumidor puede bajar ready cuando quiera sin que se pierda nada.
+//
+// Los puertos `*_in` son el canal de entrada (ready_in es una salida hacia el
+// productor) y los `*_out` el de salida (ready_out viene del consumidor). La
+// salida está registrada y ready_in sale directamente de un registro, de modo
+// que el ready del consumidor no atraviesa el bloque de forma combinacional:
+// si el consumidor para justo cuando entra un dato, ese dato se guarda en el
+// registro de reserva (`reserva`) y ready_in baja en el ciclo siguiente.
+//
+// Con ready_out siempre a 1 deja pasar un dato por ciclo con un ciclo de
+// latencia. rst es síncrono y activo a nivel alto.
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Clone, Default)]
+pub struct SkidBuffer<T: Synth> {
+    pub data_in: Signal<In, T>,
+    pub valid_in: Signal<In, Bit>,
+    pub ready_in: Signal<Out, Bit>,
+    pub data_out: Signal<Out, T>,
+    pub valid_out: Signal<Out, Bit>,
+    pub ready_out: Signal<In, Bit>,
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    // Dato que ha llegado con la salida parada
+    reserva: DFF<T>,
+    llena: DFF<Bit>,
+    // Registro de salida
+    salida: DFF<T>,
+    valida: DFF<Bit>,
+}
+
+impl<T: Synth> Logic for SkidBuffer<T> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.reserva.clock.next = self.clk.val();
+        self.llena.clock.next = self.clk.val();
+        self.salida.clock.next = self.clk.val();
+        self.valida.clock.next = self.clk.val();
+
+        // Por defecto todo se mantiene
+        self.reserva.d.next = self.reserva.q.val();
+        self.llena.d.next = self.llena.q.val();
+        self.salida.d.next = self.salida.q.val();
+        self.valida.d.next = self.valida.q.val();
+
+        if self.rst.val() {
+            self.llena.d.next = false;
+            self.valida.d.next = false;
+        } else if self.ready_out.val() | !self.valida.q.val() {
+            // La salida queda libre: primero sale lo que haya en reserva
+            if self.llena.q.val() {
+                self.salida.d.next = self.reserva.q.val();
+                self.valida.d.next = true;
+                self.llena.d.next = false;
+            } else {
+                self.salida.d.next = self.data_in.val();
+                self.valida.d.next = self.valid_in.val();
+            }
+        } else if self.valid_in.val() & !self.llena.q.val() {
+            // Salida parada: el dato aceptado espera en la reserva
+            self.reserva.d.next = self.data_in.val();
+            self.llena.d.next = true;
+        }
+
+        self.ready_in.next = !self.llena.q.val();
+        self.data_out.next = self.salida.q.val();
+        self.valid_out.next = self.valida.q.val();
+    }
+}
+
+// Interfaz valid/ready para un pipeline que avanza entero con `enable`, como
+// AdderTree o ReduceTree, que lleva un bit de válido al lado de cada etapa.
+//
+// `data` y `valid` son la salida de la última etapa, que se entrega a un
+// SkidBuffer. El pipeline avanza (enable a 1) si su última etapa está vacía o
+// si el SkidBuffer acepta el dato, y entonces también acepta un dato nuevo,
+// así que ready_in es el mismo enable. Como el ready_in del SkidBuffer sale
+// de un registro, ready_out no llega a ready_in de forma combinacional.
+//
+// Con ready_out siempre a 1 sale un dato por ciclo, un ciclo después de que
+// lo dé el pipeline. rst es síncrono y activo a nivel alto.
+#[derive(LogicBlock, Clone, Default)]
+pub struct PipelineHandshake<T: Synth> {
+    pub ready_in: Signal<Out, Bit>,
+    pub enable: Signal<Out, Bit>,
+    pub data: Signal<In, T>,
+    pub valid: Signal<In, Bit>,
+    pub data_out: Signal<Out, T>,
+    pub valid_out: Signal<Out, Bit>,
+    pub ready_out: Signal<In, Bit>,
+    pub clk: Signal<In, Clock>,
+    pub rst: Signal<In, Bit>,
+    salida: SkidBuffer<T>,
+}
+
+impl<T: Synth> Logic for PipelineHandshake<T> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.salida.clk.next = self.clk.val();
+        self.salida.rst.next = self.rst.val();
+
+        self.enable.next = !self.valid.val() | self.salida.ready_in.val();
+        self.ready_in.next = !self.valid.val() | self.salida.ready_in.val();
+
+        self.salida.data_in.next = self.data.val();
+        self.salida.valid_in.next = self.valid.val();
+        self.salida.ready_out.next = self.ready_out.val();
+        self.data_out.next = self.salida.data_out.val();
+        self.valid_out.next = self.salida.valid_out.val();
+    }
+}
+
+// Modelo ciclo a ciclo de un pipeline de `etapas` etapas con
+// PipelineHandshake, como AdderTreeStream o ReduceTreeStream: todas las
+// etapas avanzan a la vez si la última está vacía o si el SkidBuffer acepta
+// su dato. Con 0 etapas es el SkidBuffer solo. Sirve para calcular las
+// salidas esperadas de unos TestVectors con esperas en los dos lados; V es lo
+// que lleva cada dato (la suma, el valor y el índice...)
+#[derive(Debug, Clone)]
+pub struct StreamModel<V> {
+    etapas: Vec<Option<V>>,
+    reserva: Option<V>,
+    salida: Option<V>,
+}
+
+impl<V: Clone> StreamModel<V> {
+    pub fn new(etapas: usize) -> Self {
+        Self {
+            etapas: vec![None; etapas],
+            reserva: None,
+            salida: None,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.etapas.len());
+    }
+
+    // Si el pipeline avanza en el próximo flanco
+    fn avanza(&self) -> bool {
+        match self.etapas.last() {
+            Some(ultima) => ultima.is_none() || self.reserva.is_none(),
+            None => self.reserva.is_none(),
+        }
+    }
+
+    // Valor de ready_in hasta el próximo flanco
+    pub fn ready_in(&self) -> bool {
+        self.avanza()
+    }
+
+    // Dato en la salida (valid_out a 1) hasta el próximo flanco
+    pub fn output(&self) -> Option<&V> {
+        self.salida.as_ref()
+    }
+
+    // Un flanco con `entrada` en la entrada (None si valid_in está a 0). La
+    // entrada sólo se acepta si ready_in estaba a 1
+    pub fn step(&mut self, entrada: Option<V>, ready_out: bool) {
+        let avanza = self.avanza();
+        let al_buffer = match self.etapas.last() {
+            Some(ultima) => ultima.clone(),
+            None => entrada.clone(),
+        };
+        if ready_out || self.salida.is_none() {
+            self.salida = self.reserva.take().or(al_buffer);
+        } else if self.reserva.is_none() {
+            self.reserva = al_buffer;
+        }
+        if avanza && !self.etapas.is_empty() {
+            self.etapas.rotate_right(1);
+            self.etapas[0] = entrada;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TestVector, TestVectors};
+
+    #[test]
+    fn test_skid_buffer() {
+        let mut uut = SkidBuffer::<Bits<8>>::default();
+        uut.connect_all();
+
+        let mut tb = TestVectors::<SkidBuffer<Bits<8>>>::new("SkidBuffer").clock("clk", |x| &mut x.clk);
+        let rst = tb.input("rst", |x| &mut x.rst);
+        let data_in = tb.input("data_in", |x| &mut x.data_in);
+        let valid_in = tb.input("valid_in", |x| &mut x.valid_in);
+        let ready_out = tb.input("ready_out", |x| &mut x.ready_out);
+        let ready_in = tb.output("ready_in", |x| &x.ready_in);
+        let data_out = tb.output("data_out", |x| &x.data_out);
+        let valid_out = tb.output("valid_out", |x| &x.valid_out);
+
+        // Cada vector son las entradas antes del flanco y las salidas justo
+        // después
+        tb.extend([
+            TestVector::new("reset")
+                .set(rst, 1)
+                .set(valid_in, 0)
+                .set(ready_out, 0)
+                .expect(valid_out, 0)
+                .expect(ready_in, 1),
+            TestVector::new("dato 1")
+                .set(rst, 0)
+                .set(data_in, 1)
+                .set(valid_in, 1)
+                .set(ready_out, 1)
+                .expect(data_out, 1)
+                .expect(valid_out, 1)
+                .expect(ready_in, 1),
+            TestVector::new("dato 2")
+                .set(data_in, 2)
+                .expect(data_out, 2)
+                .expect(valid_out, 1)
+                .expect(ready_in, 1),
+            // El consumidor para: el 2 se queda en la salida y el 3 en reserva
+            TestVector::new("parada")
+                .set(data_in, 3)
+                .set(ready_out, 0)
+                .expect(data_out, 2)
+                .expect(valid_out, 1)
+                .expect(ready_in, 0),
+            // Con la reserva llena el productor mantiene el 4
+            TestVector::new("reserva llena")
+                .set(data_in, 4)
+                .expect(data_out, 2)
+                .expect(valid_out, 1)
+                .expect(ready_in, 0),
+            TestVector::new("sale la reserva")
+                .set(ready_out, 1)
+                .expect(data_out, 3)
+                .expect(valid_out, 1)
+                .expect(ready_in, 1),
+            TestVector::new("entra el 4")
+                .expect(data_out, 4)
+                .expect(valid_out, 1)
+                .expect(ready_in, 1),
+            TestVector::new("sin datos")
+                .set(valid_in, 0)
+                .expect(valid_out, 0)
+                .expect(ready_in, 1),
+            // Sin dato válido en la salida no hace falta ready_out
+            TestVector::new("salida vacía")
+                .set(data_in, 5)
+                .set(valid_in, 1)
+                .set(ready_out, 0)
+                .expect(data_out, 5)
+                .expect(valid_out, 1)
+                .expect(ready_in, 1),
+            TestVector::new("espera")
+                .set(valid_in, 0)
+                .expect(data_out, 5)
+                .expect(valid_out, 1)
+                .expect(ready_in, 1),
+            TestVector::new("vaciado")
+                .set(ready_out, 1)
+                .expect(valid_out, 0)
+                .expect(ready_in, 1),
+        ]);
+
+        tb.verify(&uut, 1_000).unwrap();
+    }
+
+    // Esperas pseudoaleatorias en los dos lados, con las salidas esperadas
+    // sacadas de StreamModel sin etapas
+    #[test]
+    fn test_skid_buffer_con_modelo() {
+        let mut uut = SkidBuffer::<Bits<8>>::default();
+        uut.connect_all();
+
+        let mut tb = TestVectors::<SkidBuffer<Bits<8>>>::new("SkidBufferModelo").clock("clk", |x| &mut x.clk);
+        let rst = tb.input("rst", |x| &mut x.rst);
+        let data_in = tb.input("data_in", |x| &mut x.data_in);
+        let valid_in = tb.input("valid_in", |x| &mut x.valid_in);
+        let ready_out = tb.input("ready_out", |x| &mut x.ready_out);
+        let ready_in = tb.output("ready_in", |x| &x.ready_in);
+        let data_out = tb.output("data_out", |x| &x.data_out);
+        let valid_out = tb.output("valid_out", |x| &x.valid_out);
+
+        let mut modelo = StreamModel::new(0);
+        tb.push(TestVector::new("reset").set(rst, 1).set(valid_in, 0).set(ready_out, 0));
+        let (mut siguiente, mut azar) = (0, 0x2545_f491_u64);
+        for ciclo in 0..300 {
+            azar ^= azar << 13;
+            azar ^= azar >> 7;
+            azar ^= azar << 17;
+            let (valido, listo) = (azar % 3 != 0, azar % 5 > 1);
+            let mut vector = TestVector::new(&format!("ciclo {}", ciclo))
+                .set(rst, 0)
+                .set(data_in, siguiente)
+                .set(valid_in, valido as i64)
+                .set(ready_out, listo as i64);
+            if valido && modelo.ready_in() {
+                modelo.step(Some(siguiente), listo);
+                siguiente = (siguiente + 1) % 256;
+            } else {
+                modelo.step(valido.then_some(siguiente), listo);
+            }
+            vector = vector
+                .expect(ready_in, modelo.ready_in() as i64)
+                .expect(valid_out, modelo.output().is_some() as i64);
+            if let Some(&dato) = modelo.output() {
+                vector = vector.expect(data_out, dato);
+            }
+            tb.push(vector);
+        }
+
+        tb.verify(&uut, 10_000).unwrap();
+    }
+}
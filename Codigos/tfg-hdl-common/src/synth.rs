@@ -0,0 +1,552 @@
+// Síntesis con Yosys e informe de área.
+//
+// Automatiza los pasos de "Como usar yosys.txt" (read_verilog, synth...) para
+// poder comparar el área del Verilog que genera RustHDL con la del Verilog
+// escrito a mano. Se sintetiza dos veces el mismo diseño aplanado: una a
+// puertas genéricas, de la que salen las celdas y los flip-flops, y otra
+// mapeada a LUTs con abc, que da una estimación para FPGA. Las estadísticas
+// se leen del `stat -json` de Yosys, y la profundidad (LUTs en el camino
+// combinacional más largo) del `ltp -noff` de la versión con LUTs.
+use crate::icarus::{directorio_temporal, ejecutar, IcarusError};
+use crate::verilog::fecha_utc;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+use std::time::{Duration, SystemTime};
+
+const CELDAS: &str = "celdas.json";
+const LUTS: &str = "luts.json";
+const PROFUNDIDAD: &str = "ltp.txt";
+
+#[derive(Debug)]
+pub enum YosysError {
+    // No se encuentra yosys en el PATH
+    NotInstalled(String),
+    // Error de entrada/salida preparando la síntesis o leyendo el informe
+    Io(io::Error),
+    // Yosys ha terminado con error (fichero que no compila, sin módulo top...)
+    Synthesis { status: ExitStatus, stderr: String },
+    // Yosys no ha terminado dentro del tiempo máximo
+    Timeout { limit: Duration },
+    // El `stat -json` o el `ltp` no tienen el formato esperado
+    Report(String),
+}
+
+impl fmt::Display for YosysError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            YosysError::NotInstalled(tool) => write!(f, "{} no está instalado o no está en el PATH", tool),
+            YosysError::Io(err) => write!(f, "error de E/S ejecutando Yosys: {}", err),
+            YosysError::Synthesis { status, stderr } => write!(f, "yosys falló ({}):\n{}", status, stderr),
+            YosysError::Timeout { limit } => write!(f, "yosys no terminó en {:?}", limit),
+            YosysError::Report(motivo) => write!(f, "informe de Yosys inesperado: {}", motivo),
+        }
+    }
+}
+
+impl std::error::Error for YosysError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            YosysError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for YosysError {
+    fn from(err: io::Error) -> Self {
+        YosysError::Io(err)
+    }
+}
+
+// ejecutar() es el mismo que usa Icarus, así que sus errores se traducen aquí
+impl From<IcarusError> for YosysError {
+    fn from(err: IcarusError) -> Self {
+        match err {
+            IcarusError::NotInstalled(tool) => YosysError::NotInstalled(tool),
+            IcarusError::Io(err) => YosysError::Io(err),
+            IcarusError::Timeout { limit, .. } => YosysError::Timeout { limit },
+            otro => YosysError::Report(otro.to_string()),
+        }
+    }
+}
+
+// Área de un diseño después de la síntesis
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SynthStats {
+    // Celdas tras `synth`, contando los flip-flops
+    pub cells: u64,
+    // Celdas de tipo flip-flop ($_DFF_P_, $_SDFFE_PP0P_...)
+    pub flip_flops: u64,
+    // LUTs tras mapear con `abc -lut`
+    pub luts: u64,
+    // LUTs en el camino combinacional más largo, sin contar los flip-flops
+    pub depth: u64,
+    // Celdas de `synth` por tipo
+    pub cells_by_type: BTreeMap<String, u64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct YosysRunner {
+    yosys: String,
+    timeout: Duration,
+    lut_size: usize,
+}
+
+impl Default for YosysRunner {
+    fn default() -> Self {
+        Self {
+            yosys: "yosys".into(),
+            timeout: Duration::from_secs(300),
+            lut_size: 4,
+        }
+    }
+}
+
+impl YosysRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Tiempo máximo de la síntesis
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    // Entradas de las LUTs para la estimación (4 por defecto)
+    pub fn lut_size(mut self, entradas: usize) -> Self {
+        self.lut_size = entradas;
+        self
+    }
+
+    // Ruta alternativa al ejecutable de Yosys
+    pub fn tool(mut self, yosys: &str) -> Self {
+        self.yosys = yosys.into();
+        self
+    }
+
+    // Comprueba si yosys se puede lanzar
+    pub fn available(&self) -> bool {
+        Command::new(&self.yosys)
+            .arg("-V")
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok()
+    }
+
+    // Script de Yosys para `fichero`; el módulo principal lo elige Yosys
+    // (el que no instancia nadie)
+    fn script(&self, fichero: &Path) -> String {
+        format!(
+            "read_verilog \"{}\"; synth -flatten -auto-top; tee -q -o {} stat -json; \
+             abc -lut {}; opt_clean; tee -q -o {} stat -json; tee -q -o {} ltp -noff",
+            fichero.display(),
+            CELDAS,
+            self.lut_size,
+            LUTS,
+            PROFUNDIDAD
+        )
+    }
+
+    // Sintetiza `fichero` y devuelve su área
+    pub fn synthesize(&self, fichero: &Path) -> Result<SynthStats, YosysError> {
+        let fichero = fichero.canonicalize()?;
+        let nombre = fichero
+            .file_stem()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let dir = directorio_temporal("yosys", &nombre)?;
+        let resultado = self.sintetizar_en(&fichero, &dir);
+        let _ = std::fs::remove_dir_all(&dir);
+        resultado
+    }
+
+    fn sintetizar_en(&self, fichero: &Path, dir: &Path) -> Result<SynthStats, YosysError> {
+        let mut comando = Command::new(&self.yosys);
+        comando.args(["-q", "-p", &self.script(fichero)]).current_dir(dir);
+        let (status, _stdout, stderr) = ejecutar(comando, &self.yosys, self.timeout)?;
+        if !status.success() {
+            return Err(YosysError::Synthesis { status, stderr });
+        }
+        let mut estadisticas = parse_stat_json(&std::fs::read_to_string(dir.join(CELDAS))?)?;
+        estadisticas.luts = parse_stat_json(&std::fs::read_to_string(dir.join(LUTS))?)?
+            .cells_by_type
+            .get("$lut")
+            .copied()
+            .unwrap_or(0);
+        estadisticas.depth = parse_ltp(&std::fs::read_to_string(dir.join(PROFUNDIDAD))?)?;
+        Ok(estadisticas)
+    }
+
+    // Sintetiza el Verilog de `fuente` (por ejemplo el de generate_verilog),
+    // guardándolo antes como <nombre>.v en un directorio temporal
+    pub fn synthesize_source(&self, nombre: &str, fuente: &str) -> Result<SynthStats, YosysError> {
+        let dir = directorio_temporal("yosys-fuente", nombre)?;
+        let fichero = dir.join(format!("{}.v", nombre));
+        let resultado = std::fs::write(&fichero, fuente)
+            .map_err(YosysError::from)
+            .and_then(|_| self.synthesize(&fichero));
+        let _ = std::fs::remove_dir_all(&dir);
+        resultado
+    }
+
+    // Igual que synthesize, pero si Yosys no está instalado avisa por
+    // pantalla y devuelve None
+    pub fn synthesize_if_available(&self, fichero: &Path) -> Result<Option<SynthStats>, YosysError> {
+        match self.synthesize(fichero) {
+            Ok(estadisticas) => Ok(Some(estadisticas)),
+            Err(YosysError::NotInstalled(tool)) => {
+                println!("{} no está instalado, se omite la síntesis", tool);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+// Lee la salida de `stat -json`. Con -flatten sólo queda un módulo, pero si
+// hay varios se usa el resumen "design" o, si falta, la suma de todos
+pub fn parse_stat_json(texto: &str) -> Result<SynthStats, YosysError> {
+    let json: Value = serde_json::from_str(texto).map_err(|err| YosysError::Report(err.to_string()))?;
+    let bloques: Vec<&Value> = match json.get("design") {
+        Some(design) => vec![design],
+        None => match json.get("modules").and_then(Value::as_object) {
+            Some(modulos) => modulos.values().collect(),
+            None => return Err(YosysError::Report("no hay \"design\" ni \"modules\"".into())),
+        },
+    };
+
+    let mut estadisticas = SynthStats::default();
+    for bloque in bloques {
+        estadisticas.cells += bloque
+            .get("num_cells")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| YosysError::Report("falta num_cells".into()))?;
+        if let Some(tipos) = bloque.get("num_cells_by_type").and_then(Value::as_object) {
+            for (tipo, cuenta) in tipos {
+                let cuenta = cuenta
+                    .as_u64()
+                    .ok_or_else(|| YosysError::Report(format!("cuenta no numérica para {}", tipo)))?;
+                *estadisticas.cells_by_type.entry(tipo.clone()).or_insert(0) += cuenta;
+            }
+        }
+    }
+    estadisticas.flip_flops = estadisticas
+        .cells_by_type
+        .iter()
+        .filter(|(tipo, _)| tipo.contains("DFF"))
+        .map(|(_, cuenta)| cuenta)
+        .sum();
+    Ok(estadisticas)
+}
+
+// Lee la longitud del `ltp`, que escribe una línea como
+// "Longest topological path in top (length=7):"
+pub fn parse_ltp(texto: &str) -> Result<u64, YosysError> {
+    texto
+        .split("(length=")
+        .nth(1)
+        .and_then(|resto| resto.split(')').next())
+        .and_then(|longitud| longitud.trim().parse().ok())
+        .ok_or_else(|| YosysError::Report("falta la longitud del ltp".into()))
+}
+
+// Un diseño del TFG con sus dos versiones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AreaRow {
+    pub design: String,
+    pub rust: Option<SynthStats>,
+    pub verilog: Option<SynthStats>,
+}
+
+// Busca en `raiz` los diseños con un <nombre>Rust.v y un Verilog/*Verilog.v.
+// Si un directorio tiene varios *Rust.v, el Verilog a mano va con el que se
+// llama como el directorio y los demás salen como diseños aparte, con el
+// nombre del fichero y sin Verilog
+pub fn find_designs(raiz: &Path) -> io::Result<Vec<(String, PathBuf, Option<PathBuf>)>> {
+    let mut disenos = vec![];
+    for entrada in std::fs::read_dir(raiz)? {
+        let dir = entrada?.path();
+        if !dir.is_dir() {
+            continue;
+        }
+        let rust = ficheros_con_sufijo(&dir, "Rust.v")?;
+        let verilog = ficheros_con_sufijo(&dir.join("Verilog"), "Verilog.v")?.into_iter().next();
+        let nombre = nombre_de_fichero(&dir);
+        let principal = match rust.len() {
+            1 => Some(0),
+            _ => rust.iter().position(|r| nombre_de_fichero(r) == format!("{}Rust.v", nombre)),
+        };
+        for (i, fichero) in rust.into_iter().enumerate() {
+            if principal == Some(i) {
+                disenos.push((nombre.clone(), fichero, verilog.clone()));
+            } else {
+                let otro = nombre_de_fichero(&fichero).trim_end_matches("Rust.v").to_string();
+                disenos.push((otro, fichero, None));
+            }
+        }
+    }
+    disenos.sort();
+    Ok(disenos)
+}
+
+fn nombre_de_fichero(ruta: &Path) -> String {
+    ruta.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+fn ficheros_con_sufijo(dir: &Path, sufijo: &str) -> io::Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut ficheros = vec![];
+    for entrada in std::fs::read_dir(dir)? {
+        let ruta = entrada?.path();
+        let nombre = nombre_de_fichero(&ruta);
+        // tb_*Verilog.v son testbenches, no diseños
+        if nombre.ends_with(sufijo) && !nombre.starts_with("tb_") {
+            ficheros.push(ruta);
+        }
+    }
+    ficheros.sort();
+    Ok(ficheros)
+}
+
+fn celda(estadisticas: &Option<SynthStats>, campo: fn(&SynthStats) -> u64) -> String {
+    match estadisticas {
+        Some(e) => campo(e).to_string(),
+        None => "-".into(),
+    }
+}
+
+// Diferencia de RustHDL respecto al Verilog a mano, en porcentaje
+fn diferencia(fila: &AreaRow) -> String {
+    match (&fila.rust, &fila.verilog) {
+        (Some(rust), Some(verilog)) if verilog.cells > 0 => format!(
+            "{:+.1}%",
+            (rust.cells as f64 - verilog.cells as f64) * 100.0 / verilog.cells as f64
+        ),
+        _ => "-".into(),
+    }
+}
+
+// Tabla en Markdown con una fila por diseño
+pub fn area_table(filas: &[AreaRow]) -> String {
+    let mut tabla = String::from(
+        "| Diseño | Celdas Rust | Celdas Verilog | Diferencia | FF Rust | FF Verilog | LUT Rust | LUT Verilog |\n\
+         |---|---:|---:|---:|---:|---:|---:|---:|\n",
+    );
+    for fila in filas {
+        tabla.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {} | {} |\n",
+            fila.design,
+            celda(&fila.rust, |e| e.cells),
+            celda(&fila.verilog, |e| e.cells),
+            diferencia(fila),
+            celda(&fila.rust, |e| e.flip_flops),
+            celda(&fila.verilog, |e| e.flip_flops),
+            celda(&fila.rust, |e| e.luts),
+            celda(&fila.verilog, |e| e.luts),
+        ));
+    }
+    tabla
+}
+
+// Líneas CSV (fecha,diseño,origen,celdas,ff,luts) para ir acumulando el
+// histórico del área en un fichero
+pub fn area_csv(filas: &[AreaRow], instante: SystemTime) -> String {
+    let fecha = fecha_utc(instante);
+    let mut csv = String::new();
+    for fila in filas {
+        for (origen, estadisticas) in [("rust", &fila.rust), ("verilog", &fila.verilog)] {
+            if let Some(e) = estadisticas {
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{}\n",
+                    fecha, fila.design, origen, e.cells, e.flip_flops, e.luts
+                ));
+            }
+        }
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    const STAT_CELDAS: &str = r#"{
+   "creator": "Yosys 0.33",
+   "invocation": "stat -json ",
+   "modules": {
+      "\\Registro": {
+         "num_wires": 4,
+         "num_cells": 7,
+         "num_cells_by_type": {
+            "$_AND_": 2,
+            "$_DFF_P_": 4,
+            "$_XOR_": 1
+         }
+      }
+   },
+   "design": {
+      "num_wires": 4,
+      "num_cells": 7,
+      "num_cells_by_type": {
+         "$_AND_": 2,
+         "$_DFF_P_": 4,
+         "$_XOR_": 1
+      }
+   }
+}"#;
+
+    const STAT_LUTS: &str = r#"{
+   "modules": {
+      "\\Registro": { "num_cells": 6, "num_cells_by_type": { "$_DFF_P_": 4, "$lut": 2 } }
+   }
+}"#;
+
+    const LTP: &str = "\nLongest topological path in Registro (length=3):\n    0 \\a\n    1 $abc$41$n7\n";
+
+    #[test]
+    fn test_leer_stat_json() {
+        let estadisticas = parse_stat_json(STAT_CELDAS).unwrap();
+        assert_eq!(estadisticas.cells, 7);
+        assert_eq!(estadisticas.flip_flops, 4);
+        assert_eq!(estadisticas.cells_by_type["$_XOR_"], 1);
+
+        // Sin "design" se suman los módulos
+        let luts = parse_stat_json(STAT_LUTS).unwrap();
+        assert_eq!(luts.cells, 6);
+        assert_eq!(luts.cells_by_type["$lut"], 2);
+
+        assert!(matches!(parse_stat_json("{}"), Err(YosysError::Report(_))));
+        assert!(matches!(parse_stat_json("no es json"), Err(YosysError::Report(_))));
+    }
+
+    #[test]
+    fn test_leer_ltp() {
+        assert_eq!(parse_ltp(LTP).unwrap(), 3);
+        assert_eq!(parse_ltp("Longest topological path in top (length=0):").unwrap(), 0);
+        assert!(matches!(parse_ltp(""), Err(YosysError::Report(_))));
+    }
+
+    #[test]
+    fn test_tabla_y_csv() {
+        let rust = SynthStats {
+            cells: 110,
+            flip_flops: 8,
+            luts: 30,
+            ..Default::default()
+        };
+        let verilog = SynthStats {
+            cells: 100,
+            flip_flops: 8,
+            luts: 28,
+            ..Default::default()
+        };
+        let filas = vec![
+            AreaRow {
+                design: "sumador".into(),
+                rust: Some(rust),
+                verilog: Some(verilog),
+            },
+            AreaRow {
+                design: "solo_rust".into(),
+                rust: Some(SynthStats::default()),
+                verilog: None,
+            },
+        ];
+        let tabla = area_table(&filas);
+        assert!(tabla.contains("| sumador | 110 | 100 | +10.0% | 8 | 8 | 30 | 28 |\n"));
+        assert!(tabla.contains("| solo_rust | 0 | - | - | 0 | - | 0 | - |\n"));
+
+        let csv = area_csv(&filas, UNIX_EPOCH);
+        assert_eq!(
+            csv,
+            "1970-01-01T00:00:00Z,sumador,rust,110,8,30\n\
+             1970-01-01T00:00:00Z,sumador,verilog,100,8,28\n\
+             1970-01-01T00:00:00Z,solo_rust,rust,0,0,0\n"
+        );
+    }
+
+    #[test]
+    fn test_buscar_disenos() {
+        let raiz = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        let disenos = find_designs(&raiz).unwrap();
+        let (_, rust, verilog) = disenos
+            .iter()
+            .find(|(nombre, _, _)| nombre == "sumador")
+            .expect("falta el sumador");
+        assert!(rust.ends_with("sumadorRust.v"));
+        assert!(verilog.as_ref().unwrap().ends_with("Verilog/sumadorVerilog.v"));
+        // Los diseños parametrizables del secuencial van aparte, sin Verilog
+        // a mano
+        let (_, _, verilog) = disenos
+            .iter()
+            .find(|(nombre, _, _)| nombre == "multiplicacionMatricesSecuencial")
+            .expect("falta el secuencial");
+        assert!(verilog.is_some());
+        for diseno in ["systolicMatMul", "sequentialMatMul"] {
+            let (_, rust, verilog) = disenos
+                .iter()
+                .find(|(nombre, _, _)| nombre == diseno)
+                .unwrap_or_else(|| panic!("falta {}", diseno));
+            assert!(rust.ends_with(format!("multiplicacionMatricesSecuencial/{}Rust.v", diseno)));
+            assert!(verilog.is_none());
+        }
+        // tfg-hdl-common no tiene Verilog generado
+        assert!(!disenos.iter().any(|(nombre, _, _)| nombre == "tfg-hdl-common"));
+    }
+
+    #[test]
+    fn test_yosys_falso() {
+        // Un yosys de mentira que deja los tres informes en el directorio de
+        // trabajo, para probar todo el camino sin tener Yosys instalado
+        let dir = directorio_temporal("yosys-falso", "test").unwrap();
+        let script = dir.join("yosys");
+        std::fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\ncat > {} <<'FIN'\n{}\nFIN\ncat > {} <<'FIN'\n{}\nFIN\ncat > {} <<'FIN'\n{}\nFIN\n",
+                CELDAS, STAT_CELDAS, LUTS, STAT_LUTS, PROFUNDIDAD, LTP
+            ),
+        )
+        .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let verilog = dir.join("registro.v");
+        std::fs::write(&verilog, "module Registro; endmodule\n").unwrap();
+
+        let runner = YosysRunner::new().tool(script.to_str().unwrap());
+        let estadisticas = runner.synthesize(&verilog).unwrap();
+        assert_eq!((estadisticas.cells, estadisticas.flip_flops, estadisticas.luts), (7, 4, 2));
+        assert_eq!(estadisticas.depth, 3);
+        let estadisticas = runner.synthesize_source("Registro", "module Registro; endmodule\n").unwrap();
+        assert_eq!(estadisticas.depth, 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_con_yosys() {
+        let runner = YosysRunner::new();
+        let verilog = Path::new(env!("CARGO_MANIFEST_DIR")).join("../sumador/sumadorRust.v");
+        match runner.synthesize_if_available(&verilog).unwrap() {
+            // Un sumador combinacional no tiene flip-flops
+            Some(estadisticas) => {
+                assert!(estadisticas.cells > 0);
+                assert_eq!(estadisticas.flip_flops, 0);
+                assert!(estadisticas.luts > 0);
+                assert!(estadisticas.depth > 0);
+            }
+            None => assert!(!runner.available()),
+        }
+    }
+}
@@ -0,0 +1,827 @@
+// Generador de testbenches a partir de una única lista de vectores de prueba.
+//
+// Hasta ahora cada test tenía dos juegos de estímulos escritos a mano, el
+// raw string `verilog_tb` y el vector `test_cases` de Rust, y con el tiempo
+// dejaban de coincidir. Con TestVectors se describen una sola vez los puertos
+// del diseño y los casos (entradas y salidas esperadas), y de ahí salen tanto
+// el testbench de la Simulation de RustHDL como un testbench Verilog que se
+// comprueba solo.
+//
+// Los barridos grandes (por ejemplo los 65.536 pares del sumador) no hace
+// falta guardarlos: con stream se da un generador de casos que el testbench
+// de RustHDL recorre según simula.
+use rust_hdl::prelude::*;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fmt;
+use std::panic::RefUnwindSafe;
+use std::sync::{Arc, Mutex};
+
+// Tipos que pueden ir en un puerto del testbench. Los valores de los
+// vectores se guardan como i64, así que el puerto no puede pasar de 64 bits
+pub trait PortValue: Synth {
+    const SIGNED: bool;
+    fn from_i64(valor: i64) -> Self;
+    fn to_i64(self) -> i64;
+}
+
+impl<const N: usize> PortValue for Bits<N> {
+    const SIGNED: bool = false;
+    fn from_i64(valor: i64) -> Self {
+        Bits::from(valor as u64)
+    }
+    fn to_i64(self) -> i64 {
+        self.to_u64() as i64
+    }
+}
+
+impl<const N: usize> PortValue for Signed<N> {
+    const SIGNED: bool = true;
+    fn from_i64(valor: i64) -> Self {
+        Signed::from(valor)
+    }
+    fn to_i64(self) -> i64 {
+        // Extensión de signo de los N bits
+        let bits = self.inner().to_u64();
+        if N < 64 && (bits >> (N - 1)) & 1 == 1 {
+            (bits | !((1_u64 << N) - 1)) as i64
+        } else {
+            bits as i64
+        }
+    }
+}
+
+impl PortValue for bool {
+    const SIGNED: bool = false;
+    fn from_i64(valor: i64) -> Self {
+        valor != 0
+    }
+    fn to_i64(self) -> i64 {
+        self as i64
+    }
+}
+
+// Identificadores de los puertos registrados en un TestVectors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Input(usize);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Output(usize);
+
+// Un caso de prueba: valores de entrada para un ciclo y las salidas que se
+// esperan. Las entradas que no se fijan conservan el valor del caso anterior
+#[derive(Clone, Debug)]
+pub struct TestVector {
+    nombre: String,
+    entradas: Vec<(Input, i64)>,
+    esperadas: Vec<(Output, i64)>,
+}
+
+impl TestVector {
+    pub fn new(nombre: &str) -> Self {
+        Self {
+            nombre: nombre.into(),
+            entradas: vec![],
+            esperadas: vec![],
+        }
+    }
+
+    pub fn set(mut self, puerto: Input, valor: i64) -> Self {
+        self.entradas.push((puerto, valor));
+        self
+    }
+
+    pub fn expect(mut self, puerto: Output, valor: i64) -> Self {
+        self.esperadas.push((puerto, valor));
+        self
+    }
+}
+
+// Fallo detectado al ejecutar el testbench Verilog generado
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestbenchError {
+    // El testbench ha detectado diferencias (una línea por diferencia)
+    Mismatch(Vec<String>),
+    // La salida de vvp no tiene la línea de resultado (el testbench no ha
+    // llegado al final)
+    NoResult(String),
+}
+
+impl fmt::Display for TestbenchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestbenchError::Mismatch(errores) => {
+                writeln!(f, "el testbench Verilog ha fallado en {} comprobaciones:", errores.len())?;
+                for error in errores {
+                    writeln!(f, "  {}", error)?;
+                }
+                Ok(())
+            }
+            TestbenchError::NoResult(salida) => {
+                write!(f, "el testbench Verilog no ha terminado, salida de vvp:\n{}", salida)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TestbenchError {}
+
+type Escritura<T> = Arc<dyn Fn(&mut T, i64) + Send + Sync + RefUnwindSafe>;
+type Lectura<T> = Arc<dyn Fn(&T) -> i64 + Send + Sync + RefUnwindSafe>;
+type LecturaEntrada<T> = Arc<dyn Fn(&mut T) -> i64 + Send + Sync + RefUnwindSafe>;
+type Reloj<T> = Arc<dyn Fn(&mut T, bool) + Send + Sync + RefUnwindSafe>;
+type Generador = Arc<dyn Fn() -> Box<dyn Iterator<Item = TestVector>> + Send + Sync + RefUnwindSafe>;
+
+struct PuertoEntrada<T> {
+    nombre: String,
+    bits: usize,
+    signed: bool,
+    escribir: Escritura<T>,
+    leer: LecturaEntrada<T>,
+}
+
+struct PuertoSalida<T> {
+    nombre: String,
+    bits: usize,
+    signed: bool,
+    leer: Lectura<T>,
+}
+
+// Lo que pasa en un ciclo del testbench: qué caso se aplica a las entradas y
+// qué salidas se comprueban (con el nombre del caso al que pertenecen)
+struct Ciclo<'a> {
+    entradas: Option<&'a TestVector>,
+    comprobaciones: Vec<(&'a str, Output, i64)>,
+}
+
+pub struct TestVectors<T> {
+    modulo: String,
+    reloj: Option<(String, Reloj<T>)>,
+    entradas: Vec<PuertoEntrada<T>>,
+    salidas: Vec<PuertoSalida<T>>,
+    vectores: Vec<TestVector>,
+    generador: Option<Generador>,
+    latencia: usize,
+}
+
+// Clone a mano para no exigir T: Clone (los accesos son Arc)
+impl<T> Clone for TestVectors<T> {
+    fn clone(&self) -> Self {
+        Self {
+            modulo: self.modulo.clone(),
+            reloj: self.reloj.clone(),
+            entradas: self
+                .entradas
+                .iter()
+                .map(|p| PuertoEntrada {
+                    nombre: p.nombre.clone(),
+                    bits: p.bits,
+                    signed: p.signed,
+                    escribir: p.escribir.clone(),
+                    leer: p.leer.clone(),
+                })
+                .collect(),
+            salidas: self
+                .salidas
+                .iter()
+                .map(|p| PuertoSalida {
+                    nombre: p.nombre.clone(),
+                    bits: p.bits,
+                    signed: p.signed,
+                    leer: p.leer.clone(),
+                })
+                .collect(),
+            vectores: self.vectores.clone(),
+            generador: self.generador.clone(),
+            latencia: self.latencia,
+        }
+    }
+}
+
+impl<T: Block + 'static> TestVectors<T> {
+    // `modulo` es el nombre con el que se instancia el diseño en el testbench
+    // Verilog (el mismo que se pasa a generate_named_verilog)
+    pub fn new(modulo: &str) -> Self {
+        Self {
+            modulo: modulo.into(),
+            reloj: None,
+            entradas: vec![],
+            salidas: vec![],
+            vectores: vec![],
+            generador: None,
+            latencia: 0,
+        }
+    }
+
+    // Reloj del diseño. Sin reloj el diseño se trata como combinacional
+    pub fn clock(mut self, nombre: &str, puerto: fn(&mut T) -> &mut Signal<In, Clock>) -> Self {
+        let escribir = move |x: &mut T, nivel: bool| puerto(x).next = Clock { clk: nivel };
+        self.reloj = Some((nombre.into(), Arc::new(escribir)));
+        self
+    }
+
+    // Ciclos que tarda en aparecer en las salidas el resultado de un caso.
+    // Al final se añaden ese número de ciclos manteniendo las entradas
+    pub fn latency(mut self, ciclos: usize) -> Self {
+        self.latencia = ciclos;
+        self
+    }
+
+    // Registra una entrada. `nombre` es el nombre del puerto en el Verilog
+    // generado (por ejemplo "inputs$0" para inputs[0]). El acceso puede
+    // capturar variables, así que los arrays se registran en un bucle:
+    // `tb.input(&format!("inputs${}", i), move |x| &mut x.inputs[i])`
+    pub fn input<S, F>(&mut self, nombre: &str, puerto: F) -> Input
+    where
+        S: PortValue + 'static,
+        F: Fn(&mut T) -> &mut Signal<In, S> + Send + Sync + RefUnwindSafe + 'static,
+    {
+        assert!(S::BITS <= 64, "el puerto {} tiene más de 64 bits", nombre);
+        let puerto = Arc::new(puerto);
+        let acceso = puerto.clone();
+        let escribir = move |x: &mut T, valor: i64| acceso(x).next = S::from_i64(valor);
+        let leer = move |x: &mut T| puerto(x).val().to_i64();
+        self.entradas.push(PuertoEntrada {
+            nombre: nombre.into(),
+            bits: S::BITS,
+            signed: S::SIGNED,
+            escribir: Arc::new(escribir),
+            leer: Arc::new(leer),
+        });
+        Input(self.entradas.len() - 1)
+    }
+
+    // Registra una salida
+    pub fn output<S, F>(&mut self, nombre: &str, puerto: F) -> Output
+    where
+        S: PortValue + 'static,
+        F: Fn(&T) -> &Signal<Out, S> + Send + Sync + RefUnwindSafe + 'static,
+    {
+        assert!(S::BITS <= 64, "el puerto {} tiene más de 64 bits", nombre);
+        let leer = move |x: &T| puerto(x).val().to_i64();
+        self.salidas.push(PuertoSalida {
+            nombre: nombre.into(),
+            bits: S::BITS,
+            signed: S::SIGNED,
+            leer: Arc::new(leer),
+        });
+        Output(self.salidas.len() - 1)
+    }
+
+    // Añade un caso. Falla si algún valor no cabe en su puerto, para que un
+    // caso mal escrito no se trunque en silencio en uno de los dos simuladores
+    pub fn push(&mut self, vector: TestVector) {
+        self.comprobar_vector(&vector);
+        self.vectores.push(vector);
+    }
+
+    pub fn extend<I: IntoIterator<Item = TestVector>>(&mut self, vectores: I) {
+        for vector in vectores {
+            self.push(vector);
+        }
+    }
+
+    // Casos que se generan durante la simulación en lugar de guardarse. Van
+    // detrás de los añadidos con push y `generador` se llama de nuevo en cada
+    // simulación, así que tiene que dar siempre la misma secuencia. Los
+    // valores se comprueban igual que en push, según se van generando
+    pub fn stream<F, I>(&mut self, generador: F)
+    where
+        F: Fn() -> I + Send + Sync + RefUnwindSafe + 'static,
+        I: IntoIterator<Item = TestVector>,
+        I::IntoIter: 'static,
+    {
+        self.generador = Some(Arc::new(move || Box::new(generador().into_iter())));
+    }
+
+    // Número de casos, contando los del generador
+    pub fn len(&self) -> usize {
+        self.vectores.len() + self.generador.as_ref().map_or(0, |g| g().count())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn comprobar_vector(&self, vector: &TestVector) {
+        for (puerto, valor) in &vector.entradas {
+            let p = &self.entradas[puerto.0];
+            comprobar_rango(&vector.nombre, &p.nombre, p.bits, p.signed, *valor);
+        }
+        for (puerto, valor) in &vector.esperadas {
+            let p = &self.salidas[puerto.0];
+            comprobar_rango(&vector.nombre, &p.nombre, p.bits, p.signed, *valor);
+        }
+    }
+
+    fn comprobar_latencia(&self) {
+        assert!(
+            self.latencia == 0 || self.reloj.is_some(),
+            "un diseño sin reloj no puede tener latencia"
+        );
+    }
+
+    // Todos los casos en orden, sin guardarlos
+    fn casos(&self) -> impl Iterator<Item = Cow<'_, TestVector>> + '_ {
+        let generados = self.generador.as_ref().map(|g| g()).into_iter().flatten();
+        self.vectores.iter().map(Cow::Borrowed).chain(generados.map(move |vector| {
+            self.comprobar_vector(&vector);
+            Cow::Owned(vector)
+        }))
+    }
+
+    // Todos los casos en memoria, para el testbench Verilog
+    fn todos_los_casos(&self) -> Cow<'_, [TestVector]> {
+        match self.generador {
+            None => Cow::Borrowed(&self.vectores),
+            Some(_) => Cow::Owned(self.casos().map(Cow::into_owned).collect()),
+        }
+    }
+
+    // Reparte los casos en ciclos teniendo en cuenta la latencia
+    fn ciclos<'a>(&self, vectores: &'a [TestVector]) -> Vec<Ciclo<'a>> {
+        self.comprobar_latencia();
+        let total = if vectores.is_empty() {
+            0
+        } else {
+            vectores.len() + self.latencia
+        };
+        (0..total)
+            .map(|ciclo| Ciclo {
+                entradas: vectores.get(ciclo),
+                comprobaciones: ciclo
+                    .checked_sub(self.latencia)
+                    .and_then(|i| vectores.get(i))
+                    .map(|v| {
+                        v.esperadas
+                            .iter()
+                            .map(|(puerto, valor)| (v.nombre.as_str(), *puerto, *valor))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+impl<T: Block + Send + 'static> TestVectors<T> {
+    // Añade a la simulación un testbench que aplica los casos y compara las
+    // salidas. Si alguna no coincide se imprimen todas las diferencias y la
+    // simulación termina con SimHalted
+    pub fn add_to(&self, sim: &mut Simulation<T>) {
+        let tb = self.clone();
+        sim.add_testbench(move |ep: Sim<T>| tb.banco(ep, None));
+    }
+
+    // Testbench de RustHDL. Con `traza` no se comprueban las salidas: solo se
+    // guarda una fila por ciclo con el valor de todos los puertos (ver
+    // fila_traza), que es lo que usa la co-simulación.
+    //
+    // Los casos se van sacando de casos() ciclo a ciclo y solo se guardan los
+    // que esperan su comprobación (latencia + 1 como mucho), así que el
+    // número de casos no está limitado por la memoria
+    pub(crate) fn banco(&self, mut ep: Sim<T>, traza: Option<Arc<Mutex<Vec<String>>>>) -> Result<(), SimError> {
+        self.comprobar_latencia();
+        let mut x = ep.init()?;
+        let mut errores = 0;
+        let mut casos = self.casos();
+        let mut pendientes = VecDeque::new();
+        let mut agotados = false;
+        for n in 0.. {
+            match casos.next().filter(|_| !agotados) {
+                Some(vector) => {
+                    for (puerto, valor) in &vector.entradas {
+                        (self.entradas[puerto.0].escribir)(&mut x, *valor);
+                    }
+                    pendientes.push_back(vector);
+                }
+                // Al acabar los casos se sigue con las mismas entradas hasta
+                // comprobar los que quedan
+                None if pendientes.is_empty() => break,
+                None => agotados = true,
+            }
+            match &self.reloj {
+                Some((_, reloj)) => {
+                    reloj(&mut x, false);
+                    x = ep.wait(1, x)?;
+                    reloj(&mut x, true);
+                    x = ep.wait(1, x)?;
+                }
+                None => x = ep.wait(1, x)?,
+            }
+            if let Some(traza) = &traza {
+                let entradas: Vec<i64> = self.entradas.iter().map(|p| (p.leer)(&mut x)).collect();
+                let salidas: Vec<i64> = self.salidas.iter().map(|p| (p.leer)(&x)).collect();
+                let fila = self.fila_traza(n, &entradas, &salidas);
+                traza.lock().unwrap().push(fila);
+            }
+            if pendientes.len() <= self.latencia && !agotados {
+                continue;
+            }
+            let Some(vector) = pendientes.pop_front() else {
+                continue;
+            };
+            if traza.is_some() {
+                continue;
+            }
+            for (puerto, esperado) in &vector.esperadas {
+                let salida = &self.salidas[puerto.0];
+                let obtenido = (salida.leer)(&x);
+                if obtenido != *esperado {
+                    println!(
+                        "ERROR ciclo {} ({}): {} = {}, esperado {}",
+                        n, vector.nombre, salida.nombre, obtenido, esperado
+                    );
+                    errores += 1;
+                }
+            }
+        }
+        if errores > 0 {
+            println!("RESULTADO: FALLO ({} errores)", errores);
+            return ep.halt(x);
+        }
+        ep.done(x)
+    }
+}
+
+impl<T: Block + 'static> TestVectors<T> {
+    // Testbench Verilog (módulo `test`) equivalente al de add_to. Cada
+    // diferencia se imprime con "ERROR" y al final sale una línea
+    // "RESULTADO: ..." que se comprueba con check_verilog_output
+    pub fn verilog_testbench(&self) -> String {
+        self.testbench_verilog(None)
+    }
+
+    // Con `traza` el testbench además escribe en ese fichero, con $fwrite, una
+    // fila por ciclo con el mismo formato que fila_traza
+    pub(crate) fn testbench_verilog(&self, traza: Option<&str>) -> String {
+        let mut tb = String::from("\nmodule test;\n\n");
+        if let Some((reloj, _)) = &self.reloj {
+            tb += &format!("    reg {};\n", reloj);
+        }
+        for p in &self.entradas {
+            tb += &format!("    reg {}{};\n", tipo_verilog(p.bits, p.signed), nombre_local(&p.nombre));
+        }
+        for p in &self.salidas {
+            tb += &format!("    wire {}{};\n", tipo_verilog(p.bits, p.signed), nombre_local(&p.nombre));
+        }
+        tb += "    integer errores;\n";
+        if traza.is_some() {
+            tb += "    integer traza;\n";
+        }
+        tb += "\n";
+
+        let mut conexiones: Vec<String> = vec![];
+        if let Some((reloj, _)) = &self.reloj {
+            conexiones.push(format!(".{}({})", reloj, reloj));
+        }
+        for nombre in self
+            .entradas
+            .iter()
+            .map(|p| &p.nombre)
+            .chain(self.salidas.iter().map(|p| &p.nombre))
+        {
+            conexiones.push(format!(".{}({})", nombre, nombre_local(nombre)));
+        }
+        tb += &format!(
+            "    {} uut(\n        {}\n    );\n\n",
+            self.modulo,
+            conexiones.join(",\n        ")
+        );
+
+        tb += "    initial begin\n        errores = 0;\n";
+        if let Some(fichero) = traza {
+            tb += &format!("        traza = $fopen(\"{}\", \"w\");\n", fichero);
+        }
+        if let Some((reloj, _)) = &self.reloj {
+            tb += &format!("        {} = 0;\n", reloj);
+        }
+        for p in &self.entradas {
+            tb += &format!("        {} = 0;\n", nombre_local(&p.nombre));
+        }
+
+        let vectores = self.todos_los_casos();
+        let ciclos = self.ciclos(&vectores);
+        for (n, ciclo) in ciclos.iter().enumerate() {
+            tb += &format!("\n        // Ciclo {}\n", n);
+            if let Some(vector) = ciclo.entradas {
+                tb += &format!("        // {}\n", vector.nombre);
+                for (puerto, valor) in &vector.entradas {
+                    let p = &self.entradas[puerto.0];
+                    tb += &format!(
+                        "        {} = {};\n",
+                        nombre_local(&p.nombre),
+                        literal_verilog(p.bits, *valor)
+                    );
+                }
+            }
+            match &self.reloj {
+                Some((reloj, _)) => {
+                    tb += &format!("        {r} = 0;\n        #5;\n        {r} = 1;\n        #5;\n", r = reloj)
+                }
+                None => tb += "        #10;\n",
+            }
+            if traza.is_some() {
+                let puertos: Vec<String> = self
+                    .entradas
+                    .iter()
+                    .map(|p| nombre_local(&p.nombre))
+                    .chain(self.salidas.iter().map(|p| nombre_local(&p.nombre)))
+                    .collect();
+                let formato = vec!["%b"; puertos.len()].join(" ");
+                tb += &format!(
+                    "        $fwrite(traza, \"{} {}\\n\"{});\n",
+                    n,
+                    formato,
+                    puertos.iter().map(|p| format!(", {}", p)).collect::<String>()
+                );
+            }
+            for (caso, puerto, esperado) in &ciclo.comprobaciones {
+                let p = &self.salidas[puerto.0];
+                let local = nombre_local(&p.nombre);
+                tb += &format!(
+                    "        if ({} !== {}) begin\n            $display(\"ERROR ciclo {} ({}): {} = %0d, esperado {}\", {});\n            errores = errores + 1;\n        end\n",
+                    local,
+                    literal_verilog(p.bits, *esperado),
+                    n,
+                    caso,
+                    p.nombre,
+                    esperado,
+                    local
+                );
+            }
+        }
+
+        if traza.is_some() {
+            tb += "\n        $fclose(traza);";
+        }
+        tb += &format!(
+            "\n        if (errores == 0) $display(\"RESULTADO: OK ({} ciclos)\");\n        else $display(\"RESULTADO: FALLO (%0d errores)\", errores);\n        $finish;\n    end\nendmodule\n",
+            ciclos.len()
+        );
+        tb
+    }
+
+    // Fila de la traza de un ciclo: el número de ciclo y el valor en binario
+    // de cada entrada y cada salida, en el orden en que se registraron
+    pub(crate) fn fila_traza(&self, ciclo: usize, entradas: &[i64], salidas: &[i64]) -> String {
+        let mut fila = ciclo.to_string();
+        let bits = self.entradas.iter().map(|p| p.bits).chain(self.salidas.iter().map(|p| p.bits));
+        for (bits, valor) in bits.zip(entradas.iter().chain(salidas)) {
+            fila += &format!(" {}", binario(bits, *valor));
+        }
+        fila
+    }
+
+    // Nombres de los puertos en el orden de las columnas de la traza
+    pub(crate) fn columnas_traza(&self) -> Vec<String> {
+        self.entradas
+            .iter()
+            .map(|p| p.nombre.clone())
+            .chain(self.salidas.iter().map(|p| p.nombre.clone()))
+            .collect()
+    }
+
+    // Caso al que pertenece un ciclo: el que carga las entradas o, en los
+    // ciclos de vaciado del final, el que se está comprobando
+    pub(crate) fn caso_del_ciclo(&self, ciclo: usize) -> Option<String> {
+        let vectores = self.todos_los_casos();
+        vectores
+            .get(ciclo)
+            .or_else(|| vectores.get(ciclo.checked_sub(self.latencia)?))
+            .map(|v| v.nombre.clone())
+    }
+
+    pub(crate) fn nombre_modulo(&self) -> &str {
+        &self.modulo
+    }
+
+    pub(crate) fn num_ciclos(&self) -> usize {
+        match self.len() {
+            0 => 0,
+            casos => casos + self.latencia,
+        }
+    }
+
+    // Comprueba la salida de vvp del testbench generado por verilog_testbench
+    pub fn check_verilog_output(&self, stdout: &str) -> Result<(), TestbenchError> {
+        let errores: Vec<String> = stdout
+            .lines()
+            .filter(|linea| linea.starts_with("ERROR"))
+            .map(String::from)
+            .collect();
+        if !errores.is_empty() {
+            return Err(TestbenchError::Mismatch(errores));
+        }
+        if !stdout.contains("RESULTADO: OK") {
+            return Err(TestbenchError::NoResult(stdout.into()));
+        }
+        Ok(())
+    }
+}
+
+fn comprobar_rango(caso: &str, puerto: &str, bits: usize, signed: bool, valor: i64) {
+    let (minimo, maximo) = match (signed, bits) {
+        (true, 64) => (i64::MIN as i128, i64::MAX as i128),
+        (true, n) => (-(1_i128 << (n - 1)), (1_i128 << (n - 1)) - 1),
+        (false, n) => (0, (1_i128 << n) - 1),
+    };
+    assert!(
+        (minimo..=maximo).contains(&(valor as i128)),
+        "{}: el valor {} no cabe en el puerto {} ({} bits{})",
+        caso,
+        valor,
+        puerto,
+        bits,
+        if signed { " con signo" } else { "" }
+    );
+}
+
+// Nombre de la variable del testbench para un puerto (sin los $ de los arrays)
+fn nombre_local(puerto: &str) -> String {
+    puerto.replace('$', "_")
+}
+
+fn tipo_verilog(bits: usize, signed: bool) -> String {
+    let signo = if signed { "signed " } else { "" };
+    if bits == 1 {
+        signo.into()
+    } else {
+        format!("{}[{}:0] ", signo, bits - 1)
+    }
+}
+
+// Literal sin signo con los bits del valor, para que la comparación con !==
+// no dependa de cómo extiende el signo cada herramienta
+fn literal_verilog(bits: usize, valor: i64) -> String {
+    let mascara = if bits >= 64 { u64::MAX } else { (1_u64 << bits) - 1 };
+    format!("{}'d{}", bits, (valor as u64) & mascara)
+}
+
+fn binario(bits: usize, valor: i64) -> String {
+    let mascara = if bits >= 64 { u64::MAX } else { (1_u64 << bits) - 1 };
+    format!("{:0width$b}", (valor as u64) & mascara, width = bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signed_bit_cast;
+
+    // Sumador con registro de salida, para probar el modo con reloj
+    #[derive(LogicBlock, Default)]
+    struct SumaRegistrada {
+        pub clk: Signal<In, Clock>,
+        pub a: Signal<In, Signed<4>>,
+        pub b: Signal<In, Signed<4>>,
+        pub suma: Signal<Out, Signed<5>>,
+        registro: DFF<Signed<5>>,
+    }
+
+    impl Logic for SumaRegistrada {
+        #[hdl_gen]
+        fn update(&mut self) {
+            self.registro.clock.next = self.clk.val();
+            self.registro.d.next =
+                signed_bit_cast::<5, 4>(self.a.val()) + signed_bit_cast::<5, 4>(self.b.val());
+            self.suma.next = self.registro.q.val();
+        }
+    }
+
+    fn vectores(suma_esperada: impl Fn(i64, i64) -> i64) -> TestVectors<SumaRegistrada> {
+        let mut tb = TestVectors::<SumaRegistrada>::new("SumaRegistrada").clock("clk", |x| &mut x.clk);
+        let a = tb.input("a", |x| &mut x.a);
+        let b = tb.input("b", |x| &mut x.b);
+        let suma = tb.output("suma", |x| &x.suma);
+        for (va, vb) in [(1, 2), (-8, -8), (7, 7), (-3, 5)] {
+            tb.push(
+                TestVector::new(&format!("{} + {}", va, vb))
+                    .set(a, va)
+                    .set(b, vb)
+                    .expect(suma, suma_esperada(va, vb)),
+            );
+        }
+        tb
+    }
+
+    #[test]
+    fn test_valores_de_puerto() {
+        assert_eq!(Signed::<8>::from_i64(-128).to_i64(), -128);
+        assert_eq!(Signed::<8>::from_i64(127).to_i64(), 127);
+        assert_eq!(Bits::<8>::from_i64(255).to_i64(), 255);
+        assert!(bool::from_i64(1));
+        assert_eq!(literal_verilog(8, -128), "8'd128");
+        assert_eq!(literal_verilog(11, -1024), "11'd1024");
+    }
+
+    #[test]
+    fn test_simulacion_correcta() {
+        let tb = vectores(|a, b| a + b);
+        let mut sim = Simulation::new();
+        tb.add_to(&mut sim);
+        let mut uut = SumaRegistrada::default();
+        uut.connect_all();
+        sim.run(Box::new(uut), 1_000).unwrap();
+    }
+
+    #[test]
+    fn test_simulacion_detecta_fallos() {
+        let tb = vectores(|a, b| if a == 7 { 0 } else { a + b });
+        let mut sim = Simulation::new();
+        tb.add_to(&mut sim);
+        let mut uut = SumaRegistrada::default();
+        uut.connect_all();
+        assert_eq!(sim.run(Box::new(uut), 1_000), Err(SimError::SimHalted));
+    }
+
+    #[test]
+    fn test_latencia_desplaza_las_comprobaciones() {
+        let tb = vectores(|a, b| a + b).latency(1);
+        let ciclos = tb.ciclos(&tb.vectores);
+        assert_eq!(ciclos.len(), 5);
+        assert!(ciclos[0].comprobaciones.is_empty());
+        assert_eq!(ciclos[1].comprobaciones[0].0, "1 + 2");
+        assert!(ciclos[4].entradas.is_none());
+    }
+
+    #[test]
+    fn test_casos_generados() {
+        // Todos los pares de 4 bits sin guardarlos
+        let generados = |error: i64| {
+            let mut tb = TestVectors::<SumaRegistrada>::new("SumaRegistrada").clock("clk", |x| &mut x.clk);
+            let a = tb.input("a", |x| &mut x.a);
+            let b = tb.input("b", |x| &mut x.b);
+            let suma = tb.output("suma", |x| &x.suma);
+            tb.stream(move || {
+                (-8..8).flat_map(move |va| {
+                    (-8..8).map(move |vb| {
+                        let esperada = if (va, vb) == (-8, -8) { error } else { va + vb };
+                        TestVector::new("par").set(a, va).set(b, vb).expect(suma, esperada)
+                    })
+                })
+            });
+            tb
+        };
+        let simular = |tb: TestVectors<SumaRegistrada>| {
+            let mut sim = Simulation::new();
+            tb.add_to(&mut sim);
+            let mut uut = SumaRegistrada::default();
+            uut.connect_all();
+            sim.run(Box::new(uut), 10_000)
+        };
+
+        let tb = generados(-16);
+        assert_eq!(tb.len(), 256);
+        assert_eq!(tb.num_ciclos(), 256);
+        assert!(tb.verilog_testbench().contains("RESULTADO: OK (256 ciclos)"));
+        assert_eq!(simular(tb), Ok(()));
+        assert_eq!(simular(generados(0)), Err(SimError::SimHalted));
+    }
+
+    #[test]
+    fn test_testbench_verilog() {
+        let tb = vectores(|a, b| a + b);
+        let codigo = tb.verilog_testbench();
+        assert!(codigo.contains("reg signed [3:0] a;"));
+        assert!(codigo.contains("wire signed [4:0] suma;"));
+        assert!(codigo.contains("SumaRegistrada uut("));
+        assert!(codigo.contains("a = 4'd8;"));
+        assert!(codigo.contains("if (suma !== 5'd16) begin"));
+        assert!(codigo.contains("RESULTADO: OK (4 ciclos)"));
+
+        assert!(tb.check_verilog_output("RESULTADO: OK (4 ciclos)\n").is_ok());
+        assert_eq!(
+            tb.check_verilog_output("ERROR ciclo 1 (-8 + -8): suma = 0, esperado -16\nRESULTADO: FALLO (1 errores)\n"),
+            Err(TestbenchError::Mismatch(vec![
+                "ERROR ciclo 1 (-8 + -8): suma = 0, esperado -16".into()
+            ]))
+        );
+        assert!(matches!(tb.check_verilog_output(""), Err(TestbenchError::NoResult(_))));
+    }
+
+    #[test]
+    #[should_panic(expected = "no cabe en el puerto a")]
+    fn test_valor_fuera_de_rango() {
+        let mut tb = TestVectors::<SumaRegistrada>::new("SumaRegistrada");
+        let a = tb.input("a", |x| &mut x.a);
+        tb.push(TestVector::new("fuera").set(a, 8));
+    }
+
+    #[test]
+    fn test_con_icarus() {
+        let tb = vectores(|a, b| a + b);
+        let mut uut = SumaRegistrada::default();
+        uut.connect_all();
+        let codigo = format!(
+            "{}{}",
+            tb.verilog_testbench(),
+            crate::generate_named_verilog(&uut, "SumaRegistrada").unwrap()
+        );
+        if let Some(salida) = crate::IcarusRunner::new()
+            .run_if_available("testbench_generado", &codigo)
+            .unwrap()
+        {
+            tb.check_verilog_output(&salida.stdout).unwrap();
+        }
+    }
+}
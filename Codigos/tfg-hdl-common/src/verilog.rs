@@ -0,0 +1,281 @@
+// Exportación del Verilog de un diseño con el nombre de módulo que queramos.
+//
+// generate_verilog de RustHDL siempre llama "top" al módulo principal y hasta
+// ahora se renombraba con replace("module top(", ...), que no hace nada si
+// cambia el formato de la salida. Aquí se recorre el diseño directamente con
+// el nombre pedido, así que el módulo principal y sus submódulos (Nombre$x)
+// salen ya con el nombre correcto.
+use rust_hdl::prelude::*;
+use rust_hdl_core::check_error::CheckError;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Palabras reservadas de Verilog-2005, que no se pueden usar como nombre
+const PALABRAS_RESERVADAS: &[&str] = &[
+    "always", "and", "assign", "automatic", "begin", "buf", "bufif0", "bufif1", "case", "casex",
+    "casez", "cell", "cmos", "config", "deassign", "default", "defparam", "design", "disable",
+    "edge", "else", "end", "endcase", "endconfig", "endfunction", "endgenerate", "endmodule",
+    "endprimitive", "endspecify", "endtable", "endtask", "event", "for", "force", "forever",
+    "fork", "function", "generate", "genvar", "highz0", "highz1", "if", "ifnone", "incdir",
+    "include", "initial", "inout", "input", "instance", "integer", "join", "large", "liblist",
+    "library", "localparam", "macromodule", "medium", "module", "nand", "negedge", "nmos", "nor",
+    "noshowcancelled", "not", "notif0", "notif1", "or", "output", "parameter", "pmos", "posedge",
+    "primitive", "pull0", "pull1", "pulldown", "pullup", "pulsestyle_ondetect",
+    "pulsestyle_onevent", "rcmos", "real", "realtime", "reg", "release", "repeat", "rnmos",
+    "rpmos", "rtran", "rtranif0", "rtranif1", "scalared", "showcancelled", "signed", "small",
+    "specify", "specparam", "strong0", "strong1", "supply0", "supply1", "table", "task", "time",
+    "tran", "tranif0", "tranif1", "tri", "tri0", "tri1", "triand", "trior", "trireg", "unsigned",
+    "use", "uwire", "vectored", "wait", "wand", "weak0", "weak1", "while", "wire", "wor",
+    "xnor", "xor",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerilogError {
+    // El nombre pedido no es un identificador de Verilog válido
+    InvalidName(String),
+    // Un puerto del envoltorio no sale con un nombre válido o se repite
+    PortName(String),
+    // El diseño no pasa los checks de RustHDL (señales abiertas, bucles...)
+    Check(CheckError),
+}
+
+impl fmt::Display for VerilogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerilogError::InvalidName(nombre) => {
+                write!(f, "\"{}\" no es un nombre de módulo Verilog válido", nombre)
+            }
+            VerilogError::PortName(nombre) => {
+                write!(f, "el puerto \"{}\" no es válido o está repetido", nombre)
+            }
+            VerilogError::Check(err) => write!(f, "el diseño no pasa los checks de RustHDL: {:?}", err),
+        }
+    }
+}
+
+impl std::error::Error for VerilogError {}
+
+impl From<CheckError> for VerilogError {
+    fn from(err: CheckError) -> Self {
+        VerilogError::Check(err)
+    }
+}
+
+// Identificador simple de Verilog: letra o _ seguido de letras, dígitos, _ o
+// $, y que no sea una palabra reservada
+pub fn is_verilog_identifier(nombre: &str) -> bool {
+    let mut caracteres = nombre.chars();
+    let primero_valido = match caracteres.next() {
+        Some(c) => c.is_ascii_alphabetic() || c == '_',
+        None => false,
+    };
+    primero_valido
+        && caracteres.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && !PALABRAS_RESERVADAS.contains(&nombre)
+}
+
+// Datos que se escriben en la cabecera del fichero generado. Lo normal es
+// construirla con la macro verilog_header!() para que recoja el nombre y la
+// versión del crate que genera el Verilog.
+//
+// La fecha de generación sólo se escribe con with_timestamp(): los .v que se
+// guardan en el repositorio no la llevan, para que sólo cambien cuando cambia
+// el diseño. Si está definida SOURCE_DATE_EPOCH (segundos desde 1970) se usa
+// esa fecha en vez de la actual, como en los builds reproducibles
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerilogHeader {
+    pub crate_name: String,
+    pub crate_version: String,
+    pub timestamp: bool,
+}
+
+impl VerilogHeader {
+    pub fn new(crate_name: &str, crate_version: &str) -> Self {
+        Self {
+            crate_name: crate_name.into(),
+            crate_version: crate_version.into(),
+            timestamp: false,
+        }
+    }
+
+    // La misma cabecera con la fecha de generación
+    pub fn with_timestamp(mut self) -> Self {
+        self.timestamp = true;
+        self
+    }
+
+    pub(crate) fn render(&self, modulo: &str) -> String {
+        let instante = if self.timestamp {
+            Some(instante_desde(std::env::var("SOURCE_DATE_EPOCH").ok().as_deref()))
+        } else {
+            None
+        };
+        self.render_at(modulo, instante)
+    }
+
+    fn render_at(&self, modulo: &str, instante: Option<SystemTime>) -> String {
+        let mut cabecera = format!(
+            "// Módulo {} generado con RustHDL\n// Crate: {} v{}\n",
+            modulo, self.crate_name, self.crate_version
+        );
+        if let Some(instante) = instante {
+            cabecera += &format!("// Fecha de generación: {}\n", fecha_utc(instante));
+        }
+        cabecera
+    }
+}
+
+// Instante de generación: el de SOURCE_DATE_EPOCH si tiene un número de
+// segundos y, si no, el actual
+fn instante_desde(source_date_epoch: Option<&str>) -> SystemTime {
+    match source_date_epoch.and_then(|s| s.trim().parse::<u64>().ok()) {
+        Some(segundos) => UNIX_EPOCH + Duration::from_secs(segundos),
+        None => SystemTime::now(),
+    }
+}
+
+#[macro_export]
+macro_rules! verilog_header {
+    () => {
+        $crate::verilog::VerilogHeader::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+    };
+}
+
+// Genera el Verilog de `uut` con el módulo principal llamado `nombre`
+pub fn generate_named_verilog<T: Block>(uut: &T, nombre: &str) -> Result<String, VerilogError> {
+    if !is_verilog_identifier(nombre) {
+        return Err(VerilogError::InvalidName(nombre.into()));
+    }
+    check_all(uut)?;
+    let mut defines = ModuleDefines::default();
+    uut.accept(nombre, &mut defines);
+    Ok(defines.defines())
+}
+
+// Igual que generate_named_verilog, pero con una cabecera de comentario con
+// la versión del crate y, si se pide, la fecha de generación
+pub fn generate_named_verilog_with_header<T: Block>(
+    uut: &T,
+    nombre: &str,
+    cabecera: &VerilogHeader,
+) -> Result<String, VerilogError> {
+    let codigo = generate_named_verilog(uut, nombre)?;
+    Ok(format!("{}{}", cabecera.render(nombre), codigo))
+}
+
+// Fecha en formato ISO 8601 (UTC) sin depender de ningún crate de fechas
+pub(crate) fn fecha_utc(instante: SystemTime) -> String {
+    let segundos = instante
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let dias = (segundos / 86_400) as i64;
+    let resto = segundos % 86_400;
+    let (anio, mes, dia) = civil_desde_dias(dias);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        anio,
+        mes,
+        dia,
+        resto / 3600,
+        (resto % 3600) / 60,
+        resto % 60
+    )
+}
+
+// Algoritmo de Howard Hinnant para pasar días desde 1970-01-01 a fecha civil
+fn civil_desde_dias(dias: i64) -> (i64, u32, u32) {
+    let z = dias + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let dia = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let mes = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let anio = yoe + era * 400 + if mes <= 2 { 1 } else { 0 };
+    (anio, mes, dia)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EdgeDFF;
+
+    #[derive(LogicBlock, Clone, Default)]
+    struct Registro {
+        pub clk: Signal<In, Clock>,
+        pub d: Signal<In, Bits<4>>,
+        pub q: Signal<Out, Bits<4>>,
+        dato: EdgeDFF<Bits<4>>,
+    }
+
+    impl Logic for Registro {
+        #[hdl_gen]
+        fn update(&mut self) {
+            self.dato.clk.next = self.clk.val();
+            self.dato.d.next = self.d.val();
+            self.q.next = self.dato.q.val();
+        }
+    }
+
+    #[test]
+    fn test_nombre_del_modulo() {
+        let mut uut = Registro::default();
+        uut.connect_all();
+        let codigo = generate_named_verilog(&uut, "Registro").unwrap();
+        assert!(codigo.contains("module Registro(clk,d,q);"));
+        assert!(codigo.contains("module Registro$dato("));
+        assert!(codigo.contains("Registro$dato dato("));
+        assert!(!codigo.contains("module top"));
+        // Mismo Verilog que el de RustHDL salvo el nombre
+        assert_eq!(codigo.replace("Registro", "top"), generate_verilog(&uut));
+    }
+
+    #[test]
+    fn test_nombres_invalidos() {
+        let mut uut = Registro::default();
+        uut.connect_all();
+        for nombre in ["", "1sumador", "mi modulo", "módulo", "$top", "module", "wire"] {
+            assert_eq!(
+                generate_named_verilog(&uut, nombre),
+                Err(VerilogError::InvalidName(nombre.into()))
+            );
+        }
+        assert!(is_verilog_identifier("_sumador$2"));
+    }
+
+    #[test]
+    fn test_cabecera() {
+        let mut uut = Registro::default();
+        uut.connect_all();
+        let codigo = generate_named_verilog_with_header(&uut, "Registro", &verilog_header!()).unwrap();
+        assert!(codigo.starts_with("// Módulo Registro generado con RustHDL\n"));
+        assert!(codigo.contains(&format!("// Crate: tfg-hdl-common v{}\n", env!("CARGO_PKG_VERSION"))));
+        assert!(codigo.contains("module Registro(clk,d,q);"));
+        // Sin with_timestamp no hay fecha, y el Verilog no cambia de una vez
+        // a otra
+        assert!(!codigo.contains("Fecha de generación"));
+
+        let cabecera = VerilogHeader::new("sumador", "0.1.0");
+        let instante = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(
+            cabecera.render("Sumador"),
+            "// Módulo Sumador generado con RustHDL\n// Crate: sumador v0.1.0\n"
+        );
+        assert_eq!(
+            cabecera.with_timestamp().render_at("Sumador", Some(instante)),
+            "// Módulo Sumador generado con RustHDL\n// Crate: sumador v0.1.0\n// Fecha de generación: 2024-02-29T12:34:56Z\n"
+        );
+    }
+
+    #[test]
+    fn test_source_date_epoch() {
+        assert_eq!(instante_desde(Some("1709210096")), UNIX_EPOCH + Duration::from_secs(1_709_210_096));
+        assert_eq!(instante_desde(Some(" 0\n")), UNIX_EPOCH);
+        // Sin la variable, o si no es un número, la fecha actual
+        for valor in [None, Some(""), Some("ayer")] {
+            assert!(instante_desde(valor) > UNIX_EPOCH + Duration::from_secs(1_709_210_096));
+        }
+    }
+}
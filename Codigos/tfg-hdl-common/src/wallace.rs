@@ -0,0 +1,191 @@
+use crate::mul::{Signedness, Unsigned};
+use rust_hdl::prelude::*;
+
+// Multiplicador con árbol de Wallace.
+//
+// Los W productos parciales (a desplazado si b[i] = 1) se reducen con
+// sumadores carry-save: cada uno toma tres filas y da dos, la suma bit a bit
+// (x ^ y ^ z) y los acarreos desplazados un bit, sin propagar nada entre
+// bits. Se repite hasta que quedan dos filas, y sólo la suma final de esas
+// dos propaga el acarreo. Las filas se tratan como una cola: el sumador t
+// toma las filas 3t, 3t+1 y 3t+2 y añade dos al final, con lo que las filas
+// de un mismo nivel se reducen en paralelo y el árbol tiene unos
+// log1.5(W) niveles.
+//
+// La cola vive en las W posiciones de `filas` (la fila q en filas[q % W]):
+// cuando se escribe una fila, la que ocupaba antes su posición ya se ha
+// usado.
+//
+// Con signo, el bit de más peso de b resta: su fila se invierte y el +1 de
+// -x = !x + 1 se mete en el bit 0 de la fila 1, que siempre es 0. Por eso
+// hacen falta al menos 3 bits.
+#[derive(LogicBlock, Clone)]
+pub struct WallaceMul<const W: usize, const O: usize, S: Signedness = Unsigned> {
+    pub a: Signal<In, Bits<W>>,
+    pub b: Signal<In, Bits<W>>,
+    pub resul: Signal<Out, Bits<O>>,
+    extendido: Signal<Local, Bits<O>>,
+    filas: [Signal<Local, Bits<O>>; W],
+    suma: [Signal<Local, Bits<O>>; W],
+    acarreo: [Signal<Local, Bits<O>>; W],
+    // i, para desplazar (Bits sólo se desplaza con literales o con Bits)
+    posiciones: [Constant<Bits<16>>; W],
+    // Unos por encima de los W bits de a
+    extension: Constant<Bits<O>>,
+    uno: Constant<Bits<O>>,
+    // 1 en complemento a 2, 0 sin signo
+    con_signo: Constant<Bit>,
+    modo: S,
+}
+
+impl<const W: usize, const O: usize, S: Signedness> WallaceMul<W, O, S> {
+    // Niveles de sumadores carry-save del árbol
+    pub const LEVELS: usize = {
+        let (mut filas, mut niveles) = (W, 0);
+        while filas > 2 {
+            filas = filas / 3 * 2 + filas % 3;
+            niveles += 1;
+        }
+        niveles
+    };
+}
+
+impl<const W: usize, const O: usize, S: Signedness> Default for WallaceMul<W, O, S> {
+    fn default() -> Self {
+        const {
+            assert!(W >= 3, "el árbol necesita al menos 3 filas");
+            assert!(O >= 2 * W, "el producto necesita al menos 2W bits");
+        }
+        Self {
+            a: Default::default(),
+            b: Default::default(),
+            resul: Default::default(),
+            extendido: Default::default(),
+            filas: std::array::from_fn(|_| Default::default()),
+            suma: std::array::from_fn(|_| Default::default()),
+            acarreo: std::array::from_fn(|_| Default::default()),
+            posiciones: std::array::from_fn(|i| Constant::new((i as u64).into())),
+            extension: Constant::new(!bit_cast::<O, W>(Bits::<W>::mask())),
+            uno: Constant::new(1.into()),
+            con_signo: Constant::new(S::SIGNED),
+            modo: Default::default(),
+        }
+    }
+}
+
+impl<const W: usize, const O: usize, S: Signedness> Logic for WallaceMul<W, O, S> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        // Extensión de a a O bits
+        // i = W - 1, el bit de signo: bucle de una vuelta, ver tfg_hdl_common
+        self.extendido.next = bit_cast::<O, W>(self.a.val());
+        for i in (W - 1)..W {
+            if self.con_signo.val() & self.a.val().get_bit(i) {
+                self.extendido.next = bit_cast::<O, W>(self.a.val()) | self.extension.val();
+            }
+        }
+
+        // Productos parciales
+        for i in 0..(W - 1) {
+            if self.b.val().get_bit(i) {
+                self.filas[i].next = self.extendido.val() << self.posiciones[i].val();
+            } else {
+                self.filas[i].next = 0.into();
+            }
+        }
+        for i in (W - 1)..W {
+            self.filas[i].next = 0.into();
+            if self.b.val().get_bit(i) {
+                if self.con_signo.val() {
+                    self.filas[i].next = !(self.extendido.val() << self.posiciones[i].val());
+                    self.filas[1].next = self.filas[1].val() | self.uno.val();
+                } else {
+                    self.filas[i].next = self.extendido.val() << self.posiciones[i].val();
+                }
+            }
+        }
+
+        // Sumadores carry-save
+        // W entra como `r`: bucle de una vuelta, ver tfg_hdl_common
+        for r in W..(W + 1) {
+            for t in 0..(W - 2) {
+                self.suma[t].next =
+                    self.filas[3 * t % r].val() ^ self.filas[(3 * t + 1) % r].val() ^ self.filas[(3 * t + 2) % r].val();
+                self.acarreo[t].next = ((self.filas[3 * t % r].val() & self.filas[(3 * t + 1) % r].val())
+                    | (self.filas[3 * t % r].val() & self.filas[(3 * t + 2) % r].val())
+                    | (self.filas[(3 * t + 1) % r].val() & self.filas[(3 * t + 2) % r].val()))
+                    << 1;
+                self.filas[2 * t % r].next = self.suma[t].val();
+                self.filas[(2 * t + 1) % r].next = self.acarreo[t].val();
+            }
+            // Las dos filas que quedan son las que ha añadido el último
+            // sumador, 3W - 6 y 3W - 5
+            self.resul.next = self.filas[(3 * r - 6) % r].val() + self.filas[(3 * r - 5) % r].val();
+        }
+        for t in (W - 2)..W {
+            self.suma[t].next = 0.into();
+            self.acarreo[t].next = 0.into();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mul::TwosComplement;
+
+    // Todos los pares de operandos de W bits (en la simulación de RustHDL;
+    // con Icarus lo prueba mul.rs)
+    fn todos_los_pares<const W: usize, const O: usize, S: Signedness>() {
+        let mut uut = WallaceMul::<W, O, S>::default();
+        uut.connect_all();
+
+        let mut sim = Simulation::<WallaceMul<W, O, S>>::new();
+        sim.add_testbench(move |mut ep: Sim<WallaceMul<W, O, S>>| {
+            let mut x = ep.init()?;
+            let (desde, hasta) = if S::SIGNED {
+                (-(1 << (W - 1)), 1 << (W - 1))
+            } else {
+                (0, 1 << W)
+            };
+            for va in desde..hasta {
+                for vb in desde..hasta {
+                    x.a.next = Bits::from(va as u64 & ((1 << W) - 1));
+                    x.b.next = Bits::from(vb as u64 & ((1 << W) - 1));
+                    x = ep.wait(1, x)?;
+                    sim_assert_eq!(ep, x.resul.val(), Bits::<O>::from((va * vb) as u64 & ((1 << O) - 1)), x);
+                }
+            }
+            ep.done(x)
+        });
+        sim.run(Box::new(uut), 1_000_000).unwrap();
+    }
+
+    #[test]
+    fn test_wallace_todas_las_anchuras_pequenas() {
+        todos_los_pares::<3, 6, Unsigned>();
+        todos_los_pares::<3, 6, TwosComplement>();
+        todos_los_pares::<4, 8, Unsigned>();
+        todos_los_pares::<4, 8, TwosComplement>();
+        todos_los_pares::<5, 10, TwosComplement>();
+        todos_los_pares::<6, 13, Unsigned>();
+        todos_los_pares::<6, 12, TwosComplement>();
+    }
+
+    #[test]
+    fn test_niveles_y_verilog() {
+        assert_eq!(WallaceMul::<3, 6>::LEVELS, 1);
+        assert_eq!(WallaceMul::<8, 16>::LEVELS, 4);
+        assert_eq!(WallaceMul::<16, 32>::LEVELS, 6);
+
+        let mut uut = WallaceMul::<8, 16, TwosComplement>::default();
+        uut.connect_all();
+        let verilog = generate_verilog(&uut);
+        // El sumador 2 toma las filas 6, 7 y 0 (la 8 de la cola) y deja las
+        // suyas en las posiciones 4 y 5
+        assert!(verilog.contains("suma$2 = filas$6 ^ filas$7 ^ filas$0;"), "{}", verilog);
+        assert!(verilog.contains("filas$5 = acarreo$2;"));
+        // Las dos últimas filas de 8 bits son la 18 y la 19
+        assert!(verilog.contains("resul = filas$2 + filas$3;"));
+    }
+}